@@ -0,0 +1,194 @@
+//! 阻塞式 HTTP 客户端
+//!
+//! 包装异步客户端，在内部的单线程 tokio 运行时上驱动请求，
+//! 适用于脚本和构建工具等非异步代码（类似 reqwest::blocking）。
+//! 不能在异步运行时内部调用，否则 tokio 会因嵌套运行时而 panic
+
+use crate::error::{Error, Result};
+use crate::request::{AsyncRequestBuilder, Method, Request, Version};
+use crate::response::Response;
+use bytes::Bytes;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+
+/// 阻塞式 HTTP 客户端，复用异步客户端的全部配置和连接逻辑
+#[derive(Clone)]
+pub struct HttpClient {
+    inner: crate::HttpClient,
+    runtime: Arc<Runtime>,
+}
+
+impl HttpClient {
+    /// 创建默认配置的阻塞式客户端
+    pub fn new() -> Result<Self> {
+        Self::from_client(crate::HttpClient::new())
+    }
+
+    /// 包装已配置好的异步客户端（如 `ClientBuilder::build()` 的结果）
+    pub fn from_client(client: crate::HttpClient) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| Error::other(format!("Failed to create runtime: {}", e)))?;
+
+        Ok(Self {
+            inner: client,
+            runtime: Arc::new(runtime),
+        })
+    }
+
+    /// 发送 GET 请求
+    pub fn get(&self, url: &str) -> RequestBuilder<'_> {
+        self.request(Method::GET, url)
+    }
+
+    /// 发送 POST 请求
+    pub fn post(&self, url: &str) -> RequestBuilder<'_> {
+        self.request(Method::POST, url)
+    }
+
+    /// 发送 PUT 请求
+    pub fn put(&self, url: &str) -> RequestBuilder<'_> {
+        self.request(Method::PUT, url)
+    }
+
+    /// 发送 DELETE 请求
+    pub fn delete(&self, url: &str) -> RequestBuilder<'_> {
+        self.request(Method::DELETE, url)
+    }
+
+    /// 发送 HEAD 请求
+    pub fn head(&self, url: &str) -> RequestBuilder<'_> {
+        self.request(Method::HEAD, url)
+    }
+
+    /// 发送 OPTIONS 请求，`url` 为 `*` 时针对 `base_url` 所在的整个服务器
+    pub fn options(&self, url: &str) -> RequestBuilder<'_> {
+        RequestBuilder {
+            inner: self.inner.options(url),
+            runtime: &self.runtime,
+        }
+    }
+
+    /// 发送指定方法的请求
+    pub fn request(&self, method: Method, url: &str) -> RequestBuilder<'_> {
+        RequestBuilder {
+            inner: self.inner.request(method, url),
+            runtime: &self.runtime,
+        }
+    }
+
+    /// 发送请求（直接发送Request对象）
+    pub fn send_request(&self, request: Request) -> Result<Response> {
+        self.runtime.block_on(self.inner.send_request(request))
+    }
+}
+
+/// 阻塞式请求构建器
+pub struct RequestBuilder<'a> {
+    inner: AsyncRequestBuilder<'a>,
+    runtime: &'a Runtime,
+}
+
+impl<'a> RequestBuilder<'a> {
+    /// 设置请求头
+    ///
+    /// 无效的请求头不会被设置，错误在 `send()` 时返回
+    pub fn header<K, V>(self, key: K, value: V) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.map(|builder| builder.header(key, value))
+    }
+
+    /// 设置多个请求头
+    pub fn headers<K, V, I>(self, headers: I) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+        I: IntoIterator<Item = (K, V)>,
+    {
+        self.map(|builder| builder.headers(headers))
+    }
+
+    /// 设置请求使用的 HTTP 版本
+    pub fn version(self, version: Version) -> Self {
+        self.map(|builder| builder.version(version))
+    }
+
+    /// 设置请求体
+    pub fn body<B: Into<Bytes>>(self, body: B) -> Self {
+        self.map(|builder| builder.body(body))
+    }
+
+    /// 读取文件内容作为请求体，并根据扩展名推断 Content-Type
+    pub fn file(self, path: impl AsRef<Path>) -> Self {
+        self.map(|builder| builder.file(path))
+    }
+
+    /// 设置JSON请求体
+    pub fn json<T: serde::Serialize>(self, data: &T) -> Result<Self> {
+        Ok(Self {
+            inner: self.inner.json(data)?,
+            runtime: self.runtime,
+        })
+    }
+
+    /// 对内部的异步请求构建器进行设置，用于本类型未直接提供的选项
+    pub fn map<F>(self, f: F) -> Self
+    where
+        F: FnOnce(AsyncRequestBuilder<'a>) -> AsyncRequestBuilder<'a>,
+    {
+        Self {
+            inner: f(self.inner),
+            runtime: self.runtime,
+        }
+    }
+
+    /// 构建请求（无效的请求头已被忽略）
+    pub fn build(self) -> Request {
+        self.inner.build()
+    }
+
+    /// 发送请求并阻塞直到收到响应
+    pub fn send(self) -> Result<Response> {
+        self.runtime.block_on(self.inner.send())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    #[test]
+    fn test_blocking_get() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buffer = vec![0u8; 4096];
+            let n = stream.read(&mut buffer).unwrap();
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 8\r\n\r\nblocking")
+                .unwrap();
+            String::from_utf8_lossy(&buffer[..n]).to_string()
+        });
+
+        let client = HttpClient::new().unwrap();
+        let response = client
+            .get(&format!("http://127.0.0.1:{}/sync", port))
+            .header("X-Mode", "blocking")
+            .send()
+            .unwrap();
+
+        assert_eq!(response.status_code, 200);
+        assert_eq!(response.body, b"blocking");
+        let request = server.join().unwrap();
+        assert!(request.starts_with("GET /sync HTTP/1.1\r\n"));
+        assert!(request.contains("X-Mode: blocking\r\n"));
+    }
+}
@@ -3,6 +3,7 @@
 //! 支持解析 Transfer-Encoding: chunked 的响应体
 
 use crate::error::{Result, Error};
+use crate::headers::HeaderMap;
 
 /// Chunked 传输编码解析器
 pub struct ChunkedParser;
@@ -16,13 +17,33 @@ impl ChunkedParser {
     /// # 返回
     /// 返回解析后的完整数据
     pub fn parse(data: &[u8]) -> Result<Vec<u8>> {
+        Self::parse_with_trailers(data).map(|(body, _)| body)
+    }
+
+    /// 解析 chunked 编码的数据，并返回结束 chunk 之后的 trailer headers
+    pub fn parse_with_trailers(data: &[u8]) -> Result<(Vec<u8>, HeaderMap)> {
+        Self::parse_inner(data, true)
+    }
+
+    /// 宽松解析可能被截断的 chunked 数据
+    ///
+    /// 返回截断位置之前已接收到的全部数据，不要求存在结束 chunk
+    pub fn parse_partial(data: &[u8]) -> Result<Vec<u8>> {
+        Self::parse_inner(data, false).map(|(body, _)| body)
+    }
+
+    fn parse_inner(data: &[u8], strict: bool) -> Result<(Vec<u8>, HeaderMap)> {
         let mut result = Vec::new();
+        let mut trailers = HeaderMap::new();
         let mut remaining = data;
 
         loop {
             // 找到第一个 \r\n 的位置
-            let line_end = remaining.windows(2).position(|w| w == b"\r\n")
-                .ok_or(Error::Response("Invalid chunked format: no CRLF found".to_string()))?;
+            let line_end = match remaining.windows(2).position(|w| w == b"\r\n") {
+                Some(pos) => pos,
+                None if !strict => break,
+                None => return Err(Error::Response("Invalid chunked format: no CRLF found".to_string())),
+            };
 
             // 解析 chunk 大小（可能包含扩展信息，如 "6;chunkext=val"）
             let size_line = &remaining[..line_end];
@@ -38,7 +59,16 @@ impl ChunkedParser {
 
             if chunk_size == 0 {
                 // 最后一个 chunk，检查是否有 trailer headers
-                Self::skip_trailer_headers(&mut remaining)?;
+                if strict {
+                    trailers = Self::parse_trailer_headers(&mut remaining)?;
+                }
+                break;
+            }
+
+            // 截断的 chunk：保留已接收的部分
+            if !strict && remaining.len() < chunk_size + 2 {
+                let available = remaining.len().min(chunk_size);
+                result.extend_from_slice(&remaining[..available]);
                 break;
             }
 
@@ -59,11 +89,47 @@ impl ChunkedParser {
             remaining = &remaining[chunk_size + 2..];
         }
 
-        Ok(result)
+        Ok((result, trailers))
     }
 
-    /// 跳过 trailer headers（如果存在）
-    fn skip_trailer_headers(data: &mut &[u8]) -> Result<()> {
+    /// 计算完整 chunked 数据的字节长度（包括结束 chunk 和 trailer）
+    ///
+    /// 数据尚不完整或格式无效时返回 None
+    pub fn complete_length(data: &[u8]) -> Option<usize> {
+        let mut offset = 0;
+
+        loop {
+            let remaining = &data[offset..];
+            let line_end = remaining.windows(2).position(|w| w == b"\r\n")?;
+            let size_line = String::from_utf8_lossy(&remaining[..line_end]);
+            let chunk_size_part = size_line.split(';').next().unwrap_or("").trim();
+            let chunk_size = usize::from_str_radix(chunk_size_part, 16).ok()?;
+            offset += line_end + 2;
+
+            if chunk_size == 0 {
+                // 跳过 trailer headers 直到空行
+                loop {
+                    let remaining = &data[offset..];
+                    let line_end = remaining.windows(2).position(|w| w == b"\r\n")?;
+                    offset += line_end + 2;
+                    if line_end == 0 {
+                        return Some(offset);
+                    }
+                }
+            }
+
+            offset = offset.checked_add(chunk_size)?.checked_add(2)?;
+            if offset > data.len() {
+                return None;
+            }
+        }
+    }
+
+    /// 解析 trailer headers（如果存在）
+    /// 无法通过校验的 trailer 会被忽略
+    fn parse_trailer_headers(data: &mut &[u8]) -> Result<HeaderMap> {
+        let mut trailers = HeaderMap::new();
+
         loop {
             // 找到下一个 \r\n
             let line_end = data.windows(2).position(|w| w == b"\r\n")
@@ -75,16 +141,15 @@ impl ChunkedParser {
                 break;
             }
 
-            let line = &data[..line_end];
-            if line.is_empty() {
-                *data = &data[2..];
-                break;
+            let line = String::from_utf8_lossy(&data[..line_end]);
+            if let Some((key, value)) = line.split_once(':') {
+                let _ = trailers.insert(key.trim(), value.trim());
             }
 
-            // 跳过这个 header 行
             *data = &data[line_end + 2..];
         }
-        Ok(())
+
+        Ok(trailers)
     }
 
     /// 检查是否为 chunked 传输编码
@@ -151,6 +216,32 @@ mod tests {
         assert!(ChunkedParser::parse(chunked_data).is_err());
     }
 
+    #[test]
+    fn test_parse_with_trailers() {
+        let chunked_data = b"5\r\nHello\r\n0\r\ngrpc-status: 0\r\nGrpc-Message: OK\r\n\r\n";
+        let (body, trailers) = ChunkedParser::parse_with_trailers(chunked_data).unwrap();
+        assert_eq!(body, b"Hello");
+        assert_eq!(trailers.len(), 2);
+        assert_eq!(trailers.get("grpc-status").unwrap(), "0");
+        assert_eq!(trailers.get("grpc-message").unwrap(), "OK");
+    }
+
+    #[test]
+    fn test_parse_partial_chunked() {
+        let chunked_data = b"6\r\nHello \r\n6\r\nWor";
+        assert!(ChunkedParser::parse(chunked_data).is_err());
+        let result = ChunkedParser::parse_partial(chunked_data).unwrap();
+        assert_eq!(String::from_utf8(result).unwrap(), "Hello Wor");
+    }
+
+    #[test]
+    fn test_complete_length() {
+        let chunked_data = b"6\r\nHello \r\n6\r\nWorld!\r\n0\r\n\r\nextra";
+        assert_eq!(ChunkedParser::complete_length(chunked_data), Some(chunked_data.len() - 5));
+        assert_eq!(ChunkedParser::complete_length(b"6\r\nHello \r\n6\r\nWor"), None);
+        assert_eq!(ChunkedParser::complete_length(b"0\r\nX-Trailer: a\r\n"), None);
+    }
+
     #[test]
     fn test_chunk_with_extensions() {
         // chunked with extension: "6;chunkext=val\r\nHello \r\n6\r\nWorld!\r\n0\r\n\r\n"
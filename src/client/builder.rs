@@ -2,26 +2,94 @@
 //!
 //! 提供 ClientBuilder 结构体用于构建 HTTP 客户端
 
-use crate::connection::ProxyConfig;
+use crate::connection::{ConnectOptions, Connector, MockResponse, MockTransport, ProxyConfig, TlsVersion, DEFAULT_MAX_HEADER_SIZE};
+use crate::connection::options::default_session_store;
+use crate::response::DEFAULT_MAX_HEADER_COUNT;
+use crate::decompression::Decoders;
 use crate::headers::HeaderMap;
-use crate::error::Result;
+use super::circuit::CircuitBreaker;
+use super::redirect::RedirectPolicy;
+use crate::headers::browser_headers::{Browser, FetchContext};
+use crate::error::{Error, Result};
+use rustls::client::danger::ServerCertVerifier;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::atomic::AtomicUsize;
+use std::sync::Arc;
+use std::time::Duration;
+use url::Url;
+
+/// 自动压缩请求体的最小字节数
+const AUTO_COMPRESS_THRESHOLD: usize = 1024;
 
 /// HTTP 客户端构建器
 /// 支持链式构建，类似 reqwest::Client::builder()
 #[derive(Debug, Clone)]
 pub struct ClientBuilder {
-    proxy_config: Option<ProxyConfig>,
+    proxy_chain: Vec<ProxyConfig>, // 依次经过的代理
     default_headers: HeaderMap,
+    host_headers: HashMap<String, HeaderMap>, // 按主机（小写）设置的默认请求头
     browser_headers_enabled: bool, // 是否启用浏览器请求头预设
+    browser: Browser, // 浏览器请求头预设类型
+    fetch_context: FetchContext, // 模拟的浏览器请求场景
+    allow_partial_response: bool, // 连接中断时是否返回部分响应
+    allow_http09: bool, // 是否接受没有状态行的 HTTP/0.9 响应
+    max_header_size: usize, // 响应头部大小上限
+    max_header_count: usize, // 响应头部行数上限
+    max_redirects: usize, // 最大重定向次数，0 表示不跟随重定向
+    keep_alive: Option<bool>, // 是否请求服务器保持连接，None 时只在跟随重定向时保持
+    max_retries: usize, // 连接失败时的最大重试次数
+    redirect_policy: RedirectPolicy, // 决定是否跟随某个重定向
+    proxy_bypass_localhost: bool, // 访问本地回环地址时是否绕过代理
+    https_only: bool, // 是否只允许 HTTPS 请求
+    user_agents: Vec<String>, // 轮换使用的 User-Agent 列表
+    connector: Option<Arc<dyn Connector>>, // 自定义连接器
+    connect_options: ConnectOptions, // 套接字级别的连接选项
+    circuit_breaker: Option<(usize, Duration)>, // 熔断阈值和冷却时间
+    auto_compress_request: bool, // 是否自动 gzip 压缩较大的请求体
+    sniff_compression: bool, // 是否根据魔数检测并解压未标注 Content-Encoding 的响应体
+    decoders: Decoders, // 启用的响应体解码器，决定通告的 Accept-Encoding
+    decompress: bool, // 是否自动解压响应体
+    accept_language: Option<String>, // 默认的 Accept-Language
+    timeout: Option<Duration>, // 单次请求的总超时时间
+    read_idle_timeout: Option<Duration>, // 读取响应时两次收到数据之间的最长空闲时间
+    vars: HashMap<String, String>, // 请求头中可引用的变量
+    base_url: Option<String>, // 相对路径请求使用的基础 URL
 }
 
 impl ClientBuilder {
     /// 创建新的客户端构建器
     pub fn new() -> Self {
         Self {
-            proxy_config: None,
+            proxy_chain: Vec::new(),
             default_headers: HeaderMap::new(),
+            host_headers: HashMap::new(),
             browser_headers_enabled: true, // 默认启用浏览器请求头
+            browser: Browser::default(),
+            fetch_context: FetchContext::default(),
+            allow_partial_response: false,
+            allow_http09: false,
+            max_header_size: DEFAULT_MAX_HEADER_SIZE,
+            max_header_count: DEFAULT_MAX_HEADER_COUNT,
+            max_redirects: 0,
+            keep_alive: None,
+            max_retries: 0,
+            redirect_policy: RedirectPolicy::default(),
+            proxy_bypass_localhost: true,
+            https_only: false,
+            user_agents: Vec::new(),
+            connector: None,
+            connect_options: ConnectOptions::default(),
+            circuit_breaker: None,
+            auto_compress_request: false,
+            sniff_compression: false,
+            decoders: Decoders::default(),
+            decompress: true,
+            accept_language: None,
+            timeout: None,
+            read_idle_timeout: None,
+            vars: HashMap::new(),
+            base_url: None,
         }
     }
 
@@ -31,43 +99,436 @@ impl ClientBuilder {
         self
     }
 
+    /// 设置发往指定主机的默认请求头，多次调用可为不同主机分别设置
+    ///
+    /// 主机名精确匹配（不区分大小写，不含端口）。合并顺序为：请求上设置的请求头优先，
+    /// 其次是该主机的请求头，最后是全局默认请求头；重定向到其他主机时不会重新合并
+    pub fn host_headers(mut self, host: impl Into<String>, headers: HeaderMap) -> Self {
+        self.host_headers.insert(host.into().to_ascii_lowercase(), headers);
+        self
+    }
+
+    /// 设置默认的 Accept-Language，覆盖浏览器预设和默认请求头中的值
+    ///
+    /// 请求上通过 `AsyncRequestBuilder::accept_language` 设置的值优先
+    pub fn accept_language(mut self, value: impl Into<String>) -> Self {
+        self.accept_language = Some(value.into());
+        self
+    }
+
     /// 设置代理配置
     pub fn proxy(mut self, config: ProxyConfig) -> Self {
-        self.proxy_config = Some(config);
+        self.proxy_chain = vec![config];
+        self
+    }
+
+    /// 设置代理链，请求依次经过每个代理
+    ///
+    /// 每个代理通过 CONNECT 连接到下一个代理，最后一个代理连接到目标服务器
+    pub fn proxy_chain(mut self, chain: Vec<ProxyConfig>) -> Self {
+        self.proxy_chain = chain;
+        self
+    }
+
+    /// 访问 localhost、127.0.0.1、::1 等回环地址时是否绕过代理直接连接（默认 true）
+    pub fn proxy_bypass_localhost(mut self, bypass: bool) -> Self {
+        self.proxy_bypass_localhost = bypass;
+        self
+    }
+
+    /// 设置轮换使用的 User-Agent 列表
+    ///
+    /// 每个请求按轮询顺序选取一个 User-Agent，覆盖浏览器预设和默认请求头中的 User-Agent，
+    /// 请求上显式设置的 User-Agent 不受影响。
+    /// `browser_headers::user_agents` 中的常量可作为默认列表
+    pub fn user_agents(mut self, user_agents: Vec<String>) -> Self {
+        self.user_agents = user_agents;
+        self
+    }
+
+    /// 设置本地绑定地址，所有出站连接（包括到代理的连接）都从该地址发出
+    pub fn local_address(mut self, address: IpAddr) -> Self {
+        self.connect_options = self.connect_options.local_address(address);
+        self
+    }
+
+    /// 设置自定义服务器证书校验器，直接接入 rustls
+    ///
+    /// 设置后所有 TLS 握手（包括到 HTTPS 代理的握手）都由该校验器决定是否信任证书，
+    /// 内置根证书不再使用，并覆盖其他证书相关的配置
+    pub fn custom_cert_verifier(mut self, verifier: Arc<dyn ServerCertVerifier>) -> Self {
+        self.connect_options = self.connect_options.cert_verifier(verifier);
+        self
+    }
+
+    /// 设置 TLS 握手时向目标服务器通告的 ALPN 协议（按优先级排列），默认不通告
+    ///
+    /// 协商结果可通过 `Response::alpn_protocol` 获取；客户端仍按 HTTP/1.1 收发数据
+    pub fn alpn_protocols(mut self, protocols: Vec<Vec<u8>>) -> Self {
+        self.connect_options = self.connect_options.alpn_protocols(protocols);
+        self
+    }
+
+    /// 设置是否在连接之间共享 TLS 会话缓存（默认开启）
+    ///
+    /// 开启时再次连接同一服务器会恢复之前的 TLS 会话（session ticket），跳过完整握手以降低延迟
+    pub fn tls_session_cache(mut self, enabled: bool) -> Self {
+        let store = match self.connect_options.session_store.take() {
+            Some(store) if enabled => Some(store),
+            _ => enabled.then(default_session_store),
+        };
+        self.connect_options = self.connect_options.session_store(store);
+        self
+    }
+
+    /// 设置允许协商的最低 TLS 版本
+    pub fn min_tls_version(mut self, version: TlsVersion) -> Self {
+        self.connect_options = self.connect_options.min_tls_version(version);
+        self
+    }
+
+    /// 设置允许协商的最高 TLS 版本
+    pub fn max_tls_version(mut self, version: TlsVersion) -> Self {
+        self.connect_options = self.connect_options.max_tls_version(version);
+        self
+    }
+
+    /// 设置是否启用 Happy Eyeballs（默认开启）
+    ///
+    /// 开启时域名同时解析到 IPv6 和 IPv4 地址时交错发起连接尝试，使用最先建立的连接，
+    /// 避免 IPv6 不可达的网络上连接缓慢；关闭后按解析顺序逐个尝试
+    pub fn happy_eyeballs(mut self, enabled: bool) -> Self {
+        self.connect_options = self.connect_options.happy_eyeballs(enabled);
+        self
+    }
+
+    /// 设置是否只允许 HTTPS 请求（默认 false）
+    ///
+    /// 开启后发往 http:// 的请求（包括重定向）返回错误，不能与明文连接的 HTTP 代理同时使用
+    pub fn https_only(mut self, enabled: bool) -> Self {
+        self.https_only = enabled;
         self
     }
 
+    /// 设置自定义连接器，替代默认的 TCP/TLS 连接
+    pub fn connector(mut self, connector: Arc<dyn Connector>) -> Self {
+        self.connector = Some(connector);
+        self
+    }
+
+    /// 使用模拟传输层，按请求方法和 URL 返回预设响应，不访问网络
+    pub fn with_mock(self, responses: Vec<MockResponse>) -> Self {
+        self.connector(Arc::new(MockTransport::new(responses)))
+    }
+
     /// 启用或禁用浏览器请求头预设
     pub fn browser_headers(mut self, enabled: bool) -> Self {
         self.browser_headers_enabled = enabled;
         self
     }
 
+    /// 使用指定浏览器的请求头预设模拟该浏览器（默认 Chrome）
+    pub fn impersonate(mut self, browser: Browser) -> Self {
+        self.browser = browser;
+        self.browser_headers_enabled = true;
+        self
+    }
+
+    /// 设置模拟的浏览器请求场景（默认 `Navigate`），调整预设中的 Sec-Fetch-* 和 Accept
+    ///
+    /// 调用接口时使用 `Cors` 或 `Api`，避免在 XHR/fetch 风格的请求中发送页面导航的请求头
+    pub fn fetch_context(mut self, context: FetchContext) -> Self {
+        self.fetch_context = context;
+        self
+    }
+
     /// 禁用浏览器请求头预设
     pub fn no_browser_headers(mut self) -> Self {
         self.browser_headers_enabled = false;
         self
     }
 
+    /// 读取响应体时连接中断，是否返回已接收的部分响应（默认返回错误）
+    ///
+    /// 部分响应可通过 `Response::is_complete()` 识别
+    pub fn allow_partial_response(mut self, allow: bool) -> Self {
+        self.allow_partial_response = allow;
+        self
+    }
+
+    /// 是否接受没有状态行的 HTTP/0.9 响应（默认 false）
+    ///
+    /// 启用后，不以 `HTTP/` 开头的响应会读取到连接关闭，整体作为状态码 200、版本 `HTTP/0.9` 的响应体，
+    /// 用于一些老旧或嵌入式设备
+    pub fn allow_http09(mut self, allow: bool) -> Self {
+        self.allow_http09 = allow;
+        self
+    }
+
+    /// 设置响应头部大小上限（默认 64 KiB）
+    ///
+    /// 头部超过上限仍未结束时返回错误，防止服务器发送超大头部耗尽内存
+    pub fn max_header_size(mut self, max_header_size: usize) -> Self {
+        self.max_header_size = max_header_size;
+        self
+    }
+
+    /// 设置响应头部行数上限（默认 100）
+    ///
+    /// 与 `max_header_size` 互补，防止服务器发送大量细小头部；超过上限时返回 `Error::HttpParse`
+    pub fn max_header_count(mut self, max_header_count: usize) -> Self {
+        self.max_header_count = max_header_count;
+        self
+    }
+
+    /// 启用按主机的熔断器
+    ///
+    /// 同一主机连续 `threshold` 次失败（连接错误或 5xx 响应）后，`cooldown` 时间内发往该主机的请求
+    /// 直接返回 `Error::CircuitOpen`；冷却结束后放行一个试探请求
+    pub fn circuit_breaker(mut self, threshold: usize, cooldown: Duration) -> Self {
+        self.circuit_breaker = Some((threshold, cooldown));
+        self
+    }
+
+    /// 请求体不小于 1 KiB 时是否自动使用 gzip 压缩并设置 `Content-Encoding: gzip`（默认 false）
+    ///
+    /// 已设置 Content-Encoding 的请求不会被再次压缩
+    pub fn auto_compress_request(mut self, enabled: bool) -> Self {
+        self.auto_compress_request = enabled;
+        self
+    }
+
+    /// 是否启用 gzip 解码器（默认 true），关闭后不通告也不自动解压 gzip
+    pub fn gzip(mut self, enabled: bool) -> Self {
+        self.decoders.gzip = enabled;
+        self
+    }
+
+    /// 是否启用 deflate 解码器（默认 true），关闭后不通告也不自动解压 deflate
+    pub fn deflate(mut self, enabled: bool) -> Self {
+        self.decoders.deflate = enabled;
+        self
+    }
+
+    /// 是否启用 brotli 解码器（默认 true），关闭后不通告也不自动解压 br
+    pub fn brotli(mut self, enabled: bool) -> Self {
+        self.decoders.brotli = enabled;
+        self
+    }
+
+    /// 是否自动解压响应体（默认 true）
+    ///
+    /// 关闭后仍按启用的解码器通告 Accept-Encoding，响应体保持服务器发送的压缩字节，
+    /// Content-Encoding 头保持不变，可用于存储或原样转发压缩内容
+    pub fn decompress(mut self, enabled: bool) -> Self {
+        self.decompress = enabled;
+        self
+    }
+
+    /// 响应没有可识别的 Content-Encoding 时，是否根据响应体开头的魔数检测并解压 gzip/zlib 数据（默认 false）
+    ///
+    /// 用于压缩了响应体却没有正确标注 Content-Encoding 的服务器；解压失败时保留原始响应体
+    pub fn sniff_compression(mut self, enabled: bool) -> Self {
+        self.sniff_compression = enabled;
+        self
+    }
+
+    /// 设置基础 URL，`get`/`post` 等方法的相对路径参数会相对于它解析
+    ///
+    /// 按 RFC 3986 解析：基础 URL 通常应以 `/` 结尾（如 `https://api.example.com/v1/`），
+    /// 否则最后一段路径会被替换；传入绝对 URL 的请求不受影响
+    pub fn base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_url = Some(url.into());
+        self
+    }
+
+    /// 注册请求头变量，请求头值中的 `{name}` 在发送时替换为变量值
+    ///
+    /// 注册变量后，请求头中引用未注册的变量会在发送时返回错误；字面量花括号写作 `{{` 和 `}}`
+    pub fn var(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.vars.insert(name.into(), value.into());
+        self
+    }
+
+    /// 设置请求的总超时时间（默认不超时）
+    ///
+    /// 超时覆盖整个请求过程：域名解析、TCP 连接、TLS 握手、发送请求、读取响应以及跟随重定向，
+    /// 超时后返回 `Error::Timeout` 并关闭连接
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// 设置读取响应时的空闲超时（默认不限制）
+    ///
+    /// 与总超时 `timeout` 相互独立：每次读取等待超过该时间仍未收到任何数据时放弃请求并返回 `Error::Timeout`，
+    /// 用于及时中止停滞的服务器；持续缓慢发送数据的响应不受影响。使用自定义连接器时不生效
+    pub fn read_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.read_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// 设置最大重定向跟随次数（默认 0，不跟随重定向）
+    ///
+    /// 重定向到同一来源（协议、主机、端口相同）时复用已有连接（见 `keep_alive`）
+    pub fn max_redirects(mut self, max_redirects: usize) -> Self {
+        self.max_redirects = max_redirects;
+        self
+    }
+
+    /// 设置是否请求服务器保持连接（默认只在 `max_redirects` 大于 0 时保持，以便同源重定向复用连接）
+    ///
+    /// 开启时把 `Request::new` 内置的 `Connection: close` 替换为 `keep-alive`，显式设置的 Connection 保持不变；
+    /// 服务器同意保持的连接在响应完整接收后放回连接池，供之后发往同一来源的请求复用；
+    /// 关闭时不替换，同源重定向和之后的请求都使用新连接
+    pub fn keep_alive(mut self, enabled: bool) -> Self {
+        self.keep_alive = Some(enabled);
+        self
+    }
+
+    /// 设置连接失败（I/O 或连接错误）或收到 429/503 响应时的最大重试次数（默认 0，不重试）
+    ///
+    /// 只重试幂等方法以及通过 `idempotency_key` 标记为可重试的请求。429/503 响应带有 Retry-After 时
+    /// 按其等待（超过 60 秒时不再重试，直接返回该响应），否则按指数退避等待（100 毫秒起，每次翻倍）；
+    /// 其他响应（包括其余 5xx）不会触发重试
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// 设置重定向策略（默认跟随所有重定向），与 `max_redirects` 配合使用
+    ///
+    /// 重定向目标被策略拒绝时返回错误，不发送请求
+    pub fn redirect_policy(mut self, policy: RedirectPolicy) -> Self {
+        self.redirect_policy = policy;
+        self
+    }
+
+    /// 检查相互冲突或无意义的配置，避免构建出行为出人意料的客户端
+    fn validate(&self) -> Result<()> {
+        if self.connector.is_some() {
+            // 自定义连接器替代内置的 TCP/TLS 连接，以下配置都不会生效
+            if !self.proxy_chain.is_empty() {
+                return Err(Error::other("custom connector cannot be combined with proxy settings"));
+            }
+            if self.connect_options.local_address.is_some() {
+                return Err(Error::other("custom connector cannot be combined with local_address"));
+            }
+            if self.connect_options.cert_verifier.is_some() {
+                return Err(Error::other("custom connector cannot be combined with custom_cert_verifier"));
+            }
+            if !self.connect_options.alpn_protocols.is_empty() {
+                return Err(Error::other("custom connector cannot be combined with alpn_protocols"));
+            }
+            if self.connect_options.min_tls_version.is_some() || self.connect_options.max_tls_version.is_some() {
+                return Err(Error::other("custom connector cannot be combined with TLS version settings"));
+            }
+        }
+        if let (Some(min), Some(max)) = (self.connect_options.min_tls_version, self.connect_options.max_tls_version)
+            && min > max
+        {
+            return Err(Error::other(format!("min_tls_version {:?} is above max_tls_version {:?}", min, max)));
+        }
+        // 到明文 HTTP 代理的连接会暴露代理认证和 CONNECT 目标
+        if self.https_only && self.proxy_chain.iter().any(|proxy| !proxy.proxy_tls) {
+            return Err(Error::other("https_only cannot be combined with a plain-http proxy"));
+        }
+        if let Some((threshold, _)) = self.circuit_breaker {
+            if threshold == 0 {
+                return Err(Error::other("circuit_breaker threshold must be greater than zero"));
+            }
+            // 熔断器打开后的重试直接返回 Error::CircuitOpen，超出的重试次数永远不会执行
+            if self.max_retries >= threshold {
+                return Err(Error::other("max_retries must be less than the circuit_breaker threshold"));
+            }
+        }
+        if self.timeout == Some(Duration::ZERO) {
+            return Err(Error::other("timeout must be greater than zero"));
+        }
+        if self.read_idle_timeout == Some(Duration::ZERO) {
+            return Err(Error::other("read_idle_timeout must be greater than zero"));
+        }
+        if self.max_header_size == 0 {
+            return Err(Error::other("max_header_size must be greater than zero"));
+        }
+        if self.max_header_count == 0 {
+            return Err(Error::other("max_header_count must be greater than zero"));
+        }
+        if self.sniff_compression && !self.decompress {
+            return Err(Error::other("sniff_compression cannot be combined with decompress(false)"));
+        }
+        Ok(())
+    }
+
     /// 构建 HTTP 客户端
     pub fn build(self) -> Result<super::model::HttpClient> {
+        self.validate()?;
+
+        let base_url = self
+            .base_url
+            .map(|url| Url::parse(&url).map_err(|e| Error::url_parse(format!("Invalid base URL {}: {}", url, e))))
+            .transpose()?;
+
         // 确保 crypto provider 已初始化
         crate::tls::init_crypto_provider()?;
 
         let mut client = super::model::HttpClient {
-            proxy_config: self.proxy_config,
+            proxy_chain: self.proxy_chain,
             default_headers: self.default_headers,
+            host_headers: self.host_headers,
+            allow_partial_response: self.allow_partial_response,
+            allow_http09: self.allow_http09,
+            max_header_size: self.max_header_size,
+            max_header_count: self.max_header_count,
+            max_redirects: self.max_redirects,
+            keep_alive: self.keep_alive,
+            max_retries: self.max_retries,
+            redirect_policy: self.redirect_policy,
+            proxy_bypass_localhost: self.proxy_bypass_localhost,
+            https_only: self.https_only,
+            user_agents: Arc::from(self.user_agents),
+            user_agent_index: Arc::new(AtomicUsize::new(0)),
+            connector: self.connector,
+            connect_options: self.connect_options,
+            circuit_breaker: self
+                .circuit_breaker
+                .map(|(threshold, cooldown)| Arc::new(CircuitBreaker::new(threshold, cooldown))),
+            auto_compress_threshold: self.auto_compress_request.then_some(AUTO_COMPRESS_THRESHOLD),
+            sniff_compression: self.sniff_compression,
+            decoders: if self.decompress { self.decoders } else { Decoders::none() },
+            timeout: self.timeout,
+            read_idle_timeout: self.read_idle_timeout,
+            vars: Arc::new(self.vars),
+            base_url,
+            pool: Arc::default(),
+            header_order: Arc::from(Vec::new()),
         };
 
+        if let Some(accept_language) = self.accept_language {
+            client.default_headers.insert("Accept-Language", accept_language)?;
+        }
+
         // 如果启用了浏览器请求头，将其添加到默认请求头中
         if self.browser_headers_enabled {
-            let browser_headers = crate::headers::browser_headers::chrome();
+            let browser_headers = self.browser.headers_for(self.fetch_context);
+            // 按预设顺序发送请求头，与真实浏览器一致
+            client.header_order = browser_headers.iter().map(|(key, _)| key.to_ascii_lowercase()).collect();
             for (key, value) in browser_headers {
+                // 预设中的 Accept-Encoding 由启用的解码器生成，避免通告无法解压的编码
+                if key.eq_ignore_ascii_case("accept-encoding") {
+                    continue;
+                }
                 if !client.default_headers.contains_key(&key.to_lowercase()) {
                     // 忽略插入失败的错误，继续处理其他请求头
                     let _ = client.default_headers.insert(key, value);
                 }
             }
+
+            if !client.default_headers.contains_key("accept-encoding")
+                && let Some(accept_encoding) = self.decoders.accept_encoding()
+            {
+                client.default_headers.insert("Accept-Encoding", accept_encoding)?;
+            }
         }
 
         Ok(client)
@@ -79,3 +540,131 @@ impl Default for ClientBuilder {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_rejects_conflicting_options() {
+        let error = ClientBuilder::new()
+            .with_mock(Vec::new())
+            .proxy(ProxyConfig::http("127.0.0.1", 8080))
+            .build()
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("proxy settings"), "{}", error);
+
+        let error = ClientBuilder::new()
+            .with_mock(Vec::new())
+            .local_address("127.0.0.1".parse().unwrap())
+            .build()
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("local_address"), "{}", error);
+
+        let error = ClientBuilder::new().timeout(Duration::ZERO).build().err().unwrap();
+        assert!(error.to_string().contains("timeout"), "{}", error);
+
+        let error = ClientBuilder::new().read_idle_timeout(Duration::ZERO).build().err().unwrap();
+        assert!(error.to_string().contains("read_idle_timeout"), "{}", error);
+
+        let error = ClientBuilder::new().max_header_size(0).build().err().unwrap();
+        assert!(error.to_string().contains("max_header_size"), "{}", error);
+
+        let error = ClientBuilder::new().max_header_count(0).build().err().unwrap();
+        assert!(error.to_string().contains("max_header_count"), "{}", error);
+
+        let error = ClientBuilder::new().sniff_compression(true).decompress(false).build().err().unwrap();
+        assert!(error.to_string().contains("sniff_compression"), "{}", error);
+    }
+
+    #[test]
+    fn test_build_rejects_https_only_with_plain_http_proxy() {
+        let error = ClientBuilder::new()
+            .https_only(true)
+            .proxy(ProxyConfig::http("127.0.0.1", 8080))
+            .build()
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("https_only"), "{}", error);
+
+        assert!(ClientBuilder::new().https_only(true).proxy(ProxyConfig::https("127.0.0.1", 8443)).build().is_ok());
+    }
+
+    #[test]
+    fn test_build_rejects_inverted_tls_versions() {
+        let error = ClientBuilder::new()
+            .min_tls_version(TlsVersion::Tls13)
+            .max_tls_version(TlsVersion::Tls12)
+            .build()
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("min_tls_version"), "{}", error);
+
+        let error = ClientBuilder::new().with_mock(Vec::new()).max_tls_version(TlsVersion::Tls12).build().err().unwrap();
+        assert!(error.to_string().contains("TLS version"), "{}", error);
+
+        assert!(ClientBuilder::new()
+            .min_tls_version(TlsVersion::Tls12)
+            .max_tls_version(TlsVersion::Tls12)
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_build_rejects_unusable_retry_settings() {
+        let cooldown = Duration::from_secs(10);
+        let error = ClientBuilder::new().max_retries(3).circuit_breaker(3, cooldown).build().err().unwrap();
+        assert!(error.to_string().contains("max_retries"), "{}", error);
+
+        let error = ClientBuilder::new().circuit_breaker(0, cooldown).build().err().unwrap();
+        assert!(error.to_string().contains("threshold"), "{}", error);
+
+        assert!(ClientBuilder::new().max_retries(2).circuit_breaker(3, cooldown).build().is_ok());
+    }
+
+    #[test]
+    fn test_impersonate_serializes_headers_in_browser_order() {
+        for browser in [Browser::Chrome, Browser::Firefox] {
+            let client = ClientBuilder::new().impersonate(browser).build().unwrap();
+            let preview = String::from_utf8(client.get("http://example.com/").preview().unwrap()).unwrap();
+            let names: Vec<String> = preview
+                .split("\r\n")
+                .skip(2)
+                .take_while(|line| !line.is_empty())
+                .map(|line| line.split_once(':').unwrap().0.to_ascii_lowercase())
+                .collect();
+            let expected: Vec<String> = browser.headers().iter().map(|(key, _)| key.to_ascii_lowercase()).collect();
+            assert!(preview.starts_with("GET / HTTP/1.1\r\nHost: example.com\r\n"), "{}", preview);
+            assert_eq!(names[..expected.len()], expected[..], "{}", preview);
+        }
+    }
+
+    #[test]
+    fn test_fetch_context_cors_headers() {
+        let client = ClientBuilder::new().fetch_context(FetchContext::Cors).build().unwrap();
+        let header = |name: &str| client.default_headers.get(name).map(String::as_str);
+        assert_eq!(header("sec-fetch-dest"), Some("empty"));
+        assert_eq!(header("sec-fetch-mode"), Some("cors"));
+        assert_eq!(header("sec-fetch-site"), Some("cross-site"));
+        assert_eq!(header("accept"), Some("*/*"));
+        assert_eq!(header("sec-fetch-user"), None);
+        assert_eq!(header("upgrade-insecure-requests"), None);
+        assert!(header("sec-ch-ua").is_some());
+
+        let client = ClientBuilder::new().impersonate(Browser::Firefox).build().unwrap();
+        assert_eq!(client.default_headers.get("sec-fetch-mode").map(String::as_str), Some("navigate"));
+        assert_eq!(client.default_headers.get("sec-fetch-user").map(String::as_str), Some("?1"));
+    }
+
+    #[test]
+    fn test_build_accepts_compatible_options() {
+        assert!(ClientBuilder::new().with_mock(Vec::new()).timeout(Duration::from_secs(1)).build().is_ok());
+        assert!(ClientBuilder::new()
+            .proxy(ProxyConfig::http("127.0.0.1", 8080))
+            .local_address("127.0.0.1".parse().unwrap())
+            .build()
+            .is_ok());
+    }
+}
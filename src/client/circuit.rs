@@ -0,0 +1,157 @@
+//! 按主机的熔断器
+//!
+//! 连续失败达到阈值后在冷却期内直接拒绝发往该主机的请求，
+//! 冷却期结束后放行一个试探请求，成功则恢复，失败则重新熔断
+
+use crate::error::{Error, Result};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 单个主机的熔断状态
+#[derive(Debug, Default)]
+struct HostState {
+    /// 连续失败次数
+    consecutive_failures: usize,
+    /// 熔断开始时间，None 表示未熔断
+    opened_at: Option<Instant>,
+    /// 冷却期结束后是否已有试探请求在进行
+    trial_in_flight: bool,
+}
+
+/// 按 (主机, 端口) 统计连续失败的熔断器
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    threshold: usize,
+    cooldown: Duration,
+    hosts: Mutex<HashMap<(String, u16), HostState>>,
+}
+
+impl CircuitBreaker {
+    /// 创建熔断器
+    ///
+    /// `threshold` 为触发熔断的连续失败次数，`cooldown` 为熔断持续时间
+    pub fn new(threshold: usize, cooldown: Duration) -> Self {
+        Self {
+            threshold: threshold.max(1),
+            cooldown,
+            hosts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 检查是否允许向指定主机发送请求，熔断中返回 `Error::CircuitOpen`
+    ///
+    /// 冷却期结束后放行的试探请求会得到一个许可，请求结束前应持有该许可；
+    /// 许可在记录结果之前被丢弃时（如请求超时或被取消）释放试探名额，下一个请求可以重新试探
+    pub fn check(&self, host: &str, port: u16) -> Result<CircuitPermit<'_>> {
+        let mut hosts = self.hosts.lock().unwrap();
+        let key = (host.to_string(), port);
+        let Some(state) = hosts.get_mut(&key) else {
+            return Ok(CircuitPermit { breaker: self, trial: None });
+        };
+        let Some(opened_at) = state.opened_at else {
+            return Ok(CircuitPermit { breaker: self, trial: None });
+        };
+
+        // 冷却期结束后只放行一个试探请求
+        if opened_at.elapsed() < self.cooldown || state.trial_in_flight {
+            return Err(Error::circuit_open(host, port));
+        }
+        state.trial_in_flight = true;
+        Ok(CircuitPermit { breaker: self, trial: Some(key) })
+    }
+
+    /// 记录一次成功请求，重置该主机的熔断状态
+    pub fn record_success(&self, host: &str, port: u16) {
+        self.hosts.lock().unwrap().remove(&(host.to_string(), port));
+    }
+
+    /// 记录一次失败请求，连续失败达到阈值（或试探请求失败）时开始熔断
+    pub fn record_failure(&self, host: &str, port: u16) {
+        let mut hosts = self.hosts.lock().unwrap();
+        let state = hosts.entry((host.to_string(), port)).or_default();
+        state.consecutive_failures += 1;
+        if state.trial_in_flight || state.consecutive_failures >= self.threshold {
+            state.opened_at = Some(Instant::now());
+            state.trial_in_flight = false;
+        }
+    }
+}
+
+/// `CircuitBreaker::check` 放行请求的许可，丢弃试探请求的许可时释放试探名额
+#[must_use]
+#[derive(Debug)]
+pub struct CircuitPermit<'a> {
+    breaker: &'a CircuitBreaker,
+    /// 试探请求对应的主机，普通请求为 None
+    trial: Option<(String, u16)>,
+}
+
+impl Drop for CircuitPermit<'_> {
+    fn drop(&mut self) {
+        let Some(key) = self.trial.take() else {
+            return;
+        };
+        // 已记录结果时状态已被重置或移除，此时只是重复清除标记
+        if let Some(state) = self.breaker.hosts.lock().unwrap().get_mut(&key) {
+            state.trial_in_flight = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opens_after_threshold() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(60));
+        assert!(breaker.check("example.com", 80).is_ok());
+        breaker.record_failure("example.com", 80);
+        assert!(breaker.check("example.com", 80).is_ok());
+        breaker.record_failure("example.com", 80);
+
+        assert!(breaker.check("example.com", 80).is_err());
+        // 其他主机不受影响
+        assert!(breaker.check("example.com", 443).is_ok());
+    }
+
+    #[test]
+    fn test_success_resets_failures() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(60));
+        breaker.record_failure("example.com", 80);
+        breaker.record_success("example.com", 80);
+        breaker.record_failure("example.com", 80);
+        assert!(breaker.check("example.com", 80).is_ok());
+    }
+
+    #[test]
+    fn test_single_trial_after_cooldown() {
+        let breaker = CircuitBreaker::new(1, Duration::ZERO);
+        breaker.record_failure("example.com", 80);
+
+        let trial = breaker.check("example.com", 80).unwrap();
+        assert!(breaker.check("example.com", 80).is_err());
+
+        // 试探失败重新熔断，试探成功恢复
+        breaker.record_failure("example.com", 80);
+        drop(trial);
+        let trial = breaker.check("example.com", 80).unwrap();
+        breaker.record_success("example.com", 80);
+        drop(trial);
+        let _first = breaker.check("example.com", 80).unwrap();
+        let _second = breaker.check("example.com", 80).unwrap();
+    }
+
+    #[test]
+    fn test_dropped_trial_releases_slot() {
+        let breaker = CircuitBreaker::new(1, Duration::ZERO);
+        breaker.record_failure("example.com", 80);
+
+        // 试探请求未记录结果就被放弃（如超时），之后的请求可以重新试探
+        let trial = breaker.check("example.com", 80).unwrap();
+        assert!(breaker.check("example.com", 80).is_err());
+        drop(trial);
+        assert!(breaker.check("example.com", 80).is_ok());
+    }
+}
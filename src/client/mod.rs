@@ -3,8 +3,13 @@
 //! 提供HTTP客户端的功能，支持异步请求发送
 
 pub mod builder;
+pub mod circuit;
 pub mod model;
+mod pool;
+pub mod redirect;
 
 // 导出主要类型
 pub use builder::ClientBuilder;
+pub use circuit::{CircuitBreaker, CircuitPermit};
 pub use model::HttpClient;
+pub use redirect::RedirectPolicy;
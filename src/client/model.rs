@@ -2,18 +2,63 @@
 //!
 //! 包含HttpClient结构体的定义和实现
 
-use crate::error::Result;
-use crate::request::{Method, Request, AsyncRequestBuilder};
-use crate::utils::{parse_host_port, ParsedUrl};
-use crate::connection::{AsyncConnection, AsyncHttpConnection, ProxyConfig};
-use crate::response::Response;
+use crate::error::{Error, Result};
+use crate::request::{Method, Request, RequestTarget, AsyncRequestBuilder};
+use crate::utils::{parse_host_port, resolve_url, ParsedUrl};
+use crate::connection::{AsyncConnection, AsyncHttpConnection, ConnectOptions, Connector, ProxyConfig, DEFAULT_MAX_HEADER_SIZE};
+use crate::response::{Response, DEFAULT_MAX_HEADER_COUNT};
 use crate::headers::HeaderMap;
+use crate::decompression::{compress, Compression, Decoders};
+use crate::timings::Timings;
+use crate::websocket::{self, WebSocketUpgrade};
+use super::circuit::{CircuitBreaker, CircuitPermit};
+use super::pool::ConnectionPool;
+use super::redirect::RedirectPolicy;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use std::future::Future;
+use bytes::Bytes;
+use tokio::sync::mpsc;
+
+/// 连接失败重试的初始退避时间
+const RETRY_BACKOFF: Duration = Duration::from_millis(100);
+
+/// 按 Retry-After 等待后重试的最长时间，超过时直接返回响应
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
 
 /// HTTP 客户端结构体
 #[derive(Clone)]
 pub struct HttpClient {
-    pub(crate) proxy_config: Option<ProxyConfig>,
+    pub(crate) proxy_chain: Vec<ProxyConfig>,
     pub(crate) default_headers: HeaderMap,
+    pub(crate) host_headers: HashMap<String, HeaderMap>,
+    pub(crate) allow_partial_response: bool,
+    pub(crate) allow_http09: bool,
+    pub(crate) max_header_size: usize,
+    pub(crate) max_header_count: usize,
+    pub(crate) max_redirects: usize,
+    pub(crate) keep_alive: Option<bool>,
+    pub(crate) max_retries: usize,
+    pub(crate) redirect_policy: RedirectPolicy,
+    pub(crate) proxy_bypass_localhost: bool,
+    pub(crate) https_only: bool,
+    pub(crate) user_agents: Arc<[String]>,
+    pub(crate) user_agent_index: Arc<AtomicUsize>,
+    pub(crate) connector: Option<Arc<dyn Connector>>,
+    pub(crate) connect_options: ConnectOptions,
+    pub(crate) circuit_breaker: Option<Arc<CircuitBreaker>>,
+    pub(crate) auto_compress_threshold: Option<usize>,
+    pub(crate) sniff_compression: bool,
+    pub(crate) decoders: Decoders,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) read_idle_timeout: Option<Duration>,
+    pub(crate) vars: Arc<HashMap<String, String>>,
+    pub(crate) base_url: Option<url::Url>,
+    pub(crate) pool: Arc<ConnectionPool>,
+    /// 浏览器预设的请求头发送顺序（小写名称）
+    pub(crate) header_order: Arc<[String]>,
 }
 
 impl HttpClient {
@@ -23,8 +68,33 @@ impl HttpClient {
         let _ = crate::tls::init_crypto_provider();
 
         Self {
-            proxy_config: None,
+            proxy_chain: Vec::new(),
             default_headers: HeaderMap::new(),
+            host_headers: HashMap::new(),
+            allow_partial_response: false,
+            allow_http09: false,
+            max_header_size: DEFAULT_MAX_HEADER_SIZE,
+            max_header_count: DEFAULT_MAX_HEADER_COUNT,
+            max_redirects: 0,
+            keep_alive: None,
+            max_retries: 0,
+            redirect_policy: RedirectPolicy::default(),
+            proxy_bypass_localhost: true,
+            https_only: false,
+            user_agents: Arc::from(Vec::new()),
+            user_agent_index: Arc::new(AtomicUsize::new(0)),
+            connector: None,
+            connect_options: ConnectOptions::default(),
+            circuit_breaker: None,
+            auto_compress_threshold: None,
+            sniff_compression: false,
+            decoders: Decoders::default(),
+            timeout: None,
+            read_idle_timeout: None,
+            vars: Arc::new(HashMap::new()),
+            base_url: None,
+            pool: Arc::default(),
+            header_order: Arc::from(Vec::new()),
         }
     }
 
@@ -35,80 +105,2163 @@ impl HttpClient {
 
     /// 创建一个禁用浏览器请求头的客户端
     pub fn without_browser_headers() -> Self {
-        // 确保 crypto provider 已初始化
-        let _ = crate::tls::init_crypto_provider();
-
-        Self {
-            proxy_config: None,
-            default_headers: HeaderMap::new(),
-        }
+        Self::new()
     }
 
     /// 创建使用代理的HTTP客户端
     pub fn with_proxy(proxy_config: ProxyConfig) -> Self {
-        // 确保 crypto provider 已初始化
-        let _ = crate::tls::init_crypto_provider();
-
         Self {
-            proxy_config: Some(proxy_config),
-            default_headers: HeaderMap::new(),
+            proxy_chain: vec![proxy_config],
+            ..Self::new()
         }
     }
 
     /// 发送 GET 请求
-    pub fn get(&self, url: &str) -> AsyncRequestBuilder {
-        AsyncRequestBuilder::new(Method::GET, url, self)
+    pub fn get(&self, url: &str) -> AsyncRequestBuilder<'_> {
+        self.request(Method::GET, url)
     }
 
     /// 发送 POST 请求
-    pub fn post(&self, url: &str) -> AsyncRequestBuilder {
-        AsyncRequestBuilder::new(Method::POST, url, self)
+    pub fn post(&self, url: &str) -> AsyncRequestBuilder<'_> {
+        self.request(Method::POST, url)
     }
 
     /// 发送 PUT 请求
-    pub fn put(&self, url: &str) -> AsyncRequestBuilder {
-        AsyncRequestBuilder::new(Method::PUT, url, self)
+    pub fn put(&self, url: &str) -> AsyncRequestBuilder<'_> {
+        self.request(Method::PUT, url)
     }
 
     /// 发送 DELETE 请求
-    pub fn delete(&self, url: &str) -> AsyncRequestBuilder {
-        AsyncRequestBuilder::new(Method::DELETE, url, self)
+    pub fn delete(&self, url: &str) -> AsyncRequestBuilder<'_> {
+        self.request(Method::DELETE, url)
     }
 
     /// 发送 HEAD 请求
-    pub fn head(&self, url: &str) -> AsyncRequestBuilder {
-        AsyncRequestBuilder::new(Method::HEAD, url, self)
+    pub fn head(&self, url: &str) -> AsyncRequestBuilder<'_> {
+        self.request(Method::HEAD, url)
+    }
+
+    /// 发送 OPTIONS 请求
+    ///
+    /// `url` 为 `*` 时使用 `base_url` 所在的服务器发送 `OPTIONS * HTTP/1.1`（见 `AsyncRequestBuilder::asterisk_form`）
+    pub fn options(&self, url: &str) -> AsyncRequestBuilder<'_> {
+        let builder = self.request(Method::OPTIONS, url);
+        if url == "*" {
+            builder.asterisk_form()
+        } else {
+            builder
+        }
+    }
+
+    /// 发送 GET 请求并将响应体解析为 JSON
+    ///
+    /// 状态码为 4xx 或 5xx 时返回 `Error::Http`，不会尝试解析错误页面
+    pub async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T> {
+        self.get(url).send().await?.error_for_status()?.json().await
+    }
+
+    /// 以 JSON 请求体发送 POST 请求，并将响应体解析为 JSON
+    ///
+    /// 状态码为 4xx 或 5xx 时返回 `Error::Http`，不会尝试解析错误页面
+    pub async fn post_json<T, B>(&self, url: &str, body: &B) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+        B: serde::Serialize,
+    {
+        self.post(url).json(body)?.send().await?.error_for_status()?.json().await
+    }
+
+    /// 发送指定方法的请求
+    ///
+    /// 设置了 `base_url` 时，相对路径会相对于它解析，绝对 URL 保持不变
+    pub fn request(&self, method: Method, url: &str) -> AsyncRequestBuilder<'_> {
+        match &self.base_url {
+            Some(base) if url::Url::parse(url).is_err() => match resolve_url(base, url) {
+                Ok(resolved) => AsyncRequestBuilder::new(method, resolved.as_str(), self),
+                Err(_) => AsyncRequestBuilder::new(method, url, self),
+            },
+            _ => AsyncRequestBuilder::new(method, url, self),
+        }
     }
 
     /// 发送请求（直接发送Request对象）
-    pub async fn send_request(&self, mut request: Request) -> Result<Response> {
-        // 合并默认请求头
-        for (key, value) in self.default_headers.inner() {
-            if !request.headers.contains_key(key) {
-                request.headers.insert(key.clone(), value.clone());
+    ///
+    /// 设置了超时时间时，超时覆盖整个过程：域名解析、TCP 连接、TLS 握手、收发数据以及重定向
+    pub async fn send_request(&self, request: Request) -> Result<Response> {
+        self.with_timeout(self.send_request_inner(request)).await
+    }
+
+    async fn send_request_inner(&self, mut request: Request) -> Result<Response> {
+        request.load_body_file().await?;
+        if let Some(stream) = request.body_stream.take() {
+            let body = stream.take().ok_or_else(|| Error::other("request body channel was already consumed"))?;
+            return self.send_streaming_request_inner(request, body).await;
+        }
+        self.prepare_request(&mut request)?;
+
+        // 只重试可以安全重放的请求，每次重试发送完全相同的请求（包括 Idempotency-Key）
+        let start = Instant::now();
+        let mut retries = 0;
+        loop {
+            let attempt = request.try_clone().ok_or_else(|| Error::other("request body cannot be replayed"))?;
+            let result = self.send_with_redirects(attempt, start).await;
+            if retries >= self.max_retries || !request.is_retry_safe() {
+                return result;
             }
+            // 服务器要求稍后重试时优先按 Retry-After 等待，否则按指数退避等待
+            let delay = match &result {
+                Err(error) if is_retryable(error) => retry_backoff(retries),
+                Ok(response) if matches!(response.status_code, 429 | 503) => match response.retry_after() {
+                    Some(delay) if delay > MAX_RETRY_AFTER => return result,
+                    Some(delay) => delay,
+                    None => retry_backoff(retries),
+                },
+                _ => return result,
+            };
+            retries += 1;
+            tokio::time::sleep(delay).await;
         }
+    }
+
+    /// 发送请求并跟随重定向，`start` 为计算各阶段耗时的起点
+    async fn send_with_redirects(&self, mut request: Request, start: Instant) -> Result<Response> {
+        let original_url = url::Url::parse(&request.url)
+            .map_err(|e| Error::url_parse(format!("Invalid request URL {}: {}", request.url, e)))?;
+        let mut reusable_connection: Option<(ParsedUrl, Box<dyn AsyncConnection>)> = None;
+        let mut redirects = 0;
+
+        loop {
+            let parsed_url = parse_host_port(&request.url)?;
+            if self.https_only && !parsed_url.is_https {
+                return Err(Error::other(format!("https_only client refused non-HTTPS URL {}", request.url)));
+            }
+            let _permit = self.check_circuit(&parsed_url)?;
+
+            // 同源且连接可复用时继续使用已有连接，其次使用连接池中的空闲连接，否则创建新连接
+            let (mut connection, preconnected) = match reusable_connection.take() {
+                Some((previous_url, connection)) if is_same_origin(&previous_url, &parsed_url) => (connection, false),
+                _ => match self.pool.take(&parsed_url) {
+                    Some(connection) => (connection, true),
+                    None => (self.open_connection(&parsed_url).await?, false),
+                },
+            };
+
+            let mut response = self.execute(&request, &parsed_url, connection.as_mut()).await;
+            // 空闲连接可能已被服务器关闭，此时改用新连接重发（只重发可以安全重放的请求）
+            if preconnected && request.is_retry_safe() && matches!(&response, Err(error) if is_retryable(error)) {
+                connection = self.open_connection(&parsed_url).await?;
+                response = self.execute(&request, &parsed_url, connection.as_mut()).await;
+            }
+            self.record_outcome(&parsed_url, matches!(&response, Ok(response) if response.status_code < 500));
+            let mut response = response?;
+
+            let next_request = if redirects < self.max_redirects {
+                redirect_request(&request, &response, &self.redirect_policy, &original_url)?
+            } else {
+                None
+            };
+            let Some(next_request) = next_request else {
+                response.timings = Some(Timings::from_connection(start, connection.timings(), Instant::now()));
+                // 完整分帧的 keep-alive 连接放回连接池，供之后发往同一来源的请求使用
+                if self.keep_alive_enabled() && connection.is_reusable() && response.is_keep_alive() {
+                    self.pool.put(&parsed_url, connection);
+                } else {
+                    let _ = connection.close().await;
+                }
+                return Ok(response);
+            };
+
+            redirects += 1;
+            request = next_request;
+            // 连接层确认响应已完整分帧，且服务器未要求关闭连接时才复用
+            if self.keep_alive_enabled() && connection.is_reusable() && response.is_keep_alive() {
+                reusable_connection = Some((parsed_url, connection));
+            } else {
+                let _ = connection.close().await;
+            }
+        }
+    }
+
+    /// 发送请求体来自通道的请求
+    ///
+    /// 请求体以 chunked 编码发送，每收到一个分块写出一帧，通道关闭后写出结束块；
+    /// 请求体无法重放，因此不跟随重定向
+    pub async fn send_streaming_request(&self, request: Request, body: mpsc::Receiver<Bytes>) -> Result<Response> {
+        self.with_timeout(self.send_streaming_request_inner(request, body)).await
+    }
+
+    async fn send_streaming_request_inner(&self, mut request: Request, body: mpsc::Receiver<Bytes>) -> Result<Response> {
+        let start = Instant::now();
+        self.prepare_streaming_request(&mut request)?;
 
         let parsed_url = parse_host_port(&request.url)?;
+        let _permit = self.check_circuit(&parsed_url)?;
+        let target = self.request_target(&request, &parsed_url);
+        let head = request.build_request_bytes(&parsed_url, target)?;
 
-        // 创建连接
-        let mut connection = self.create_connection(&parsed_url).await?;
+        let mut connection = self.open_connection(&parsed_url).await?;
+        if let Some(nodelay) = request.tcp_nodelay {
+            connection.set_nodelay(nodelay)?;
+        }
+        let raw_response = connection.send_streaming_request(&head, body, &parsed_url).await;
+        let mut response = raw_response.and_then(|raw_response| self.parse_response(connection.as_ref(), raw_response));
+        self.record_outcome(&parsed_url, matches!(&response, Ok(response) if response.status_code < 500));
+        if let Ok(response) = &mut response {
+            response.timings = Some(Timings::from_connection(start, connection.timings(), Instant::now()));
+            let _ = connection.close().await;
+        }
+        response
+    }
+
+    /// 在同一个 keep-alive 连接上以 HTTP/1.1 管线化方式发送多个请求，按顺序返回响应
+    ///
+    /// 所有请求必须是幂等方法且属于同一来源（协议、主机、端口相同）；
+    /// 服务器必须按 Content-Length 或 chunked 分帧返回除最后一个以外的响应，不跟随重定向
+    pub async fn pipeline(&self, requests: Vec<Request>) -> Result<Vec<Response>> {
+        self.with_timeout(self.pipeline_inner(requests)).await
+    }
+
+    async fn pipeline_inner(&self, requests: Vec<Request>) -> Result<Vec<Response>> {
+        let Some(first) = requests.first() else {
+            return Ok(Vec::new());
+        };
+        let parsed_url = parse_host_port(&first.url)?;
+
+        let mut serialized = Vec::with_capacity(requests.len());
+        for mut request in requests {
+            request.load_body_file().await?;
+            if !request.method.is_idempotent() {
+                return Err(Error::other(format!(
+                    "Cannot pipeline non-idempotent {} request",
+                    request.method.as_str()
+                )));
+            }
+            let request_url = parse_host_port(&request.url)?;
+            if !is_same_origin(&parsed_url, &request_url) {
+                return Err(Error::other(format!(
+                    "Pipelined requests must share one origin: {}",
+                    request.url
+                )));
+            }
+
+            self.apply_default_headers(&mut request)?;
+            request.headers.retain(|key, _| !key.eq_ignore_ascii_case("connection"));
+            request.headers.insert("Connection".to_string(), "keep-alive".to_string());
+            let target = self.request_target(&request, &request_url);
+            serialized.push(request.build_request_bytes(&request_url, target)?);
+        }
 
-        // 构建HTTP请求
-        let request_str = request.serialize_to_string(&parsed_url)?;
+        let _permit = self.check_circuit(&parsed_url)?;
+        let mut connection = self.open_connection(&parsed_url).await?;
+        let raw_responses = connection.send_requests(&serialized, &parsed_url).await;
+        self.record_outcome(&parsed_url, raw_responses.is_ok());
+
+        let responses = raw_responses?
+            .into_iter()
+            .map(|raw_response| self.parse_response(connection.as_ref(), raw_response))
+            .collect();
+        let _ = connection.close().await;
+        responses
+    }
+
+    /// 建立 WebSocket 连接
+    ///
+    /// 支持 ws:// 和 wss:// URL，复用客户端的代理和 TLS 配置完成 HTTP/1.1 Upgrade 握手，
+    /// 校验 Sec-WebSocket-Accept 后返回已升级的底层连接。
+    /// 配置了代理时先通过 CONNECT 建立到目标的隧道（ws:// 也是如此），再在隧道内握手；
+    /// 与普通请求一样使用自定义连接器、熔断器和客户端超时
+    pub async fn websocket(&self, url: &str) -> Result<WebSocketUpgrade> {
+        self.with_timeout(self.websocket_inner(url)).await
+    }
+
+    async fn websocket_inner(&self, url: &str) -> Result<WebSocketUpgrade> {
+        let http_url = if let Some(rest) = url.strip_prefix("wss://") {
+            format!("https://{}", rest)
+        } else if let Some(rest) = url.strip_prefix("ws://") {
+            format!("http://{}", rest)
+        } else {
+            url.to_string()
+        };
+
+        let key = websocket::generate_key()?;
+        let mut request = Request::minimal(Method::GET, &http_url).headers([
+            ("Connection", "Upgrade"),
+            ("Upgrade", "websocket"),
+            ("Sec-WebSocket-Version", "13"),
+            ("Sec-WebSocket-Key", key.as_str()),
+        ]);
+        self.apply_default_headers(&mut request)?;
+
+        let parsed_url = parse_host_port(&request.url)?;
+        let request_str = request.build_request_string(&parsed_url, RequestTarget::Origin)?;
+
+        let _permit = self.check_circuit(&parsed_url)?;
+        let connection = self.open_upgrade_connection(&parsed_url).await?;
+        let mut stream = connection.into_boxed_stream()?;
+        let response = websocket::handshake(&mut stream, &request_str, &key).await;
+        let success = match &response {
+            Ok(_) => true,
+            Err(Error::Http { status, .. }) => *status < 500,
+            Err(_) => false,
+        };
+        self.record_outcome(&parsed_url, success);
+
+        Ok(WebSocketUpgrade { response: response?, stream })
+    }
+
+    /// 创建用于协议升级的独占连接，失败时计入熔断统计
+    ///
+    /// 配置了连接器时由连接器创建；普通 HTTP 代理通常不转发 Upgrade，因此经过代理时总是建立 CONNECT 隧道
+    async fn open_upgrade_connection(&self, parsed_url: &ParsedUrl) -> Result<Box<dyn AsyncConnection>> {
+        let connection: Result<Box<dyn AsyncConnection>> = match (&self.connector, self.proxy_for(parsed_url)) {
+            (Some(connector), _) => connector.connect(parsed_url).await,
+            (None, Some(chain)) => {
+                AsyncHttpConnection::tunnel_via_proxy_chain_with_options(chain, parsed_url, &self.connect_options)
+                    .await
+                    .map(|connection| Box::new(connection) as Box<dyn AsyncConnection>)
+            }
+            (None, None) => AsyncHttpConnection::direct_with_options(parsed_url, &self.connect_options)
+                .await
+                .map(|connection| Box::new(connection) as Box<dyn AsyncConnection>),
+        };
+        if connection.is_err() {
+            self.record_outcome(parsed_url, false);
+        }
+        connection
+    }
+
+    /// 在客户端超时时间内执行操作，超时后放弃操作（关闭连接）并返回 `Error::Timeout`
+    async fn with_timeout<T>(&self, operation: impl Future<Output = Result<T>>) -> Result<T> {
+        match self.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, operation)
+                .await
+                .map_err(|_| Error::timeout(format!("request timed out after {:?}", timeout)))?,
+            None => operation.await,
+        }
+    }
+
+    /// 按发送时的方式处理并序列化请求，返回将写入连接的字节，不建立连接
+    ///
+    /// `streaming` 为 true 时按通道请求体处理，只返回请求头部分
+    pub(crate) fn preview_request(&self, mut request: Request, streaming: bool) -> Result<Vec<u8>> {
+        request.load_body_file_sync()?;
+        if streaming {
+            self.prepare_streaming_request(&mut request)?;
+        } else {
+            self.prepare_request(&mut request)?;
+        }
+        let parsed_url = parse_host_port(&request.url)?;
+        let target = self.request_target(&request, &parsed_url);
+        request.build_request_bytes(&parsed_url, target)
+    }
+
+    /// 按客户端配置处理待发送的请求：合并默认请求头、自动压缩请求体、需要时请求保持连接
+    fn prepare_request(&self, request: &mut Request) -> Result<()> {
+        self.apply_default_headers(request)?;
+        if let Some(threshold) = self.auto_compress_threshold {
+            compress_request_body(request, threshold)?;
+        }
+
+        // 只替换 `Request::new` 内置的 `Connection: close`，显式设置的 Connection 保持不变
+        if self.keep_alive_enabled() {
+            request.merge_default_header("Connection", "keep-alive");
+        }
+        Ok(())
+    }
+
+    /// 是否请求服务器保持连接：未显式设置 `keep_alive` 时只在跟随重定向（可能复用连接）时保持
+    fn keep_alive_enabled(&self) -> bool {
+        self.keep_alive.unwrap_or(self.max_redirects > 0)
+    }
+
+    /// 处理请求体来自通道的请求：合并默认请求头并改用 chunked 编码
+    fn prepare_streaming_request(&self, request: &mut Request) -> Result<()> {
+        self.apply_default_headers(request)?;
+        request.body = None;
+        request
+            .headers
+            .retain(|key, _| !key.eq_ignore_ascii_case("content-length") && !key.eq_ignore_ascii_case("transfer-encoding"));
+        request.headers.insert("Transfer-Encoding".to_string(), "chunked".to_string());
+        Ok(())
+    }
+
+    /// 为请求合并 User-Agent 轮换和客户端默认请求头，并替换请求头中的客户端变量
+    fn apply_default_headers(&self, request: &mut Request) -> Result<()> {
+        // 目标主机的默认请求头先于全局默认请求头合并，因此优先
+        let host_headers = parse_host_port(&request.url)
+            .ok()
+            .and_then(|parsed_url| self.host_headers.get(&parsed_url.hostname.to_ascii_lowercase()));
+        if let Some(host_headers) = host_headers {
+            for (key, value) in host_headers.inner() {
+                request.merge_default_header(key, value);
+            }
+        }
+
+        // 轮换 User-Agent，优先于默认请求头中的 User-Agent
+        if let Some(user_agent) = self.next_user_agent() {
+            request.merge_default_header("User-Agent", user_agent);
+        }
+
+        // 合并默认请求头
+        for (key, value) in self.default_headers.inner() {
+            request.merge_default_header(key, value);
+        }
+        if request.header_order.is_empty() {
+            request.header_order = self.header_order.to_vec();
+        }
+
+        // 未注册变量时请求头原样发送，不解析花括号
+        if !self.vars.is_empty() {
+            for (key, value) in request.headers.iter_mut() {
+                *value = substitute_vars(value, &self.vars)
+                    .map_err(|e| Error::other(format!("Invalid header {}: {}", key, e)))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 在指定连接上发送单个请求并解析响应
+    async fn execute(&self, request: &Request, parsed_url: &ParsedUrl, connection: &mut dyn AsyncConnection) -> Result<Response> {
+        // 构建HTTP请求（经 HTTP 代理发送明文请求时使用绝对形式）
+        let target = self.request_target(request, parsed_url);
+        let request_bytes = request.build_request_bytes(parsed_url, target)?;
+        if let Some(nodelay) = request.tcp_nodelay {
+            connection.set_nodelay(nodelay)?;
+        }
 
         // 发送请求并获取响应
-        let raw_response = connection.send_request(&request_str, &parsed_url).await?;
+        let raw_response = connection.send_request(&request_bytes, parsed_url).await?;
 
         // 将原始响应字节流解析为 Response 结构
-        Response::from_raw_bytes(raw_response)
+        self.parse_response(connection, raw_response)
+    }
+
+    /// 将连接上读取的原始响应解析为 Response，记录连接协商的 ALPN 协议，并按配置检测压缩格式
+    fn parse_response(&self, connection: &dyn AsyncConnection, raw_response: Vec<u8>) -> Result<Response> {
+        let mut response = if self.allow_http09 && !raw_response.starts_with(b"HTTP/") {
+            Response::from_http09_bytes(raw_response)
+        } else {
+            Response::parse_raw_bytes(raw_response, connection.is_truncated(), self.max_header_count)?
+        };
+        response.decompress_body(self.decoders)?;
+        response.alpn_protocol = connection.alpn_protocol().map(<[u8]>::to_vec);
+        if self.sniff_compression {
+            response.decompress_sniffed();
+        }
+        Ok(response)
+    }
+
+    /// 检查熔断器是否放行发往该主机的请求，返回的许可需持有到记录请求结果之后
+    fn check_circuit(&self, parsed_url: &ParsedUrl) -> Result<Option<CircuitPermit<'_>>> {
+        self.circuit_breaker
+            .as_ref()
+            .map(|breaker| breaker.check(&parsed_url.hostname, parsed_url.port))
+            .transpose()
+    }
+
+    /// 向熔断器记录请求结果，连接错误和 5xx 响应计为失败
+    fn record_outcome(&self, parsed_url: &ParsedUrl, success: bool) {
+        let Some(breaker) = &self.circuit_breaker else {
+            return;
+        };
+        if success {
+            breaker.record_success(&parsed_url.hostname, parsed_url.port);
+        } else {
+            breaker.record_failure(&parsed_url.hostname, parsed_url.port);
+        }
+    }
+
+    /// 按轮询顺序获取下一个 User-Agent，未配置时返回 None
+    fn next_user_agent(&self) -> Option<&str> {
+        if self.user_agents.is_empty() {
+            return None;
+        }
+        let index = self.user_agent_index.fetch_add(1, Ordering::Relaxed);
+        Some(&self.user_agents[index % self.user_agents.len()])
+    }
+
+    /// 根据请求和是否经 HTTP 代理发送明文请求选择请求目标形式
+    fn request_target(&self, request: &Request, parsed_url: &ParsedUrl) -> RequestTarget {
+        let via_http_proxy = self.proxy_for(parsed_url).is_some() && !parsed_url.is_https;
+        RequestTarget::for_request(request, via_http_proxy)
+    }
+
+    /// 获取访问指定 URL 时依次经过的代理，不使用代理时返回 None
+    fn proxy_for(&self, parsed_url: &ParsedUrl) -> Option<&[ProxyConfig]> {
+        if self.proxy_chain.is_empty() || (self.proxy_bypass_localhost && is_loopback_host(&parsed_url.hostname)) {
+            return None;
+        }
+        Some(&self.proxy_chain)
+    }
+
+    /// 预先建立到 URL 所在来源的连接（包括 TLS 握手），放入连接池供之后的请求使用
+    ///
+    /// 每个预连接只被下一个发往同一来源的请求使用一次；预连接已被服务器关闭时，
+    /// 可以安全重放的请求会改用新连接重发。连接成功或失败都会计入熔断统计
+    pub async fn preconnect(&self, url: &str) -> Result<()> {
+        self.with_timeout(async {
+            let parsed_url = parse_host_port(url)?;
+            let _permit = self.check_circuit(&parsed_url)?;
+            // 连接失败已在 open_connection 中计入熔断统计，连接成功时同样记录，使熔断器的试探得以结束
+            let connection = self.open_connection(&parsed_url).await?;
+            self.record_outcome(&parsed_url, true);
+            self.pool.put(&parsed_url, connection);
+            Ok(())
+        })
+        .await
+    }
+
+    /// 创建连接，失败时计入熔断统计
+    async fn open_connection(&self, parsed_url: &ParsedUrl) -> Result<Box<dyn AsyncConnection>> {
+        let connection = self.create_connection(parsed_url).await;
+        if connection.is_err() {
+            self.record_outcome(parsed_url, false);
+        }
+        connection
     }
 
     /// 创建连接
     async fn create_connection(&self, parsed_url: &ParsedUrl) -> Result<Box<dyn AsyncConnection>> {
-        match &self.proxy_config {
-            Some(config) => Ok(Box::new(AsyncHttpConnection::via_proxy(config.clone(), parsed_url).await?)),
-            None => Ok(Box::new(AsyncHttpConnection::direct(parsed_url).await?)),
+        if let Some(connector) = &self.connector {
+            return connector.connect(parsed_url).await;
+        }
+
+        let connection = self.connect(parsed_url).await?;
+        Ok(Box::new(
+            connection
+                .with_partial_response(self.allow_partial_response)
+                .with_http09(self.allow_http09)
+                .with_max_header_size(self.max_header_size)
+                .with_read_idle_timeout(self.read_idle_timeout),
+        ))
+    }
+
+    /// 建立到目标服务器的连接（必要时经过代理）
+    async fn connect(&self, parsed_url: &ParsedUrl) -> Result<AsyncHttpConnection> {
+        match self.proxy_for(parsed_url) {
+            Some([config]) => {
+                AsyncHttpConnection::via_proxy_with_options(config.clone(), parsed_url, &self.connect_options).await
+            }
+            Some(chain) => {
+                AsyncHttpConnection::via_proxy_chain_with_options(chain, parsed_url, &self.connect_options).await
+            }
+            None => AsyncHttpConnection::direct_with_options(parsed_url, &self.connect_options).await,
         }
     }
 }
+
+/// 将 `{name}` 替换为对应变量的值，`{{` 和 `}}` 表示字面量花括号
+fn substitute_vars(template: &str, vars: &HashMap<String, String>) -> std::result::Result<String, String> {
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(index) = rest.find(['{', '}']) {
+        result.push_str(&rest[..index]);
+        let tail = &rest[index..];
+        if let Some(after) = tail.strip_prefix("{{") {
+            result.push('{');
+            rest = after;
+        } else if let Some(after) = tail.strip_prefix("}}") {
+            result.push('}');
+            rest = after;
+        } else if tail.starts_with('}') {
+            return Err("unmatched '}'".to_string());
+        } else {
+            let end = tail.find('}').ok_or_else(|| "unclosed '{'".to_string())?;
+            let name = &tail[1..end];
+            let value = vars.get(name).ok_or_else(|| format!("unknown variable {{{}}}", name))?;
+            result.push_str(value);
+            rest = &tail[end + 1..];
+        }
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// 判断请求失败是否由连接问题导致，可以重试（熔断器打开时 `Error::CircuitOpen` 不重试）
+fn is_retryable(error: &Error) -> bool {
+    matches!(error, Error::Io(_) | Error::Connection(_))
+}
+
+/// 第 `retries` 次重试前的退避时间：100 毫秒起，每次翻倍，最多 6.4 秒
+fn retry_backoff(retries: usize) -> Duration {
+    RETRY_BACKOFF * (1 << retries.min(6))
+}
+
+/// 判断主机是否为本地回环地址
+fn is_loopback_host(hostname: &str) -> bool {
+    let host = hostname.trim_start_matches('[').trim_end_matches(']');
+    if host.eq_ignore_ascii_case("localhost") {
+        return true;
+    }
+    host.parse::<std::net::IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+/// 判断两个 URL 是否同源（协议、主机、端口均相同）
+fn is_same_origin(a: &ParsedUrl, b: &ParsedUrl) -> bool {
+    a.is_https == b.is_https && a.hostname.eq_ignore_ascii_case(&b.hostname) && a.port == b.port
+}
+
+/// 根据重定向响应构建下一个请求，响应不是可跟随的重定向时返回 None
+fn redirect_request(
+    request: &Request,
+    response: &Response,
+    policy: &RedirectPolicy,
+    original_url: &url::Url,
+) -> Result<Option<Request>> {
+    if !matches!(response.status_code, 301 | 302 | 303 | 307 | 308) {
+        return Ok(None);
+    }
+    let Some(location) = response.get_header("location") else {
+        return Ok(None);
+    };
+
+    let current_url = url::Url::parse(&request.url)
+        .map_err(|e| Error::url_parse(format!("Invalid request URL {}: {}", request.url, e)))?;
+    let next_url = resolve_url(&current_url, location)?;
+    if !policy.allows(original_url, &next_url) {
+        return Err(Error::other(format!("Redirect to {} refused by redirect policy", next_url)));
+    }
+
+    // 无法重放请求体时不跟随重定向，直接返回重定向响应
+    let Some(mut next_request) = request.try_clone() else {
+        return Ok(None);
+    };
+    next_request.url = next_url.to_string();
+
+    // 303 以及 POST 的 301/302 重定向改为不带请求体的 GET
+    let to_get = response.status_code == 303
+        || (matches!(response.status_code, 301 | 302) && request.method == Method::POST);
+    if to_get {
+        next_request.method = Method::GET;
+        next_request.body = None;
+        next_request.headers.retain(|key, _| {
+            !key.eq_ignore_ascii_case("content-length")
+                && !key.eq_ignore_ascii_case("content-type")
+                && !key.eq_ignore_ascii_case("content-encoding")
+        });
+    }
+
+    // 跨域重定向时不转发认证信息，覆盖的 Host 也只适用于原来的来源
+    if current_url.origin() != next_url.origin() {
+        next_request.headers.retain(|key, _| {
+            !key.eq_ignore_ascii_case("authorization")
+                && !key.eq_ignore_ascii_case("cookie")
+                && !key.eq_ignore_ascii_case("host")
+        });
+    }
+
+    Ok(Some(next_request))
+}
+
+/// 请求体达到 `threshold` 字节且未设置 Content-Encoding 时使用 gzip 压缩请求体
+fn compress_request_body(request: &mut Request, threshold: usize) -> Result<()> {
+    let Some(body) = &request.body else {
+        return Ok(());
+    };
+    if body.len() < threshold || request.headers.keys().any(|key| key.eq_ignore_ascii_case("content-encoding")) {
+        return Ok(());
+    }
+
+    let compressed = compress(body, Compression::Gzip)?;
+    request.headers.retain(|key, _| !key.eq_ignore_ascii_case("content-length"));
+    request.headers.insert("Content-Encoding".to_string(), "gzip".to_string());
+    request.headers.insert("Content-Length".to_string(), compressed.len().to_string());
+    request.body = Some(compressed.into());
+    Ok(())
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::{BoxedStream, MockResponse};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn test_http_via_proxy_uses_absolute_form() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = vec![0u8; 4096];
+            let n = stream.read(&mut buffer).await.unwrap();
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok")
+                .await
+                .unwrap();
+            String::from_utf8_lossy(&buffer[..n]).to_string()
+        });
+
+        let client = HttpClient::with_proxy(ProxyConfig::http("127.0.0.1", port));
+        let response = client.get("http://example.com/path").send().await.unwrap();
+        assert_eq!(response.body, b"ok");
+
+        let received = server.await.unwrap();
+        assert!(received.starts_with("GET http://example.com/path HTTP/1.1\r\n"));
+    }
+
+    /// 启动一个依次处理 `count` 个连接的本地服务器，返回收到的请求
+    async fn spawn_capture_server(count: usize) -> (u16, tokio::task::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let handle = tokio::spawn(async move {
+            let mut requests = Vec::new();
+            for _ in 0..count {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buffer = vec![0u8; 8192];
+                let n = stream.read(&mut buffer).await.unwrap();
+                requests.push(String::from_utf8_lossy(&buffer[..n]).to_string());
+                stream
+                    .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+                    .await
+                    .unwrap();
+            }
+            requests
+        });
+
+        (port, handle)
+    }
+
+    /// 从请求中提取指定请求头（不区分大小写）的全部值
+    fn header_values(request: &str, name: &str) -> Vec<String> {
+        request
+            .split("\r\n")
+            .filter_map(|line| line.split_once(':'))
+            .filter(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.trim().to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_user_agent_rotation() {
+        let (port, server) = spawn_capture_server(4).await;
+        let client = HttpClient::builder()
+            .user_agents(vec!["agent-a".to_string(), "agent-b".to_string()])
+            .build()
+            .unwrap();
+
+        let url = format!("http://127.0.0.1:{}/", port);
+        for _ in 0..3 {
+            client.get(&url).send().await.unwrap();
+        }
+        client.get(&url).header("User-Agent", "explicit").send().await.unwrap();
+
+        let agents: Vec<Vec<String>> = server
+            .await
+            .unwrap()
+            .iter()
+            .map(|request| header_values(request, "user-agent"))
+            .collect();
+        assert_eq!(agents, vec![vec!["agent-a"], vec!["agent-b"], vec!["agent-a"], vec!["explicit"]]);
+    }
+
+    #[tokio::test]
+    async fn test_websocket_handshake_and_echo() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        // 本地 WebSocket 回显服务器：完成握手后将收到的一个帧去掉掩码原样返回
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = vec![0u8; 4096];
+            let n = stream.read(&mut buffer).await.unwrap();
+            let request = String::from_utf8_lossy(&buffer[..n]).to_string();
+            let key = header_values(&request, "sec-websocket-key").remove(0);
+            let reply = format!(
+                "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+                websocket::accept_key(&key)
+            );
+            stream.write_all(reply.as_bytes()).await.unwrap();
+
+            let mut header = [0u8; 2];
+            stream.read_exact(&mut header).await.unwrap();
+            let length = (header[1] & 0x7F) as usize;
+            let mut mask = [0u8; 4];
+            stream.read_exact(&mut mask).await.unwrap();
+            let mut payload = vec![0u8; length];
+            stream.read_exact(&mut payload).await.unwrap();
+            for (i, byte) in payload.iter_mut().enumerate() {
+                *byte ^= mask[i % 4];
+            }
+
+            let mut frame = vec![header[0], length as u8];
+            frame.extend_from_slice(&payload);
+            stream.write_all(&frame).await.unwrap();
+        });
+
+        let client = HttpClient::new();
+        let mut upgrade = client.websocket(&format!("ws://127.0.0.1:{}/echo", port)).await.unwrap();
+        assert_eq!(upgrade.response.status_code, 101);
+
+        // 发送一个带掩码的文本帧 "hello"
+        let mask = [1u8, 2, 3, 4];
+        let mut frame = vec![0x81, 0x80 | 5];
+        frame.extend_from_slice(&mask);
+        frame.extend(b"hello".iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+        upgrade.stream.write_all(&frame).await.unwrap();
+
+        let mut echo = [0u8; 7];
+        upgrade.stream.read_exact(&mut echo).await.unwrap();
+        assert_eq!(&echo[..2], &[0x81, 5]);
+        assert_eq!(&echo[2..], b"hello");
+    }
+
+    #[tokio::test]
+    async fn test_local_address_binding() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server = tokio::spawn(async move {
+            let (mut stream, peer) = listener.accept().await.unwrap();
+            let mut buffer = vec![0u8; 4096];
+            let _ = stream.read(&mut buffer).await.unwrap();
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+                .await
+                .unwrap();
+            peer
+        });
+
+        let local_address: std::net::IpAddr = "127.0.0.1".parse().unwrap();
+        let client = HttpClient::builder().local_address(local_address).build().unwrap();
+        client.get(&format!("http://localhost:{}/", port)).send().await.unwrap();
+
+        assert_eq!(server.await.unwrap().ip(), local_address);
+    }
+
+    #[tokio::test]
+    async fn test_loopback_bypasses_proxy() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = vec![0u8; 4096];
+            let _ = stream.read(&mut buffer).await.unwrap();
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 6\r\n\r\ndirect")
+                .await
+                .unwrap();
+        });
+
+        // 代理地址不可达，只有绕过代理才能成功
+        let unreachable_proxy = ProxyConfig::http("127.0.0.1", 1);
+        let client = HttpClient::with_proxy(unreachable_proxy.clone());
+        let response = client.get(&format!("http://127.0.0.1:{}/", port)).send().await.unwrap();
+        assert_eq!(response.body, b"direct");
+
+        let client = HttpClient::builder()
+            .proxy(unreachable_proxy)
+            .proxy_bypass_localhost(false)
+            .build()
+            .unwrap();
+        assert!(client.get(&format!("http://localhost:{}/", port)).send().await.is_err());
+
+        assert!(is_loopback_host("localhost"));
+        assert!(is_loopback_host("[::1]"));
+        assert!(!is_loopback_host("example.com"));
+    }
+
+    /// 启动一个发送部分响应体后重置连接的本地服务器
+    async fn spawn_reset_server() -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = vec![0u8; 4096];
+            let _ = stream.read(&mut buffer).await.unwrap();
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\n\r\npartial")
+                .await
+                .unwrap();
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            // SO_LINGER 为 0 时关闭连接会发送 RST
+            stream.set_linger(Some(std::time::Duration::ZERO)).unwrap();
+        });
+
+        port
+    }
+
+    #[test]
+    fn test_header_variable_substitution() {
+        let client = HttpClient::builder()
+            .no_browser_headers()
+            .var("token", "abc123")
+            .var("tenant", "acme")
+            .build()
+            .unwrap();
+
+        let bytes = client
+            .get("http://example.com/")
+            .header("Authorization", "Bearer {token}")
+            .header("X-Scope", "{tenant}/{{literal}}")
+            .preview()
+            .unwrap();
+        let preview = String::from_utf8(bytes).unwrap();
+        assert!(preview.contains("\r\nAuthorization: Bearer abc123\r\n"));
+        assert!(preview.contains("\r\nX-Scope: acme/{literal}\r\n"));
+
+        let error = client
+            .get("http://example.com/")
+            .header("Authorization", "Bearer {missing}")
+            .preview()
+            .unwrap_err();
+        assert!(error.to_string().contains("unknown variable {missing}"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_same_host_redirect_policy() {
+        let mocks = vec![
+            MockResponse::new(Method::GET, "http://a.example/start", 302).header("Location", "http://b.example/next"),
+            MockResponse::new(Method::GET, "http://b.example/next", 200).body("followed"),
+        ];
+
+        let client = HttpClient::builder().with_mock(mocks.clone()).max_redirects(5).build().unwrap();
+        let response = client.get("http://a.example/start").send().await.unwrap();
+        assert_eq!(response.body, b"followed");
+
+        let client = HttpClient::builder()
+            .with_mock(mocks)
+            .max_redirects(5)
+            .redirect_policy(RedirectPolicy::SameHost)
+            .build()
+            .unwrap();
+        let error = client.get("http://a.example/start").send().await.unwrap_err();
+        assert!(error.to_string().contains("refused by redirect policy"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_https_only_refuses_plain_http() {
+        let mocks = vec![
+            MockResponse::new(Method::GET, "https://a.example/start", 302).header("Location", "http://a.example/next"),
+            MockResponse::new(Method::GET, "http://a.example/next", 200).body("plain"),
+        ];
+        let client = HttpClient::builder().with_mock(mocks).max_redirects(5).https_only(true).build().unwrap();
+
+        let error = client.get("http://a.example/next").send().await.unwrap_err();
+        assert!(error.to_string().contains("https_only"), "{}", error);
+        // 重定向到 http:// 同样被拒绝
+        let error = client.get("https://a.example/start").send().await.unwrap_err();
+        assert!(error.to_string().contains("https_only"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_sniff_compression_decompresses_unlabeled_gzip() {
+        let compressed = compress(b"rescued content", Compression::Gzip).unwrap();
+        let mocks = vec![MockResponse::new(Method::GET, "http://broken.example/", 200).body(compressed.clone())];
+
+        let client = HttpClient::builder().with_mock(mocks.clone()).build().unwrap();
+        let response = client.get("http://broken.example/").send().await.unwrap();
+        assert_eq!(response.body, compressed);
+
+        let client = HttpClient::builder().with_mock(mocks).sniff_compression(true).build().unwrap();
+        let response = client.get("http://broken.example/").send().await.unwrap();
+        assert_eq!(response.body, b"rescued content");
+    }
+
+    /// 记录每次发送请求时底层套接字 TCP_NODELAY 设置的连接器
+    #[derive(Debug, Default)]
+    struct NodelayRecorder {
+        observed: Arc<std::sync::Mutex<Vec<Option<bool>>>>,
+    }
+
+    struct RecordingConnection {
+        inner: AsyncHttpConnection,
+        observed: Arc<std::sync::Mutex<Vec<Option<bool>>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Connector for NodelayRecorder {
+        async fn connect(&self, parsed_url: &ParsedUrl) -> Result<Box<dyn AsyncConnection>> {
+            Ok(Box::new(RecordingConnection {
+                inner: AsyncHttpConnection::direct(parsed_url).await?,
+                observed: self.observed.clone(),
+            }))
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl AsyncConnection for RecordingConnection {
+        async fn send_request(&mut self, request: &[u8], parsed_url: &ParsedUrl) -> Result<Vec<u8>> {
+            self.observed.lock().unwrap().push(self.inner.nodelay());
+            self.inner.send_request(request, parsed_url).await
+        }
+
+        fn set_nodelay(&mut self, nodelay: bool) -> Result<()> {
+            self.inner.set_nodelay(nodelay)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tcp_nodelay_per_request() {
+        let (port, server) = spawn_capture_server(2).await;
+        let recorder = Arc::new(NodelayRecorder::default());
+        let observed = recorder.observed.clone();
+        let client = HttpClient::builder().connector(recorder).build().unwrap();
+
+        let url = format!("http://127.0.0.1:{}/", port);
+        client.get(&url).send().await.unwrap();
+        client.post(&url).tcp_nodelay(false).body("bulk").send().await.unwrap();
+        server.await.unwrap();
+
+        assert_eq!(*observed.lock().unwrap(), vec![Some(true), Some(false)]);
+    }
+
+    #[tokio::test]
+    async fn test_accept_encoding_matches_enabled_decoders() {
+        let client = HttpClient::builder().build().unwrap();
+        assert_eq!(client.default_headers.get("accept-encoding").map(String::as_str), Some("gzip, deflate, br"));
+
+        let compressed = compress(b"brotli body", Compression::Brotli).unwrap();
+        let client = HttpClient::builder()
+            .brotli(false)
+            .with_mock(vec![MockResponse::new(Method::GET, "http://br.example/", 200)
+                .header("Content-Encoding", "br")
+                .body(compressed.clone())])
+            .build()
+            .unwrap();
+        assert_eq!(client.default_headers.get("accept-encoding").map(String::as_str), Some("gzip, deflate"));
+        assert_eq!(client.get("http://br.example/").send().await.unwrap().body, compressed);
+
+        let client = HttpClient::builder().gzip(false).deflate(false).brotli(false).build().unwrap();
+        assert!(!client.default_headers.contains_key("accept-encoding"));
+    }
+
+    #[tokio::test]
+    async fn test_decompress_disabled_returns_raw_gzip() {
+        let compressed = compress(b"stored as-is", Compression::Gzip).unwrap();
+        let client = HttpClient::builder()
+            .decompress(false)
+            .with_mock(vec![MockResponse::new(Method::GET, "http://gz.example/", 200)
+                .header("Content-Encoding", "gzip")
+                .body(compressed.clone())])
+            .build()
+            .unwrap();
+        assert_eq!(client.default_headers.get("accept-encoding").map(String::as_str), Some("gzip, deflate, br"));
+
+        let response = client.get("http://gz.example/").send().await.unwrap();
+        assert_eq!(response.body, compressed);
+        assert_eq!(response.get_header("content-encoding").map(String::as_str), Some("gzip"));
+    }
+
+    #[test]
+    fn test_host_headers_apply_only_to_matching_host() {
+        let mut global = HeaderMap::new();
+        global.insert("Authorization", "Bearer global").unwrap();
+        global.insert("X-Client", "rr").unwrap();
+        let mut a_headers = HeaderMap::new();
+        a_headers.insert("Authorization", "Bearer a").unwrap();
+        let mut b_headers = HeaderMap::new();
+        b_headers.insert("X-Api-Key", "b-key").unwrap();
+
+        let client = HttpClient::builder()
+            .browser_headers(false)
+            .default_headers(global)
+            .host_headers("api.a.com", a_headers)
+            .host_headers("API.B.COM", b_headers)
+            .build()
+            .unwrap();
+        let preview = |builder: AsyncRequestBuilder<'_>| String::from_utf8(builder.preview().unwrap()).unwrap();
+
+        let a = preview(client.get("https://api.a.com/items"));
+        assert!(a.contains("\r\nauthorization: Bearer a\r\n"), "{}", a);
+        assert!(a.contains("\r\nx-client: rr\r\n"), "{}", a);
+        assert!(!a.contains("x-api-key"), "{}", a);
+
+        let b = preview(client.get("http://api.b.com:8080/"));
+        assert!(b.contains("\r\nauthorization: Bearer global\r\n"), "{}", b);
+        assert!(b.contains("\r\nx-api-key: b-key\r\n"), "{}", b);
+
+        let other = preview(client.get("https://sub.api.a.com/"));
+        assert!(other.contains("\r\nauthorization: Bearer global\r\n"), "{}", other);
+
+        let explicit = preview(client.get("https://api.a.com/").header("Authorization", "Bearer request"));
+        assert!(explicit.contains("\r\nAuthorization: Bearer request\r\n"), "{}", explicit);
+        assert!(!explicit.contains("Bearer a"), "{}", explicit);
+    }
+
+    #[tokio::test]
+    async fn test_base_url_resolves_relative_paths() {
+        let client = HttpClient::builder()
+            .base_url("https://api.example.com/v1/")
+            .with_mock(vec![MockResponse::new(Method::GET, "https://api.example.com/v1/users/1", 200).body("user")])
+            .build()
+            .unwrap();
+
+        assert_eq!(client.get("users/1").build().url, "https://api.example.com/v1/users/1");
+        assert_eq!(client.post("/health").build().url, "https://api.example.com/health");
+        assert_eq!(client.get("http://other.example/x").build().url, "http://other.example/x");
+        assert_eq!(client.get("users/1").send().await.unwrap().body, b"user");
+
+        let error = HttpClient::builder().base_url("not a url").build().err().unwrap();
+        assert!(matches!(error, Error::UrlParse(_)), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_key_retried_with_same_key() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        // 前两次读取请求后直接断开连接，第三次正常响应
+        let server = tokio::spawn(async move {
+            let mut requests = Vec::new();
+            for attempt in 0..3 {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buffer = vec![0u8; 4096];
+                let n = stream.read(&mut buffer).await.unwrap();
+                requests.push(String::from_utf8_lossy(&buffer[..n]).to_string());
+                if attempt == 2 {
+                    stream.write_all(b"HTTP/1.1 201 Created\r\nContent-Length: 0\r\n\r\n").await.unwrap();
+                }
+            }
+            requests
+        });
+
+        let client = HttpClient::builder().no_browser_headers().max_retries(2).build().unwrap();
+        let response = client
+            .post(&format!("http://127.0.0.1:{}/payments", port))
+            .idempotency_key("pay-42")
+            .body("amount=10")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status_code, 201);
+
+        let requests = server.await.unwrap();
+        assert_eq!(requests.len(), 3);
+        for request in &requests {
+            assert!(request.contains("\r\nIdempotency-Key: pay-42\r\n"), "{}", request);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_honors_retry_after() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server = tokio::spawn(async move {
+            let responses = [
+                &b"HTTP/1.1 503 Service Unavailable\r\nRetry-After: 1\r\nContent-Length: 0\r\n\r\n"[..],
+                b"HTTP/1.1 429 Too Many Requests\r\nContent-Length: 0\r\n\r\n",
+                b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok",
+            ];
+            for response in responses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buffer = vec![0u8; 4096];
+                let _ = stream.read(&mut buffer).await.unwrap();
+                stream.write_all(response).await.unwrap();
+            }
+        });
+
+        let client = HttpClient::builder().no_browser_headers().max_retries(2).build().unwrap();
+        let start = Instant::now();
+        let response = client.get(&format!("http://127.0.0.1:{}/", port)).send().await.unwrap();
+        assert_eq!(response.body, b"ok");
+        // 503 按 Retry-After 等待 1 秒，没有 Retry-After 的 429 按退避等待 200 毫秒
+        assert!(start.elapsed() >= Duration::from_millis(1100), "{:?}", start.elapsed());
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_connection_reset_is_retried() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server = tokio::spawn(async move {
+            // 第一次连接不读取请求就关闭，未读数据使内核发送 RST
+            let (stream, _) = listener.accept().await.unwrap();
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            drop(stream);
+
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = vec![0u8; 4096];
+            let _ = stream.read(&mut buffer).await.unwrap();
+            stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok").await.unwrap();
+        });
+
+        let client = HttpClient::builder().no_browser_headers().max_retries(1).build().unwrap();
+        let response = client.get(&format!("http://127.0.0.1:{}/", port)).send().await.unwrap();
+        assert_eq!(response.body, b"ok");
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_post_without_idempotency_key_not_retried() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = vec![0u8; 4096];
+            let _ = stream.read(&mut buffer).await.unwrap();
+            drop(stream);
+            // 不应再有第二次连接
+            tokio::time::timeout(std::time::Duration::from_millis(300), listener.accept()).await.is_err()
+        });
+
+        let client = HttpClient::builder().no_browser_headers().max_retries(2).build().unwrap();
+        let result = client.post(&format!("http://127.0.0.1:{}/payments", port)).body("amount=10").send().await;
+        assert!(result.is_err());
+        assert!(server.await.unwrap(), "POST without idempotency key was retried");
+    }
+
+    #[tokio::test]
+    async fn test_preconnect_reuses_warmed_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = vec![0u8; 4096];
+            let n = stream.read(&mut buffer).await.unwrap();
+            stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 4\r\n\r\nwarm").await.unwrap();
+            let request = String::from_utf8_lossy(&buffer[..n]).to_string();
+            // 请求必须使用预连接，不应再有新的 TCP 连接
+            let new_connection = tokio::time::timeout(std::time::Duration::from_millis(300), listener.accept()).await.is_ok();
+            (request, new_connection)
+        });
+
+        let client = HttpClient::builder().no_browser_headers().build().unwrap();
+        let url = format!("http://127.0.0.1:{}/page", port);
+        client.preconnect(&url).await.unwrap();
+        assert_eq!(client.pool.len(), 1);
+
+        let response = client.get(&url).send().await.unwrap();
+        assert_eq!(response.body, b"warm");
+        assert_eq!(client.pool.len(), 0);
+
+        let (request, new_connection) = server.await.unwrap();
+        assert!(request.starts_with("GET /page HTTP/1.1\r\n"));
+        assert!(!new_connection, "request opened a new connection instead of using the preconnected one");
+    }
+
+    #[tokio::test]
+    async fn test_closed_preconnection_falls_back_to_new_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server = tokio::spawn(async move {
+            // 预连接建立后立即被服务器关闭
+            let (stream, _) = listener.accept().await.unwrap();
+            drop(stream);
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = vec![0u8; 4096];
+            let _ = stream.read(&mut buffer).await.unwrap();
+            stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nfresh").await.unwrap();
+        });
+
+        let client = HttpClient::builder().no_browser_headers().build().unwrap();
+        let url = format!("http://127.0.0.1:{}/", port);
+        client.preconnect(&url).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        let response = client.get(&url).send().await.unwrap();
+        assert_eq!(response.body, b"fresh");
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_closed_preconnection_does_not_resend_post() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            drop(stream);
+            // 不可重放的 POST 不应改用新连接重发
+            tokio::time::timeout(std::time::Duration::from_millis(300), listener.accept()).await.is_err()
+        });
+
+        let client = HttpClient::builder().no_browser_headers().build().unwrap();
+        let url = format!("http://127.0.0.1:{}/orders", port);
+        client.preconnect(&url).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        assert!(client.post(&url).body("item=1").send().await.is_err());
+        assert!(server.await.unwrap(), "POST was re-sent on a new connection");
+    }
+
+    #[tokio::test]
+    async fn test_closed_preconnection_resends_get() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server = tokio::spawn(async move {
+            // 预连接在使用前被服务端关闭
+            let (stream, _) = listener.accept().await.unwrap();
+            drop(stream);
+
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = vec![0u8; 4096];
+            let n = stream.read(&mut buffer).await.unwrap();
+            stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nfresh").await.unwrap();
+            String::from_utf8_lossy(&buffer[..n]).to_string()
+        });
+
+        let client = HttpClient::builder().no_browser_headers().build().unwrap();
+        let url = format!("http://127.0.0.1:{}/page", port);
+        client.preconnect(&url).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        let response = client.get(&url).send().await.unwrap();
+        assert_eq!(response.body, b"fresh");
+        assert!(server.await.unwrap().starts_with("GET /page HTTP/1.1\r\n"));
+    }
+
+    #[tokio::test]
+    async fn test_preconnect_closes_half_open_circuit() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        tokio::spawn(async move {
+            for response in [
+                &b"HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\n\r\n"[..],
+                b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok",
+            ] {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buffer = vec![0u8; 4096];
+                let _ = stream.read(&mut buffer).await;
+                let _ = stream.write_all(response).await;
+            }
+        });
+
+        let client = HttpClient::builder()
+            .no_browser_headers()
+            .circuit_breaker(1, std::time::Duration::from_millis(50))
+            .build()
+            .unwrap();
+        let url = format!("http://127.0.0.1:{}/", port);
+
+        assert_eq!(client.get(&url).send().await.unwrap().status_code, 503);
+        assert!(client.get(&url).send().await.unwrap_err().is_circuit_open());
+        tokio::time::sleep(std::time::Duration::from_millis(60)).await;
+
+        // 预连接作为试探成功后熔断器恢复，之后的请求不再受试探名额限制
+        client.preconnect(&url).await.unwrap();
+        let breaker = client.circuit_breaker.as_ref().unwrap();
+        let first = breaker.check("127.0.0.1", port).unwrap();
+        let second = breaker.check("127.0.0.1", port).unwrap();
+        drop((first, second));
+
+        assert_eq!(client.get(&url).send().await.unwrap().body, b"ok");
+    }
+
+    #[test]
+    fn test_redirect_not_followed_for_streamed_body() {
+        let raw = b"HTTP/1.1 307 Temporary Redirect\r\nLocation: /next\r\nContent-Length: 0\r\n\r\n".to_vec();
+        let response = Response::from_raw_bytes(raw).unwrap();
+        let original_url = url::Url::parse("http://example.com/upload").unwrap();
+
+        let buffered = Request::post("http://example.com/upload").body("data");
+        let next = redirect_request(&buffered, &response, &RedirectPolicy::default(), &original_url).unwrap();
+        assert_eq!(next.unwrap().url, "http://example.com/next");
+
+        let (_tx, rx) = mpsc::channel(1);
+        let streamed = Request::post("http://example.com/upload").body_channel(rx);
+        assert!(redirect_request(&streamed, &response, &RedirectPolicy::default(), &original_url).unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_consumed_body_channel_is_not_resent() {
+        let (tx, rx) = mpsc::channel(1);
+        drop(tx);
+        let request = Request::post("http://127.0.0.1:9/upload").body_channel(rx);
+        let body_stream = request.body_stream.clone().unwrap();
+        assert!(body_stream.take().is_some());
+
+        let error = HttpClient::new().send_request(request).await.unwrap_err();
+        assert!(error.to_string().contains("already consumed"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_get_json_and_post_json() {
+        #[derive(serde::Deserialize, Debug, PartialEq)]
+        struct User {
+            id: u32,
+            name: String,
+        }
+
+        let client = HttpClient::builder()
+            .with_mock(vec![
+                MockResponse::new(Method::GET, "http://api.example/users/1", 200)
+                    .json(&serde_json::json!({"id": 1, "name": "alice"}))
+                    .unwrap(),
+                MockResponse::new(Method::POST, "http://api.example/users", 201)
+                    .json(&serde_json::json!({"id": 2, "name": "bob"}))
+                    .unwrap(),
+                MockResponse::new(Method::GET, "http://api.example/broken", 500).body("<html>oops</html>"),
+            ])
+            .build()
+            .unwrap();
+
+        let user: User = client.get_json("http://api.example/users/1").await.unwrap();
+        assert_eq!(user, User { id: 1, name: "alice".to_string() });
+
+        let user: User = client
+            .post_json("http://api.example/users", &serde_json::json!({"name": "bob"}))
+            .await
+            .unwrap();
+        assert_eq!(user, User { id: 2, name: "bob".to_string() });
+
+        let error = client.get_json::<User>("http://api.example/broken").await.err().unwrap();
+        assert!(matches!(error, Error::Http { status: 500, .. }), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_allow_http09_response() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server = tokio::spawn(async move {
+            for _ in 0..2 {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buffer = vec![0u8; 4096];
+                let _ = stream.read(&mut buffer).await.unwrap();
+                stream.write_all(b"temperature=21.5\n").await.unwrap();
+            }
+        });
+
+        let url = format!("http://127.0.0.1:{}/sensor", port);
+        let client = HttpClient::builder().no_browser_headers().allow_http09(true).build().unwrap();
+        let response = client.get(&url).send().await.unwrap();
+        assert_eq!(response.status_code, 200);
+        assert_eq!(response.version, "HTTP/0.9");
+        assert!(response.headers.is_empty());
+        assert_eq!(response.body, b"temperature=21.5\n");
+
+        let client = HttpClient::builder().no_browser_headers().build().unwrap();
+        assert!(client.get(&url).send().await.is_err());
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_read_idle_timeout_aborts_trickling_response() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        // 第一个连接每 300 毫秒发送一个字节，第二个连接每 20 毫秒发送一个字节
+        let server = tokio::spawn(async move {
+            for interval in [300, 20] {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                tokio::spawn(async move {
+                    let mut buffer = vec![0u8; 4096];
+                    let _ = stream.read(&mut buffer).await.unwrap();
+                    stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\n").await.unwrap();
+                    for byte in b"hello" {
+                        tokio::time::sleep(std::time::Duration::from_millis(interval)).await;
+                        if stream.write_all(&[*byte]).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+
+        let url = format!("http://127.0.0.1:{}/slow", port);
+        let client = HttpClient::builder()
+            .no_browser_headers()
+            .read_idle_timeout(std::time::Duration::from_millis(100))
+            .build()
+            .unwrap();
+        let error = client.get(&url).send().await.err().unwrap();
+        assert!(matches!(error, Error::Timeout(_)), "{}", error);
+
+        let response = client.get(&url).send().await.unwrap();
+        assert_eq!(response.body, b"hello");
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_response_timings() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = vec![0u8; 4096];
+            let _ = stream.read(&mut buffer).await.unwrap();
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 4\r\n\r\n").await.unwrap();
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            stream.write_all(b"done").await.unwrap();
+        });
+
+        let client = HttpClient::builder().no_browser_headers().build().unwrap();
+        let response = client.get(&format!("http://localhost:{}/", port)).send().await.unwrap();
+        server.await.unwrap();
+
+        let timings = response.timings().unwrap();
+        let dns = timings.dns.unwrap();
+        let connect = timings.connect.unwrap();
+        let ttfb = timings.ttfb.unwrap();
+        assert_eq!(timings.tls_handshake, None);
+        assert!(dns <= connect && connect <= ttfb && ttfb <= timings.total, "{:?}", timings);
+        assert!(ttfb >= std::time::Duration::from_millis(20), "{:?}", timings);
+        assert!(timings.total >= ttfb + std::time::Duration::from_millis(15), "{:?}", timings);
+
+        assert!(Response::from_raw_bytes(b"HTTP/1.1 204 No Content\r\n\r\n".to_vec()).unwrap().timings().is_none());
+    }
+
+    /// 接受一个 WebSocket 握手并返回 101 响应的服务器，返回收到的握手请求
+    async fn spawn_websocket_server() -> (u16, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = vec![0u8; 4096];
+            let n = stream.read(&mut buffer).await.unwrap();
+            let upgrade = String::from_utf8_lossy(&buffer[..n]).to_string();
+            let key = header_values(&upgrade, "sec-websocket-key").remove(0);
+            let response = format!(
+                "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+                websocket::accept_key(&key)
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+            upgrade
+        });
+        (port, server)
+    }
+
+    #[tokio::test]
+    async fn test_websocket_through_http_proxy() {
+        let (ws_port, ws_server) = spawn_websocket_server().await;
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy_port = listener.local_addr().unwrap().port();
+
+        // 转发代理：按 CONNECT 的目标建立连接，之后双向转发数据
+        let proxy = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = vec![0u8; 4096];
+            let n = stream.read(&mut buffer).await.unwrap();
+            let connect = String::from_utf8_lossy(&buffer[..n]).to_string();
+            let target = connect.split_whitespace().nth(1).unwrap().to_string();
+            let mut upstream = tokio::net::TcpStream::connect(&target).await.unwrap();
+            stream.write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n").await.unwrap();
+            tokio::spawn(async move {
+                let _ = tokio::io::copy_bidirectional(&mut stream, &mut upstream).await;
+            });
+            connect
+        });
+
+        let client = HttpClient::builder()
+            .no_browser_headers()
+            .proxy(ProxyConfig::http("127.0.0.1", proxy_port))
+            .proxy_bypass_localhost(false)
+            .build()
+            .unwrap();
+        let upgrade = client.websocket(&format!("ws://127.0.0.1:{}/socket", ws_port)).await.unwrap();
+        assert_eq!(upgrade.response.status_code, 101);
+
+        let connect = proxy.await.unwrap();
+        assert!(connect.starts_with(&format!("CONNECT 127.0.0.1:{} HTTP/1.1\r\n", ws_port)), "{}", connect);
+        let upgrade = ws_server.await.unwrap();
+        assert!(upgrade.starts_with("GET /socket HTTP/1.1\r\n"), "{}", upgrade);
+        assert!(upgrade.contains("Upgrade: websocket\r\n"), "{}", upgrade);
+    }
+
+    /// 通过内存管道连接到模拟 WebSocket 服务器的连接器
+    #[derive(Debug)]
+    struct DuplexConnector;
+
+    struct DuplexConnection(tokio::io::DuplexStream);
+
+    #[async_trait::async_trait]
+    impl Connector for DuplexConnector {
+        async fn connect(&self, _parsed_url: &ParsedUrl) -> Result<Box<dyn AsyncConnection>> {
+            let (client, mut server) = tokio::io::duplex(4096);
+            tokio::spawn(async move {
+                let mut buffer = vec![0u8; 4096];
+                let n = server.read(&mut buffer).await.unwrap();
+                let upgrade = String::from_utf8_lossy(&buffer[..n]).to_string();
+                let key = header_values(&upgrade, "sec-websocket-key").remove(0);
+                let response = format!(
+                    "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+                    websocket::accept_key(&key)
+                );
+                server.write_all(response.as_bytes()).await.unwrap();
+            });
+            Ok(Box::new(DuplexConnection(client)))
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl AsyncConnection for DuplexConnection {
+        async fn send_request(&mut self, _request: &[u8], _parsed_url: &ParsedUrl) -> Result<Vec<u8>> {
+            Err(Error::other("not used"))
+        }
+
+        fn into_boxed_stream(self: Box<Self>) -> Result<BoxedStream> {
+            Ok(Box::new(self.0))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_websocket_uses_connector_and_timeout() {
+        let client = HttpClient::builder().connector(Arc::new(DuplexConnector)).build().unwrap();
+        let upgrade = client.websocket("ws://unreachable.invalid/socket").await.unwrap();
+        assert_eq!(upgrade.response.status_code, 101);
+
+        // 模拟传输层不支持协议升级，返回错误而不是绕过它直接连接
+        let client = HttpClient::builder().with_mock(Vec::new()).build().unwrap();
+        assert!(client.websocket("ws://unreachable.invalid/socket").await.is_err());
+
+        // 服务器不响应握手时受客户端超时约束
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move { listener.accept().await.unwrap() });
+        let client = HttpClient::builder()
+            .timeout(std::time::Duration::from_millis(200))
+            .build()
+            .unwrap();
+        let error = client.websocket(&format!("ws://127.0.0.1:{}/", port)).await.err().unwrap();
+        assert!(matches!(error, Error::Timeout(_)), "{}", error);
+        drop(server.await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_max_header_count() {
+        let mock = (0..5).fold(MockResponse::new(Method::GET, "http://many.example/", 200), |mock, i| {
+            mock.header(format!("X-H{}", i), "v")
+        });
+
+        let client = HttpClient::builder().with_mock(vec![mock.clone()]).max_header_count(3).build().unwrap();
+        let error = client.get("http://many.example/").send().await.unwrap_err();
+        assert!(error.to_string().contains("too many response headers"), "{}", error);
+
+        let client = HttpClient::builder().with_mock(vec![mock]).build().unwrap();
+        assert_eq!(client.get("http://many.example/").send().await.unwrap().status_code, 200);
+    }
+
+    #[tokio::test]
+    async fn test_same_origin_redirect_reuses_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server = tokio::spawn(async move {
+            let mut accepted = 0;
+            let mut requests = Vec::new();
+            while let Ok(Ok((mut stream, _))) =
+                tokio::time::timeout(std::time::Duration::from_millis(500), listener.accept()).await
+            {
+                accepted += 1;
+                let mut buffer = vec![0u8; 4096];
+                loop {
+                    let n = stream.read(&mut buffer).await.unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    let request = String::from_utf8_lossy(&buffer[..n]).to_string();
+                    let reply: &[u8] = if request.starts_with("GET /x HTTP/1.1") {
+                        b"HTTP/1.1 301 Moved Permanently\r\nLocation: /x/\r\nContent-Length: 0\r\n\r\n"
+                    } else {
+                        b"HTTP/1.1 200 OK\r\nContent-Length: 4\r\n\r\ndone"
+                    };
+                    requests.push(request);
+                    stream.write_all(reply).await.unwrap();
+                }
+            }
+            (accepted, requests)
+        });
+
+        let client = HttpClient::builder().no_browser_headers().max_redirects(5).build().unwrap();
+        let response = client.get(&format!("http://127.0.0.1:{}/x", port)).send().await.unwrap();
+        assert_eq!(response.status_code, 200);
+        assert_eq!(response.body, b"done");
+        drop(client);
+
+        let (accepted, requests) = server.await.unwrap();
+        assert_eq!(accepted, 1);
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1].starts_with("GET /x/ HTTP/1.1"));
+    }
+
+    #[tokio::test]
+    async fn test_keep_alive_connection_header() {
+        let (port, server) = spawn_capture_server(5).await;
+        let url = format!("http://127.0.0.1:{}/", port);
+        let builder = || HttpClient::builder().no_browser_headers();
+
+        builder().build().unwrap().get(&url).send().await.unwrap();
+        builder().max_redirects(5).build().unwrap().get(&url).send().await.unwrap();
+        builder().keep_alive(true).build().unwrap().get(&url).send().await.unwrap();
+        builder().max_redirects(5).keep_alive(false).build().unwrap().get(&url).send().await.unwrap();
+        // 显式设置的 Connection 不被覆盖
+        let client = builder().max_redirects(5).build().unwrap();
+        client.get(&url).header("Connection", "close").send().await.unwrap();
+
+        let requests = server.await.unwrap();
+        let connection: Vec<Vec<String>> = requests.iter().map(|request| header_values(request, "connection")).collect();
+        assert_eq!(connection, [["close"], ["keep-alive"], ["keep-alive"], ["close"], ["close"]]);
+    }
+
+    #[tokio::test]
+    async fn test_keep_alive_connection_returned_to_pool() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = vec![0u8; 4096];
+            for body in ["one", "two"] {
+                let _ = stream.read(&mut buffer).await.unwrap();
+                let response = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}", body.len(), body);
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+            // 第二个请求必须复用第一个请求的连接
+            tokio::time::timeout(std::time::Duration::from_millis(300), listener.accept()).await.is_err()
+        });
+
+        let client = HttpClient::builder().no_browser_headers().keep_alive(true).build().unwrap();
+        let url = format!("http://127.0.0.1:{}/", port);
+        assert_eq!(client.get(&url).send().await.unwrap().body, b"one");
+        assert_eq!(client.pool.len(), 1);
+        assert_eq!(client.get(&url).send().await.unwrap().body, b"two");
+        assert!(server.await.unwrap(), "second request opened a new connection");
+    }
+
+    #[tokio::test]
+    async fn test_interim_responses_skipped() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = vec![0u8; 4096];
+            let _ = stream.read(&mut buffer).await.unwrap();
+            stream.write_all(b"HTTP/1.1 100 Continue\r\n\r\n").await.unwrap();
+            stream.flush().await.unwrap();
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            stream
+                .write_all(b"HTTP/1.1 103 Early Hints\r\nLink: </style.css>; rel=preload\r\n\r\nHTTP/1.1 200 OK\r\nContent-Length: 4\r\n\r\nfine")
+                .await
+                .unwrap();
+        });
+
+        let client = HttpClient::builder().no_browser_headers().build().unwrap();
+        let response = client.get(&format!("http://127.0.0.1:{}/", port)).send().await.unwrap();
+        assert_eq!(response.status_code, 200);
+        assert_eq!(response.body, b"fine");
+        assert_eq!(response.get_header("link"), None);
+    }
+
+    #[tokio::test]
+    async fn test_oversized_response_headers_rejected() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = vec![0u8; 4096];
+            let _ = stream.read(&mut buffer).await.unwrap();
+            // 持续发送头部而不发送结束空行
+            let _ = stream.write_all(b"HTTP/1.1 200 OK\r\n").await;
+            let line = format!("X-Filler: {}\r\n", "a".repeat(1000));
+            while stream.write_all(line.as_bytes()).await.is_ok() {}
+        });
+
+        let client = HttpClient::builder().no_browser_headers().max_header_size(8 * 1024).build().unwrap();
+        let error = client.get(&format!("http://127.0.0.1:{}/", port)).send().await.unwrap_err();
+        assert!(error.to_string().contains("response headers too large"));
+    }
+
+    /// 启动一个转发 CONNECT 隧道的本地代理，返回端口和收到的 CONNECT 请求行
+    async fn spawn_forwarding_proxy() -> (u16, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let handle = tokio::spawn(async move {
+            let (mut client, _) = listener.accept().await.unwrap();
+            let mut buffer = vec![0u8; 4096];
+            let n = client.read(&mut buffer).await.unwrap();
+            let request = String::from_utf8_lossy(&buffer[..n]).to_string();
+            let request_line = request.lines().next().unwrap().to_string();
+            let target = request_line.split_whitespace().nth(1).unwrap().to_string();
+
+            let mut upstream = tokio::net::TcpStream::connect(&target).await.unwrap();
+            client.write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n").await.unwrap();
+            let _ = tokio::io::copy_bidirectional(&mut client, &mut upstream).await;
+            request_line
+        });
+
+        (port, handle)
+    }
+
+    #[tokio::test]
+    async fn test_proxy_chain_connects_in_order() {
+        let (last_port, last_proxy) = spawn_capture_server(1).await;
+        let (first_port, first_proxy) = spawn_forwarding_proxy().await;
+
+        let client = HttpClient::builder()
+            .no_browser_headers()
+            .proxy_chain(vec![
+                ProxyConfig::http("127.0.0.1", first_port),
+                ProxyConfig::http("127.0.0.1", last_port),
+            ])
+            .build()
+            .unwrap();
+        let response = client.get("http://example.com/chained").send().await.unwrap();
+        assert_eq!(response.status_code, 200);
+
+        assert_eq!(
+            first_proxy.await.unwrap(),
+            format!("CONNECT 127.0.0.1:{} HTTP/1.1", last_port)
+        );
+        let requests = last_proxy.await.unwrap();
+        assert!(requests[0].starts_with("GET http://example.com/chained HTTP/1.1\r\n"));
+    }
+
+    #[tokio::test]
+    async fn test_proxy_chain_tunnels_to_https_target() {
+        use crate::connection::tls::test_support::{localhost_acceptor, HostVerifier};
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let target_port = listener.local_addr().unwrap().port();
+        let acceptor = localhost_acceptor(Vec::new());
+        // 目标服务器在两层隧道之后完成 TLS 握手，返回收到的 SNI 和请求行
+        let target = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut stream = acceptor.accept(stream).await.unwrap();
+            let server_name = stream.get_ref().1.server_name().map(str::to_string);
+            let mut buffer = vec![0u8; 4096];
+            let n = stream.read(&mut buffer).await.unwrap();
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 6\r\n\r\nsecure")
+                .await
+                .unwrap();
+            let _ = stream.shutdown().await;
+            (server_name, String::from_utf8_lossy(&buffer[..n]).lines().next().unwrap().to_string())
+        });
+        let (last_port, last_proxy) = spawn_forwarding_proxy().await;
+        let (first_port, first_proxy) = spawn_forwarding_proxy().await;
+
+        let client = HttpClient::builder()
+            .no_browser_headers()
+            .custom_cert_verifier(HostVerifier::new("localhost"))
+            .proxy_bypass_localhost(false)
+            .proxy_chain(vec![
+                ProxyConfig::http("127.0.0.1", first_port),
+                ProxyConfig::http("127.0.0.1", last_port),
+            ])
+            .build()
+            .unwrap();
+        let response = client
+            .get(&format!("https://localhost:{}/chained", target_port))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.body, b"secure");
+
+        assert_eq!(
+            first_proxy.await.unwrap(),
+            format!("CONNECT 127.0.0.1:{} HTTP/1.1", last_port)
+        );
+        assert_eq!(
+            last_proxy.await.unwrap(),
+            format!("CONNECT localhost:{} HTTP/1.1", target_port)
+        );
+        let (server_name, request_line) = target.await.unwrap();
+        assert_eq!(server_name.as_deref(), Some("localhost"));
+        assert_eq!(request_line, "GET /chained HTTP/1.1");
+    }
+
+    #[tokio::test]
+    async fn test_proxy_chain_reports_failed_hop() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = vec![0u8; 4096];
+            let _ = stream.read(&mut buffer).await.unwrap();
+            stream.write_all(b"HTTP/1.1 403 Forbidden\r\n\r\n").await.unwrap();
+        });
+
+        let client = HttpClient::builder()
+            .no_browser_headers()
+            .proxy_chain(vec![
+                ProxyConfig::http("127.0.0.1", port),
+                ProxyConfig::http("127.0.0.1", 9),
+            ])
+            .build()
+            .unwrap();
+        let error = client.get("http://example.com/").send().await.unwrap_err();
+        let message = error.to_string();
+        assert!(message.contains(&format!("Proxy hop 1 (127.0.0.1:{})", port)), "{}", message);
+        assert!(message.contains("403"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_reads_responses_in_order() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut received = Vec::new();
+            let mut buffer = vec![0u8; 4096];
+            // 等待三个请求全部到达后再一次性写出全部响应
+            while received.windows(4).filter(|w| *w == b"\r\n\r\n").count() < 3 {
+                let n = stream.read(&mut buffer).await.unwrap();
+                received.extend_from_slice(&buffer[..n]);
+            }
+            stream
+                .write_all(
+                    b"HTTP/1.1 200 OK\r\nContent-Length: 1\r\n\r\na\
+                      HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n1\r\nb\r\n0\r\n\r\n\
+                      HTTP/1.1 404 Not Found\r\nContent-Length: 1\r\n\r\nc",
+                )
+                .await
+                .unwrap();
+            String::from_utf8(received).unwrap()
+        });
+
+        let client = HttpClient::builder().no_browser_headers().build().unwrap();
+        let base = format!("http://127.0.0.1:{}", port);
+        let responses = client
+            .pipeline(vec![
+                Request::get(&format!("{}/a", base)),
+                Request::get(&format!("{}/b", base)),
+                Request::get(&format!("{}/c", base)),
+            ])
+            .await
+            .unwrap();
+
+        let bodies: Vec<&[u8]> = responses.iter().map(|r| r.body.as_slice()).collect();
+        assert_eq!(bodies, vec![&b"a"[..], b"b", b"c"]);
+        assert_eq!(responses[2].status_code, 404);
+
+        let received = server.await.unwrap();
+        let order: Vec<&str> = received.lines().filter(|line| line.starts_with("GET ")).collect();
+        assert_eq!(order, vec!["GET /a HTTP/1.1", "GET /b HTTP/1.1", "GET /c HTTP/1.1"]);
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_rejects_non_idempotent() {
+        let client = HttpClient::new();
+        let result = client
+            .pipeline(vec![Request::get("http://127.0.0.1:1/"), Request::post("http://127.0.0.1:1/")])
+            .await;
+        assert!(result.unwrap_err().to_string().contains("non-idempotent POST"));
+    }
+
+    #[tokio::test]
+    async fn test_timeout_covers_stalled_tls_handshake() {
+        // 接受 TCP 连接但从不完成 TLS 握手
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = vec![0u8; 4096];
+            while stream.read(&mut buffer).await.map(|n| n > 0).unwrap_or(false) {}
+        });
+
+        let client = HttpClient::builder()
+            .timeout(std::time::Duration::from_millis(200))
+            .build()
+            .unwrap();
+        let started = std::time::Instant::now();
+        let error = client
+            .get(&format!("https://127.0.0.1:{}/", port))
+            .send()
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error, Error::Timeout(_)), "unexpected error: {}", error);
+        assert!(started.elapsed() < std::time::Duration::from_secs(2));
+        // 超时后连接被关闭
+        tokio::time::timeout(std::time::Duration::from_secs(1), server).await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_connection_closed_before_response() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            for reply in [&b""[..], b"HTTP/1.1 200 OK\r\nContent-"] {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buffer = vec![0u8; 4096];
+                let _ = stream.read(&mut buffer).await.unwrap();
+                stream.write_all(reply).await.unwrap();
+            }
+        });
+
+        let client = HttpClient::builder().no_browser_headers().build().unwrap();
+        let url = format!("http://127.0.0.1:{}/", port);
+
+        let error = client.get(&url).send().await.unwrap_err();
+        assert!(
+            matches!(error, Error::Connection(ref message) if message == "server closed connection before sending response"),
+            "{}",
+            error
+        );
+        let error = client.get(&url).send().await.unwrap_err();
+        assert!(error.to_string().contains("before completing response headers"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_accept_language_precedence() {
+        let (port, server) = spawn_capture_server(3).await;
+        let url = format!("http://127.0.0.1:{}/", port);
+
+        let client = HttpClient::builder().build().unwrap();
+        client.get(&url).accept_language("en-US").send().await.unwrap();
+
+        let client = HttpClient::builder().accept_language("fr-FR").build().unwrap();
+        client.get(&url).send().await.unwrap();
+        client.get(&url).accept_language("de-DE").send().await.unwrap();
+
+        let requests = server.await.unwrap();
+        assert_eq!(header_values(&requests[0], "accept-language"), vec!["en-US"]);
+        assert_eq!(header_values(&requests[1], "accept-language"), vec!["fr-FR"]);
+        assert_eq!(header_values(&requests[2], "accept-language"), vec!["de-DE"]);
+    }
+
+    #[tokio::test]
+    async fn test_auto_compress_request_body() {
+        let (port, server) = spawn_capture_server(2).await;
+        let client = HttpClient::builder()
+            .no_browser_headers()
+            .auto_compress_request(true)
+            .build()
+            .unwrap();
+        let url = format!("http://127.0.0.1:{}/logs", port);
+
+        let large = "log line\n".repeat(1000);
+        client.post(&url).body(large.clone()).send().await.unwrap();
+        client.post(&url).body("tiny").send().await.unwrap();
+
+        let requests = server.await.unwrap();
+        assert_eq!(header_values(&requests[0], "content-encoding"), vec!["gzip"]);
+        let length: usize = header_values(&requests[0], "content-length")[0].parse().unwrap();
+        assert!(length < large.len());
+
+        assert!(header_values(&requests[1], "content-encoding").is_empty());
+        assert_eq!(header_values(&requests[1], "content-length"), vec!["4"]);
+        assert!(requests[1].ends_with("\r\n\r\ntiny"));
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker_fails_fast() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let accepted = Arc::new(AtomicUsize::new(0));
+
+        let server_accepted = accepted.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                server_accepted.fetch_add(1, Ordering::SeqCst);
+                let mut buffer = vec![0u8; 4096];
+                let _ = stream.read(&mut buffer).await;
+                let _ = stream
+                    .write_all(b"HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\n\r\n")
+                    .await;
+            }
+        });
+
+        let client = HttpClient::builder()
+            .no_browser_headers()
+            .circuit_breaker(2, std::time::Duration::from_secs(60))
+            .build()
+            .unwrap();
+        let url = format!("http://127.0.0.1:{}/", port);
+
+        for _ in 0..2 {
+            assert_eq!(client.get(&url).send().await.unwrap().status_code, 503);
+        }
+        for _ in 0..3 {
+            let error = client.get(&url).send().await.unwrap_err();
+            assert!(error.is_circuit_open(), "{}", error);
+        }
+        assert_eq!(accepted.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_circuit_trial_timeout_releases_slot() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        // 第一个请求返回 503 触发熔断，试探请求不响应直到超时，之后的请求正常返回
+        tokio::spawn(async move {
+            let mut held = Vec::new();
+            for response in [&b"HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\n\r\n"[..], b"", b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n"] {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buffer = vec![0u8; 4096];
+                let _ = stream.read(&mut buffer).await;
+                let _ = stream.write_all(response).await;
+                held.push(stream);
+            }
+        });
+
+        let client = HttpClient::builder()
+            .no_browser_headers()
+            .circuit_breaker(1, std::time::Duration::from_millis(50))
+            .timeout(std::time::Duration::from_millis(200))
+            .build()
+            .unwrap();
+        let url = format!("http://127.0.0.1:{}/", port);
+
+        assert_eq!(client.get(&url).send().await.unwrap().status_code, 503);
+        tokio::time::sleep(std::time::Duration::from_millis(60)).await;
+        let error = client.get(&url).send().await.unwrap_err();
+        assert!(matches!(error, Error::Timeout(_)), "{}", error);
+        assert_eq!(client.get(&url).send().await.unwrap().status_code, 200);
+    }
+
+    #[tokio::test]
+    async fn test_partial_response_on_reset() {
+        let port = spawn_reset_server().await;
+        let client = HttpClient::builder()
+            .no_browser_headers()
+            .allow_partial_response(true)
+            .build()
+            .unwrap();
+        let response = client.get(&format!("http://127.0.0.1:{}/", port)).send().await.unwrap();
+        assert!(!response.is_complete());
+        assert_eq!(response.body, b"partial");
+
+        let port = spawn_reset_server().await;
+        let client = HttpClient::builder().no_browser_headers().build().unwrap();
+        assert!(client.get(&format!("http://127.0.0.1:{}/", port)).send().await.is_err());
+    }
+
+    async fn spawn_short_body_server() -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = vec![0u8; 4096];
+            let _ = stream.read(&mut buffer).await.unwrap();
+            // 响应体短于 Content-Length 时正常关闭连接
+            stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\n\r\nshort").await.unwrap();
+        });
+
+        port
+    }
+
+    #[tokio::test]
+    async fn test_short_content_length_body() {
+        let port = spawn_short_body_server().await;
+        let client = HttpClient::builder().no_browser_headers().build().unwrap();
+        let error = client.get(&format!("http://127.0.0.1:{}/", port)).send().await.unwrap_err();
+        assert!(matches!(error, Error::Connection(_)), "{}", error);
+
+        let port = spawn_short_body_server().await;
+        let client = HttpClient::builder()
+            .no_browser_headers()
+            .allow_partial_response(true)
+            .build()
+            .unwrap();
+        let response = client.get(&format!("http://127.0.0.1:{}/", port)).send().await.unwrap();
+        assert!(!response.is_complete());
+        assert_eq!(response.body, b"short");
+    }
+}
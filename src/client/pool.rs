@@ -0,0 +1,122 @@
+//! 预先建立的空闲连接
+//!
+//! 按来源（协议、主机、端口）保存 `HttpClient::preconnect` 建立的连接，
+//! 之后发往同一来源的请求优先取出使用，每个连接只使用一次。
+//! 每个来源最多保存 `MAX_IDLE_PER_ORIGIN` 个连接，空闲超过 `IDLE_TIMEOUT` 的连接被丢弃
+
+use crate::connection::AsyncConnection;
+use crate::utils::ParsedUrl;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 每个来源最多保存的空闲连接数
+const MAX_IDLE_PER_ORIGIN: usize = 8;
+
+/// 空闲连接的最长保存时间，超过后服务器很可能已关闭连接
+const IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// 连接池的键：(是否 HTTPS, 小写主机名, 端口)
+type OriginKey = (bool, String, u16);
+
+/// 空闲连接及其放入连接池的时间
+type IdleConnection = (Instant, Box<dyn AsyncConnection>);
+
+/// 按来源保存的空闲连接
+pub(crate) struct ConnectionPool {
+    max_idle_per_origin: usize,
+    idle_timeout: Duration,
+    /// 每个来源的空闲连接，按放入顺序排列
+    idle: Mutex<HashMap<OriginKey, Vec<IdleConnection>>>,
+}
+
+impl Default for ConnectionPool {
+    fn default() -> Self {
+        Self::new(MAX_IDLE_PER_ORIGIN, IDLE_TIMEOUT)
+    }
+}
+
+impl ConnectionPool {
+    /// 创建连接池，`max_idle_per_origin` 为每个来源的连接上限，`idle_timeout` 为最长空闲时间
+    pub(crate) fn new(max_idle_per_origin: usize, idle_timeout: Duration) -> Self {
+        Self {
+            max_idle_per_origin: max_idle_per_origin.max(1),
+            idle_timeout,
+            idle: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 放入一个空闲连接，该来源的连接已达上限时丢弃最早放入的连接
+    pub(crate) fn put(&self, parsed_url: &ParsedUrl, connection: Box<dyn AsyncConnection>) {
+        let mut idle = self.idle.lock().unwrap();
+        let connections = idle.entry(origin_key(parsed_url)).or_default();
+        connections.retain(|(since, _)| since.elapsed() < self.idle_timeout);
+        if connections.len() >= self.max_idle_per_origin {
+            connections.remove(0);
+        }
+        connections.push((Instant::now(), connection));
+    }
+
+    /// 取出一个发往该来源、未超过空闲时间的连接（优先最近放入的）
+    pub(crate) fn take(&self, parsed_url: &ParsedUrl) -> Option<Box<dyn AsyncConnection>> {
+        let mut idle = self.idle.lock().unwrap();
+        let key = origin_key(parsed_url);
+        let connections = idle.get_mut(&key)?;
+        connections.retain(|(since, _)| since.elapsed() < self.idle_timeout);
+        let connection = connections.pop().map(|(_, connection)| connection);
+        if connections.is_empty() {
+            idle.remove(&key);
+        }
+        connection
+    }
+
+    /// 空闲连接数量
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.idle.lock().unwrap().values().map(Vec::len).sum()
+    }
+}
+
+fn origin_key(parsed_url: &ParsedUrl) -> OriginKey {
+    (parsed_url.is_https, parsed_url.hostname.to_ascii_lowercase(), parsed_url.port)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+    use crate::utils::parse_host_port;
+    use async_trait::async_trait;
+
+    /// 只用于占位的连接
+    struct PlaceholderConnection;
+
+    #[async_trait]
+    impl AsyncConnection for PlaceholderConnection {
+        async fn send_request(&mut self, _request: &[u8], _parsed_url: &ParsedUrl) -> Result<Vec<u8>> {
+            Ok(Vec::new())
+        }
+    }
+
+    #[test]
+    fn test_caps_connections_per_origin() {
+        let pool = ConnectionPool::new(2, IDLE_TIMEOUT);
+        let url = parse_host_port("http://example.com/").unwrap();
+        for _ in 0..3 {
+            pool.put(&url, Box::new(PlaceholderConnection));
+        }
+        pool.put(&parse_host_port("https://example.com/").unwrap(), Box::new(PlaceholderConnection));
+        assert_eq!(pool.len(), 3);
+    }
+
+    #[test]
+    fn test_drops_expired_connections() {
+        let pool = ConnectionPool::new(MAX_IDLE_PER_ORIGIN, Duration::from_millis(20));
+        let url = parse_host_port("http://example.com/").unwrap();
+        pool.put(&url, Box::new(PlaceholderConnection));
+        std::thread::sleep(Duration::from_millis(30));
+
+        assert!(pool.take(&url).is_none());
+        assert_eq!(pool.len(), 0);
+    }
+}
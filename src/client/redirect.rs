@@ -0,0 +1,84 @@
+//! 重定向策略
+//!
+//! 决定是否跟随指向某个 URL 的重定向，与 `max_redirects` 的次数限制配合使用
+
+use std::fmt;
+use std::sync::Arc;
+use url::Url;
+
+/// 重定向策略
+#[derive(Clone, Default)]
+pub enum RedirectPolicy {
+    /// 跟随所有重定向（只受 `max_redirects` 限制）
+    #[default]
+    All,
+    /// 只跟随与原始请求同源（协议、主机、端口均相同）的重定向，
+    /// 拒绝 https 到 http 的降级以及同一主机其他端口的重定向
+    SameHost,
+    /// 由自定义函数决定是否跟随指向该 URL 的重定向
+    Custom(Arc<dyn Fn(&Url) -> bool + Send + Sync>),
+}
+
+impl RedirectPolicy {
+    /// 使用自定义函数创建重定向策略，函数返回 true 时跟随重定向
+    pub fn custom<F>(allow: F) -> Self
+    where
+        F: Fn(&Url) -> bool + Send + Sync + 'static,
+    {
+        RedirectPolicy::Custom(Arc::new(allow))
+    }
+
+    /// 判断从 `original` 发起的请求是否可以重定向到 `next`
+    pub fn allows(&self, original: &Url, next: &Url) -> bool {
+        match self {
+            RedirectPolicy::All => true,
+            RedirectPolicy::SameHost => original.origin().is_tuple() && original.origin() == next.origin(),
+            RedirectPolicy::Custom(allow) => allow(next),
+        }
+    }
+}
+
+impl fmt::Debug for RedirectPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RedirectPolicy::All => f.write_str("All"),
+            RedirectPolicy::SameHost => f.write_str("SameHost"),
+            RedirectPolicy::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redirect_policy_allows() {
+        let original = Url::parse("https://Example.com/start").unwrap();
+        let same_origin = Url::parse("https://example.com:443/next").unwrap();
+        let other_host = Url::parse("https://evil.example.net/").unwrap();
+
+        assert!(RedirectPolicy::All.allows(&original, &other_host));
+        assert!(RedirectPolicy::SameHost.allows(&original, &same_origin));
+        assert!(!RedirectPolicy::SameHost.allows(&original, &other_host));
+
+        let allowlist = RedirectPolicy::custom(|url| url.host_str() == Some("evil.example.net"));
+        assert!(allowlist.allows(&original, &other_host));
+        assert!(!allowlist.allows(&original, &same_origin));
+    }
+
+    #[test]
+    fn test_same_host_rejects_scheme_downgrade() {
+        let original = Url::parse("https://example.com/login").unwrap();
+        let downgraded = Url::parse("http://example.com/login").unwrap();
+        assert!(!RedirectPolicy::SameHost.allows(&original, &downgraded));
+    }
+
+    #[test]
+    fn test_same_host_rejects_port_change() {
+        let original = Url::parse("http://example.com/").unwrap();
+        let other_port = Url::parse("http://example.com:8080/").unwrap();
+        assert!(!RedirectPolicy::SameHost.allows(&original, &other_port));
+        assert!(RedirectPolicy::SameHost.allows(&original, &Url::parse("http://EXAMPLE.com:80/x").unwrap()));
+    }
+}
@@ -2,119 +2,619 @@
 //!
 //! 包含异步 Connection trait 和 AsyncHttpConnection 实现
 
-use crate::connection::{ProxyConfig, AsyncProxyConnection, AsyncTlsManager};
+use crate::chunked::ChunkedParser;
+use crate::connection::{ProxyConfig, AsyncProxyConnection, AsyncTlsManager, ConnectOptions};
+use crate::connection::options::{connect_tcp_timed, socket_handle};
+use crate::connection::proxy::hop_error;
 use crate::error::{Error, Result};
+use crate::response::is_keep_alive;
+use crate::timings::ConnectionTimings;
 use crate::utils::ParsedUrl;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::TcpStream;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadBuf};
 use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::sync::mpsc;
+
+/// 异步字节流 trait，统一 TCP 流和 TLS 流
+pub trait AsyncStream: AsyncRead + AsyncWrite + Unpin + Send + Sync {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send + Sync> AsyncStream for T {}
+
+/// 装箱的异步字节流
+pub type BoxedStream = Box<dyn AsyncStream>;
+
+/// 默认的响应头部大小上限（64 KiB）
+pub const DEFAULT_MAX_HEADER_SIZE: usize = 64 * 1024;
 
 /// 异步连接接口 trait
 #[async_trait]
 pub trait AsyncConnection: Send + Sync {
     /// 发送请求并获取响应
-    async fn send_request(&mut self, request: &str, parsed_url: &ParsedUrl) -> Result<Vec<u8>>;
+    async fn send_request(&mut self, request: &[u8], parsed_url: &ParsedUrl) -> Result<Vec<u8>>;
+
+    /// 依次发送多个请求并按顺序返回响应
+    ///
+    /// 默认逐个调用 `send_request`；支持 HTTP/1.1 管线化的连接会先连续写出全部请求再依次读取响应
+    async fn send_requests(&mut self, requests: &[Vec<u8>], parsed_url: &ParsedUrl) -> Result<Vec<Vec<u8>>> {
+        let mut responses = Vec::with_capacity(requests.len());
+        for request in requests {
+            responses.push(self.send_request(request, parsed_url).await?);
+        }
+        Ok(responses)
+    }
+
+    /// 发送请求头后以 chunked 编码逐块写出通道中的请求体，通道关闭后写出结束块并读取响应
+    ///
+    /// 默认先收集全部分块再调用 `send_request`；`AsyncHttpConnection` 会在收到分块时立即写出
+    async fn send_streaming_request(
+        &mut self,
+        head: &[u8],
+        mut body: mpsc::Receiver<Bytes>,
+        parsed_url: &ParsedUrl,
+    ) -> Result<Vec<u8>> {
+        let mut request = head.to_vec();
+        while let Some(chunk) = body.recv().await {
+            request.extend_from_slice(&encode_chunk(&chunk));
+        }
+        request.extend_from_slice(LAST_CHUNK);
+        self.send_request(&request, parsed_url).await
+    }
+
+    /// 正常完成请求后关闭连接，TLS 连接会先发送 close_notify
+    async fn close(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// 上一次响应是否因连接中断而不完整
+    fn is_truncated(&self) -> bool {
+        false
+    }
+
+    /// TLS 握手协商的 ALPN 协议，明文连接或未协商时为 None
+    fn alpn_protocol(&self) -> Option<&[u8]> {
+        None
+    }
+
+    /// 建立连接和读取最近一次响应时记录的各阶段时刻
+    fn timings(&self) -> ConnectionTimings {
+        ConnectionTimings::default()
+    }
+
+    /// 上一次响应结束后连接是否仍可用于发送下一个请求
+    fn is_reusable(&self) -> bool {
+        false
+    }
+
+    /// 设置底层 TCP 连接的 TCP_NODELAY，无法调整的连接（如经过代理的连接）忽略该设置
+    fn set_nodelay(&mut self, _nodelay: bool) -> Result<()> {
+        Ok(())
+    }
+
+    /// 取出底层字节流用于协议升级（如 WebSocket），不支持的连接返回错误
+    fn into_boxed_stream(self: Box<Self>) -> Result<BoxedStream> {
+        Err(Error::connection("connection does not support protocol upgrades"))
+    }
+}
+
+/// 连接器 trait，负责为目标 URL 创建连接
+///
+/// 客户端默认直接建立 TCP/TLS 连接，可替换为自定义实现（如 `MockTransport`）
+#[async_trait]
+pub trait Connector: Send + Sync + std::fmt::Debug {
+    /// 创建到目标服务器的连接
+    async fn connect(&self, parsed_url: &ParsedUrl) -> Result<Box<dyn AsyncConnection>>;
 }
 
 /// 异步 HTTP 连接结构体
 /// 负责异步 HTTP 数据传输，支持直接连接和代理连接
+/// HTTPS 目标在建立连接时完成 TLS 握手，连接复用时无需重新握手
 pub struct AsyncHttpConnection {
-    stream: TcpStream,
-    tls_manager: AsyncTlsManager,
+    stream: BoxedStream,
+    allow_partial_response: bool,
+    allow_http09: bool,
+    max_header_size: usize,
+    /// 两次读取之间允许的最长空闲时间
+    read_idle_timeout: Option<Duration>,
+    truncated: bool,
+    reusable: bool,
+    /// 已读取但属于下一个响应的数据
+    buffered: Vec<u8>,
+    /// TLS 握手协商的 ALPN 协议
+    alpn_protocol: Option<Vec<u8>>,
+    /// 各阶段完成的时刻
+    timings: ConnectionTimings,
+    /// 直接连接时底层 TCP 套接字的句柄，用于按请求调整套接字选项
+    socket: Option<std::net::TcpStream>,
 }
 
 impl AsyncHttpConnection {
     /// 创建直接连接
     pub async fn direct(parsed_url: &ParsedUrl) -> Result<Self> {
+        Self::direct_with_options(parsed_url, &ConnectOptions::default()).await
+    }
+
+    /// 按连接选项创建直接连接
+    pub async fn direct_with_options(parsed_url: &ParsedUrl, options: &ConnectOptions) -> Result<Self> {
         let addr = format!("{}:{}", parsed_url.hostname, parsed_url.port);
-        let stream = tokio::net::TcpStream::connect(&addr)
+        let (stream, dns_resolved) = connect_tcp_timed(&addr, options)
             .await
-            .map_err(|e| Error::connection(format!("Failed to connect to {}: {}", addr, e)))?;
+            .map_err(|e| match e {
+                Error::Io(e) => Error::connection(format!("Failed to connect to {}: {}", addr, e)),
+                e => e,
+            })?;
 
         // 设置 TCP 参数
         stream.set_nodelay(true)
             .map_err(|e| Error::connection(format!("Failed to set TCP_NODELAY: {}", e)))?;
 
-        Ok(Self {
-            stream,
-            tls_manager: AsyncTlsManager::new(),
-        })
+        let (stream, socket) = socket_handle(stream)
+            .map_err(|e| Error::connection(format!("Failed to duplicate socket handle: {}", e)))?;
+
+        let timings = ConnectionTimings {
+            dns_resolved: Some(dns_resolved),
+            connected: Some(Instant::now()),
+            ..ConnectionTimings::default()
+        };
+        let mut connection = Self::from_stream(Box::new(stream), parsed_url, options, timings).await?;
+        connection.socket = Some(socket);
+        Ok(connection)
     }
 
     /// 创建代理连接
+    /// HTTPS 目标通过 CONNECT 建立隧道，HTTP 目标直接将请求发送给代理
     pub async fn via_proxy(proxy_config: ProxyConfig, parsed_url: &ParsedUrl) -> Result<Self> {
-        let mut proxy_conn = AsyncProxyConnection::new(proxy_config).await?;
-        proxy_conn.establish_tunnel(&parsed_url.hostname, parsed_url.port).await?;
+        Self::via_proxy_with_options(proxy_config, parsed_url, &ConnectOptions::default()).await
+    }
 
-        // 提取 stream，避免部分移动问题
-        let stream = proxy_conn.stream;
+    /// 按连接选项创建代理连接
+    pub async fn via_proxy_with_options(
+        proxy_config: ProxyConfig,
+        parsed_url: &ParsedUrl,
+        options: &ConnectOptions,
+    ) -> Result<Self> {
+        let mut proxy_conn = AsyncProxyConnection::with_options(proxy_config, options).await?;
+        if parsed_url.is_https {
+            proxy_conn.establish_tunnel(&parsed_url.hostname, parsed_url.port).await?;
+        }
+
+        Self::from_stream(proxy_conn.stream, parsed_url, options, ConnectionTimings::connected_now()).await
+    }
+
+    /// 经过代理链创建连接
+    /// 依次经过每个代理建立隧道，HTTPS 目标由最后一个代理 CONNECT 到目标服务器，
+    /// HTTP 目标的请求直接发送给最后一个代理
+    pub async fn via_proxy_chain_with_options(
+        proxy_chain: &[ProxyConfig],
+        parsed_url: &ParsedUrl,
+        options: &ConnectOptions,
+    ) -> Result<Self> {
+        let mut proxy_conn = AsyncProxyConnection::chain(proxy_chain, options).await?;
+        if parsed_url.is_https
+            && let Some(last) = proxy_chain.last()
+        {
+            proxy_conn
+                .establish_tunnel(&parsed_url.hostname, parsed_url.port)
+                .await
+                .map_err(|e| hop_error(proxy_chain.len(), last, e))?;
+        }
+
+        Self::from_stream(proxy_conn.stream, parsed_url, options, ConnectionTimings::connected_now()).await
+    }
+
+    /// 经过代理链建立到目标服务器的 CONNECT 隧道，目标为 HTTP 时同样使用隧道
+    ///
+    /// 用于需要独占端到端连接的协议（如 WebSocket 升级），HTTPS 目标在隧道内完成 TLS 握手
+    pub async fn tunnel_via_proxy_chain_with_options(
+        proxy_chain: &[ProxyConfig],
+        parsed_url: &ParsedUrl,
+        options: &ConnectOptions,
+    ) -> Result<Self> {
+        let mut proxy_conn = AsyncProxyConnection::chain(proxy_chain, options).await?;
+        if let Some(last) = proxy_chain.last() {
+            proxy_conn
+                .establish_tunnel(&parsed_url.hostname, parsed_url.port)
+                .await
+                .map_err(|e| hop_error(proxy_chain.len(), last, e))?;
+        }
+
+        Self::from_stream(proxy_conn.stream, parsed_url, options, ConnectionTimings::connected_now()).await
+    }
+
+    /// 基于已建立的流创建连接，HTTPS 目标会在此完成 TLS 握手
+    async fn from_stream(
+        stream: BoxedStream,
+        parsed_url: &ParsedUrl,
+        options: &ConnectOptions,
+        mut timings: ConnectionTimings,
+    ) -> Result<Self> {
+        // ALPN 只向目标服务器通告，不用于与 HTTPS 代理的握手
+        let (stream, alpn_protocol): (BoxedStream, _) = if parsed_url.is_https {
+            let tls_stream = AsyncTlsManager::from_options(options)
+                .with_alpn_protocols(options.alpn_protocols.clone())
+                .create_tls_stream(stream, &parsed_url.hostname)
+                .await?;
+            let alpn_protocol = tls_stream.get_ref().1.alpn_protocol().map(<[u8]>::to_vec);
+            timings.tls_handshaken = Some(Instant::now());
+            (Box::new(tls_stream), alpn_protocol)
+        } else {
+            (stream, None)
+        };
 
         Ok(Self {
             stream,
-            tls_manager: AsyncTlsManager::new(),
+            allow_partial_response: false,
+            allow_http09: false,
+            max_header_size: DEFAULT_MAX_HEADER_SIZE,
+            read_idle_timeout: None,
+            truncated: false,
+            reusable: false,
+            buffered: Vec::new(),
+            alpn_protocol,
+            timings,
+            socket: None,
         })
     }
+
+    /// 取出底层连接（如 WebSocket 升级后交给其他协议使用）
+    pub fn into_stream(self) -> BoxedStream {
+        self.stream
+    }
+
+    /// 设置读取响应时遇到连接错误是否返回已接收的部分响应
+    pub fn with_partial_response(mut self, allow: bool) -> Self {
+        self.allow_partial_response = allow;
+        self
+    }
+
+    /// 底层 TCP 连接当前的 TCP_NODELAY 设置，经过代理的连接返回 None
+    pub fn nodelay(&self) -> Option<bool> {
+        self.socket.as_ref().and_then(|socket| socket.nodelay().ok())
+    }
+
+    /// 设置是否接受没有状态行的 HTTP/0.9 响应，此时读取到连接关闭为止
+    pub fn with_http09(mut self, allow: bool) -> Self {
+        self.allow_http09 = allow;
+        self
+    }
+
+    /// 读取下一个响应，并更新截断和可复用状态
+    async fn read_next_response(&mut self, head_request: bool) -> Result<Vec<u8>> {
+        // 上次多读的数据已包含本次响应的开头时，第一个字节视为此刻到达
+        let mut first_byte = (!self.buffered.is_empty()).then(Instant::now);
+        let mut reader = FirstByteTimer {
+            inner: &mut self.stream,
+            first_byte: &mut first_byte,
+        };
+        let (response, truncated, reusable) = read_response(
+            &mut reader,
+            &mut self.buffered,
+            head_request,
+            self.allow_partial_response,
+            self.allow_http09,
+            self.max_header_size,
+            self.read_idle_timeout,
+        )
+        .await?;
+        self.truncated = truncated;
+        self.reusable = reusable;
+        self.timings.first_byte = first_byte;
+        Ok(response)
+    }
+
+    /// 写出数据并立即刷新
+    async fn write_and_flush(&mut self, data: &[u8]) -> Result<()> {
+        self.stream.write_all(data).await
+            .map_err(|e| Error::connection(format!("Failed to write request: {}", e)))?;
+        self.stream.flush().await
+            .map_err(|e| Error::connection(format!("Failed to flush request: {}", e)))
+    }
+
+    /// 设置响应头部大小上限，超过上限时停止读取并返回错误
+    pub fn with_max_header_size(mut self, max_header_size: usize) -> Self {
+        self.max_header_size = max_header_size;
+        self
+    }
+
+    /// 设置读取响应时两次收到数据之间允许的最长空闲时间，超过后停止读取并返回 `Error::Timeout`
+    pub fn with_read_idle_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.read_idle_timeout = timeout;
+        self
+    }
 }
 
 #[async_trait]
 impl AsyncConnection for AsyncHttpConnection {
-    async fn send_request(&mut self, request: &str, parsed_url: &ParsedUrl) -> Result<Vec<u8>> {
-        if parsed_url.is_https {
-            self.send_https_request(request, parsed_url).await
-        } else {
-            self.send_http_request(request).await
+    async fn send_request(&mut self, request: &[u8], _parsed_url: &ParsedUrl) -> Result<Vec<u8>> {
+        // 发送请求
+        self.stream.write_all(request).await
+            .map_err(|e| Error::connection(format!("Failed to write request: {}", e)))?;
+        self.stream.flush().await
+            .map_err(|e| Error::connection(format!("Failed to flush request: {}", e)))?;
+
+        // 读取响应
+        self.read_next_response(request.starts_with(b"HEAD ")).await
+    }
+
+    async fn send_streaming_request(
+        &mut self,
+        head: &[u8],
+        mut body: mpsc::Receiver<Bytes>,
+        _parsed_url: &ParsedUrl,
+    ) -> Result<Vec<u8>> {
+        self.write_and_flush(head).await?;
+        // 每收到一个分块立即写出，保证长连接上传的实时性
+        while let Some(chunk) = body.recv().await {
+            if !chunk.is_empty() {
+                self.write_and_flush(&encode_chunk(&chunk)).await?;
+            }
+        }
+        self.write_and_flush(LAST_CHUNK).await?;
+
+        self.read_next_response(false).await
+    }
+
+    async fn send_requests(&mut self, requests: &[Vec<u8>], _parsed_url: &ParsedUrl) -> Result<Vec<Vec<u8>>> {
+        // 连续写出全部请求，再按顺序读取响应
+        for request in requests {
+            self.stream.write_all(request).await
+                .map_err(|e| Error::connection(format!("Failed to write request: {}", e)))?;
+        }
+        self.stream.flush().await
+            .map_err(|e| Error::connection(format!("Failed to flush request: {}", e)))?;
+
+        let mut responses = Vec::with_capacity(requests.len());
+        for (index, request) in requests.iter().enumerate() {
+            responses.push(self.read_next_response(request.starts_with(b"HEAD ")).await?);
+            if index + 1 < requests.len() && !self.reusable {
+                return Err(Error::connection(format!(
+                    "Connection closed after {} of {} pipelined responses",
+                    index + 1,
+                    requests.len()
+                )));
+            }
+        }
+        Ok(responses)
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        // TLS 流的 shutdown 会发送 close_notify 告警，然后关闭底层 TCP 连接的写方向
+        self.stream.shutdown().await
+            .map_err(|e| Error::other(format!("Failed to close connection: {}", e)))
+    }
+
+    fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    fn alpn_protocol(&self) -> Option<&[u8]> {
+        self.alpn_protocol.as_deref()
+    }
+
+    fn timings(&self) -> ConnectionTimings {
+        self.timings
+    }
+
+    fn is_reusable(&self) -> bool {
+        self.reusable
+    }
+
+    fn set_nodelay(&mut self, nodelay: bool) -> Result<()> {
+        match &self.socket {
+            Some(socket) => socket
+                .set_nodelay(nodelay)
+                .map_err(|e| Error::connection(format!("Failed to set TCP_NODELAY: {}", e))),
+            None => Ok(()),
         }
     }
+
+    fn into_boxed_stream(self: Box<Self>) -> Result<BoxedStream> {
+        Ok(self.stream)
+    }
 }
 
-impl AsyncHttpConnection {
-    /// 通过HTTPS发送请求
-    async fn send_https_request(&mut self, request: &str, parsed_url: &ParsedUrl) -> Result<Vec<u8>> {
-        let mut tls_stream = self
-            .tls_manager
-            .create_tls_stream(&mut self.stream, &parsed_url.hostname).await?;
+/// chunked 编码的结束块
+const LAST_CHUNK: &[u8] = b"0\r\n\r\n";
 
-        // 发送请求
-        tls_stream.write_all(request.as_bytes()).await
-            .map_err(|e| Error::other(format!("Failed to write request: {}", e)))?;
-        tls_stream.flush().await
-            .map_err(|e| Error::other(format!("Failed to flush request: {}", e)))?;
+/// 将数据编码为一个 chunked 分块（十六进制长度行 + 数据 + CRLF），空数据不生成分块
+pub(crate) fn encode_chunk(data: &[u8]) -> Vec<u8> {
+    if data.is_empty() {
+        return Vec::new();
+    }
+    let mut frame = format!("{:x}\r\n", data.len()).into_bytes();
+    frame.extend_from_slice(data);
+    frame.extend_from_slice(b"\r\n");
+    frame
+}
 
-        // 读取响应
-        let mut response = Vec::new();
-        let mut buffer = [0u8; 8192];
-
-        loop {
-            match tls_stream.read(&mut buffer).await {
-                Ok(0) => break,
-                Ok(n) => response.extend_from_slice(&buffer[..n]),
-                Err(e) => return Err(Error::other(format!("Failed to read response: {}", e))),
+/// 响应体的分帧方式
+enum BodyFraming {
+    /// 没有响应体
+    Empty,
+    /// 固定长度的响应体
+    Length(usize),
+    /// chunked 传输编码
+    Chunked,
+    /// 读取到连接关闭为止
+    UntilClose,
+}
+
+/// 解析响应头部，确定响应体分帧方式以及连接是否可保持
+fn parse_framing(head: &[u8], head_request: bool) -> (BodyFraming, bool) {
+    let head = String::from_utf8_lossy(head);
+    let mut lines = head.split("\r\n");
+    let status_line = lines.next().unwrap_or("");
+    let mut parts = status_line.split_whitespace();
+    let version = parts.next().unwrap_or("");
+    let status_code: u16 = parts.next().and_then(|code| code.parse().ok()).unwrap_or(0);
+
+    let mut content_length = None;
+    let mut conflicting_length = false;
+    let mut chunked = false;
+    let mut connection = None;
+    for line in lines {
+        if let Some((key, value)) = line.split_once(':') {
+            let key = key.trim().to_lowercase();
+            let value = value.trim().to_lowercase();
+            match key.as_str() {
+                "content-length" => {
+                    let length = value.parse::<usize>().ok();
+                    conflicting_length |= content_length.is_some() && content_length != length;
+                    content_length = length;
+                }
+                "transfer-encoding" => chunked = value.contains("chunked"),
+                "connection" => connection = Some(value),
+                _ => {}
             }
         }
+    }
+    // 多个不一致的 Content-Length 无法确定响应边界，解析时会报错，连接也不能再复用
+    let mut keep_alive = is_keep_alive(version, connection.as_deref()) && !conflicting_length;
 
-        Ok(response)
+    let framing = if head_request || (100..200).contains(&status_code) || status_code == 204 || status_code == 304 {
+        BodyFraming::Empty
+    } else if chunked {
+        BodyFraming::Chunked
+    } else if let Some(length) = content_length {
+        BodyFraming::Length(length)
+    } else {
+        keep_alive = false;
+        BodyFraming::UntilClose
+    };
+
+    (framing, keep_alive)
+}
+
+/// 判断响应头部是否属于 1xx 中间响应（如 100 Continue、103 Early Hints），101 不属于中间响应
+fn is_interim_response(head: &[u8]) -> bool {
+    let status_line = head.split(|&b| b == b'\r').next().unwrap_or(head);
+    let status_code = std::str::from_utf8(status_line)
+        .ok()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|code| code.parse::<u16>().ok());
+    matches!(status_code, Some(code) if (100..200).contains(&code) && code != 101)
+}
+
+/// 读取时记录第一次收到数据的时刻
+struct FirstByteTimer<'a, S> {
+    inner: &'a mut S,
+    first_byte: &'a mut Option<Instant>,
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for FirstByteTimer<'_, S> {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let filled = buf.filled().len();
+        let this = &mut *self;
+        let result = Pin::new(&mut *this.inner).poll_read(cx, buf);
+        if this.first_byte.is_none() && buf.filled().len() > filled {
+            *this.first_byte = Some(Instant::now());
+        }
+        result
     }
+}
 
-    /// 通过HTTP发送请求
-    async fn send_http_request(&mut self, request: &str) -> Result<Vec<u8>> {
-        // 发送请求
-        self.stream.write_all(request.as_bytes()).await
-            .map_err(|e| Error::other(format!("Failed to write request: {}", e)))?;
-        self.stream.flush().await
-            .map_err(|e| Error::other(format!("Failed to flush request: {}", e)))?;
+/// 判断数据是否以 `HTTP/` 开头（数据不足 5 字节时判断是否为其前缀）
+pub(crate) fn may_be_status_line(data: &[u8]) -> bool {
+    let len = data.len().min(5);
+    data[..len] == b"HTTP/"[..len]
+}
 
-        // 读取响应
-        let mut response = Vec::new();
-        let mut buffer = [0u8; 8192];
-
-        loop {
-            match self.stream.read(&mut buffer).await {
-                Ok(0) => break,
-                Ok(n) => response.extend_from_slice(&buffer[..n]),
-                Err(e) => return Err(Error::other(format!("Failed to read response: {}", e))),
+/// 读取一个完整响应
+/// 根据 Content-Length 或 chunked 编码确定响应结束位置，无法确定时读取到连接关闭
+/// 头部超过 `max_header_size` 仍未结束时返回错误，避免无限制地缓冲
+/// `buffered` 为上次读取时多读的数据，本次响应之后多读的数据会重新放回其中
+/// `allow_http09` 为 true 时，不以 `HTTP/` 开头的响应按 HTTP/0.9 处理，读取到连接关闭
+/// 设置了 `read_idle_timeout` 时，任意一次读取等待超过该时间仍未收到数据则返回 `Error::Timeout`
+/// 返回响应字节、响应是否因连接错误而被截断，以及连接是否可复用
+pub(crate) async fn read_response<S: AsyncRead + Unpin>(
+    stream: &mut S,
+    buffered: &mut Vec<u8>,
+    head_request: bool,
+    allow_partial: bool,
+    allow_http09: bool,
+    max_header_size: usize,
+    read_idle_timeout: Option<Duration>,
+) -> Result<(Vec<u8>, bool, bool)> {
+    let mut response = std::mem::take(buffered);
+    let mut buffer = [0u8; 8192];
+    let mut framing: Option<(usize, BodyFraming, bool)> = None;
+
+    loop {
+        // HTTP/0.9 响应没有状态行和头部，整个响应都是响应体
+        if allow_http09 && framing.is_none() && !response.is_empty() && !may_be_status_line(&response) {
+            framing = Some((0, BodyFraming::UntilClose, false));
+        }
+
+        if framing.is_none()
+            && let Some(pos) = response.windows(4).position(|w| w == b"\r\n\r\n")
+        {
+            if pos + 4 > max_header_size {
+                return Err(Error::other("response headers too large"));
             }
+            // 跳过 1xx 中间响应（101 除外，它是协议切换的最终响应），继续读取最终响应
+            if is_interim_response(&response[..pos]) {
+                response.drain(..pos + 4);
+                continue;
+            }
+            let (body_framing, keep_alive) = parse_framing(&response[..pos], head_request);
+            framing = Some((pos + 4, body_framing, keep_alive));
         }
 
-        Ok(response)
+        if framing.is_none() && response.len() > max_header_size {
+            return Err(Error::other("response headers too large"));
+        }
+
+        if let Some((body_start, body_framing, keep_alive)) = &framing {
+            let body = &response[*body_start..];
+            let complete = match body_framing {
+                BodyFraming::Empty => Some(0),
+                BodyFraming::Length(length) => (body.len() >= *length).then_some(*length),
+                BodyFraming::Chunked => ChunkedParser::complete_length(body),
+                BodyFraming::UntilClose => None,
+            };
+            if let Some(length) = complete {
+                *buffered = response.split_off(body_start + length);
+                return Ok((response, false, *keep_alive));
+            }
+        }
+
+        let read = stream.read(&mut buffer);
+        let result = match read_idle_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, read).await.map_err(|_| {
+                Error::timeout(format!("no response data received for {:?}", timeout))
+            })?,
+            None => read.await,
+        };
+        match result {
+            // 响应头接收完整之前连接被关闭
+            Ok(0) if framing.is_none() => {
+                return Err(Error::connection(if response.is_empty() {
+                    "server closed connection before sending response"
+                } else {
+                    "server closed connection before completing response headers"
+                }));
+            }
+            // 按 Content-Length 或 chunked 分帧的响应体未接收完整之前连接被关闭
+            Ok(0) if !matches!(framing, Some((_, BodyFraming::UntilClose, _))) => {
+                if allow_partial {
+                    return Ok((response, true, false));
+                }
+                return Err(Error::connection("server closed connection before completing response body"));
+            }
+            Ok(0) => break,
+            Ok(n) => response.extend_from_slice(&buffer[..n]),
+            Err(e) => {
+                // 头部已完整接收时，可以选择返回部分响应体
+                if allow_partial && framing.is_some() {
+                    return Ok((response, true, false));
+                }
+                return Err(Error::connection(format!("Failed to read response: {}", e)));
+            }
+        }
     }
+
+    Ok((response, false, false))
 }
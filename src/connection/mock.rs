@@ -0,0 +1,152 @@
+//! 模拟传输层
+//!
+//! 提供不依赖网络的连接实现，按请求方法和 URL 返回预设响应，用于编写确定性的测试
+
+use crate::connection::{AsyncConnection, Connector};
+use crate::error::{Error, Result};
+use crate::request::Method;
+use crate::response::StatusCode;
+use crate::utils::{parse_host_port, ParsedUrl};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// 预设的模拟响应
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    method: Method,
+    url: String,
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl MockResponse {
+    /// 创建匹配指定方法和 URL 的模拟响应
+    pub fn new(method: Method, url: &str, status: u16) -> Self {
+        Self {
+            method,
+            url: url.to_string(),
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    /// 添加响应头
+    pub fn header<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.headers.push((key.into(), value.into()));
+        self
+    }
+
+    /// 设置响应体
+    pub fn body<B: Into<Vec<u8>>>(mut self, body: B) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// 设置 JSON 响应体，并添加对应的 Content-Type
+    pub fn json<T: serde::Serialize>(self, data: &T) -> Result<Self> {
+        let body = serde_json::to_vec(data)
+            .map_err(|e| Error::other(format!("JSON serialization failed: {}", e)))?;
+        Ok(self.header("Content-Type", "application/json").body(body))
+    }
+
+    /// 序列化为原始 HTTP 响应
+    fn to_raw(&self) -> Vec<u8> {
+        let reason = StatusCode { code: self.status }.canonical_reason().unwrap_or("Unknown");
+        let mut raw = format!("HTTP/1.1 {} {}\r\n", self.status, reason);
+        for (key, value) in &self.headers {
+            raw.push_str(&format!("{}: {}\r\n", key, value));
+        }
+        raw.push_str(&format!("Content-Length: {}\r\n\r\n", self.body.len()));
+
+        let mut raw = raw.into_bytes();
+        raw.extend_from_slice(&self.body);
+        raw
+    }
+}
+
+/// 模拟传输层，实现 `Connector`，不创建任何套接字
+#[derive(Debug, Clone, Default)]
+pub struct MockTransport {
+    responses: Arc<Vec<MockResponse>>,
+}
+
+impl MockTransport {
+    /// 使用预设响应创建模拟传输层
+    pub fn new(responses: Vec<MockResponse>) -> Self {
+        Self {
+            responses: Arc::new(responses),
+        }
+    }
+
+    /// 查找与请求方法和 URL 匹配的响应
+    fn find(&self, method: &str, parsed_url: &ParsedUrl) -> Option<&MockResponse> {
+        self.responses.iter().find(|response| {
+            response.method.as_str() == method
+                && parse_host_port(&response.url).is_ok_and(|url| {
+                    url.hostname == parsed_url.hostname
+                        && url.port == parsed_url.port
+                        && url.is_https == parsed_url.is_https
+                        && url.full_path == parsed_url.full_path
+                })
+        })
+    }
+}
+
+#[async_trait]
+impl Connector for MockTransport {
+    async fn connect(&self, _parsed_url: &ParsedUrl) -> Result<Box<dyn AsyncConnection>> {
+        Ok(Box::new(MockConnection {
+            transport: self.clone(),
+        }))
+    }
+}
+
+/// 模拟连接
+struct MockConnection {
+    transport: MockTransport,
+}
+
+#[async_trait]
+impl AsyncConnection for MockConnection {
+    async fn send_request(&mut self, request: &[u8], parsed_url: &ParsedUrl) -> Result<Vec<u8>> {
+        let request = String::from_utf8_lossy(request);
+        let method = request.split_whitespace().next().unwrap_or("");
+        self.transport
+            .find(method, parsed_url)
+            .map(|response| response.to_raw())
+            .ok_or_else(|| {
+                Error::connection(format!(
+                    "No mock response for {} {}{}",
+                    method, parsed_url.hostname, parsed_url.full_path
+                ))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_mock_matches_method_and_url() {
+        let transport = MockTransport::new(vec![
+            MockResponse::new(Method::GET, "http://example.com/a", 200).body("get"),
+            MockResponse::new(Method::POST, "http://example.com/a", 201).body("post"),
+        ]);
+        let parsed_url = parse_host_port("http://example.com/a").unwrap();
+        let mut connection = transport.connect(&parsed_url).await.unwrap();
+
+        let raw = connection.send_request(b"POST /a HTTP/1.1\r\n\r\n", &parsed_url).await.unwrap();
+        assert!(raw.starts_with(b"HTTP/1.1 201 Created\r\n"));
+        assert!(raw.ends_with(b"post"));
+
+        let other_url = parse_host_port("http://example.com/b").unwrap();
+        assert!(connection.send_request(b"GET /b HTTP/1.1\r\n\r\n", &other_url).await.is_err());
+    }
+}
@@ -2,11 +2,16 @@
 //!
 //! 包含连接抽象、TLS 支持和代理连接功能
 
+#[allow(clippy::module_inception)]
 pub mod connection;
 pub mod tls;
 pub mod proxy;
+pub mod mock;
+pub mod options;
 
-pub use connection::{AsyncConnection, AsyncHttpConnection};
+pub use connection::{AsyncConnection, AsyncHttpConnection, AsyncStream, BoxedStream, Connector, DEFAULT_MAX_HEADER_SIZE};
+pub use mock::{MockResponse, MockTransport};
+pub use options::{ConnectOptions, TlsVersion};
 pub use tls::AsyncTlsManager;
 pub use proxy::{ProxyConfig, ProxyType, AsyncProxyConnection};
 
@@ -0,0 +1,336 @@
+//! 连接选项
+//!
+//! 包含建立 TCP 连接和 TLS 握手时使用的配置
+
+use crate::error::{Error, Result};
+use futures_util::stream::{FuturesUnordered, StreamExt};
+use std::io;
+use rustls::client::danger::ServerCertVerifier;
+use rustls::client::{ClientSessionMemoryCache, ClientSessionStore};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::net::{TcpSocket, TcpStream};
+
+/// 默认 TLS 会话缓存最多保存的服务器数量
+const SESSION_CACHE_SIZE: usize = 256;
+
+/// Happy Eyeballs 中相邻两次连接尝试之间的间隔（RFC 8305 推荐值）
+const CONNECTION_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+/// TLS 协议版本
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TlsVersion {
+    /// TLS 1.2
+    Tls12,
+    /// TLS 1.3
+    Tls13,
+}
+
+/// 建立连接时使用的选项
+#[derive(Debug, Clone)]
+pub struct ConnectOptions {
+    /// 本地绑定地址，设置后连接从该地址发出
+    pub local_address: Option<IpAddr>,
+    /// 自定义服务器证书校验器，设置后替代内置根证书校验
+    pub cert_verifier: Option<Arc<dyn ServerCertVerifier>>,
+    /// TLS 握手时向目标服务器通告的 ALPN 协议，为空时不发送 ALPN 扩展
+    pub alpn_protocols: Vec<Vec<u8>>,
+    /// 是否交错并发尝试解析到的 IPv6/IPv4 地址（Happy Eyeballs），默认开启
+    pub happy_eyeballs: bool,
+    /// TLS 会话缓存，使用同一缓存的连接可以恢复之前的会话而跳过完整握手，None 时不恢复会话
+    pub session_store: Option<Arc<dyn ClientSessionStore>>,
+    /// 允许协商的最低 TLS 版本，None 时不限制
+    pub min_tls_version: Option<TlsVersion>,
+    /// 允许协商的最高 TLS 版本，None 时不限制
+    pub max_tls_version: Option<TlsVersion>,
+}
+
+impl Default for ConnectOptions {
+    fn default() -> Self {
+        Self {
+            local_address: None,
+            cert_verifier: None,
+            alpn_protocols: Vec::new(),
+            happy_eyeballs: true,
+            session_store: Some(default_session_store()),
+            min_tls_version: None,
+            max_tls_version: None,
+        }
+    }
+}
+
+impl ConnectOptions {
+    /// 创建默认连接选项
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置本地绑定地址
+    pub fn local_address(mut self, address: IpAddr) -> Self {
+        self.local_address = Some(address);
+        self
+    }
+
+    /// 设置自定义服务器证书校验器
+    pub fn cert_verifier(mut self, verifier: Arc<dyn ServerCertVerifier>) -> Self {
+        self.cert_verifier = Some(verifier);
+        self
+    }
+
+    /// 设置 TLS 握手时向目标服务器通告的 ALPN 协议（按优先级排列）
+    pub fn alpn_protocols(mut self, protocols: Vec<Vec<u8>>) -> Self {
+        self.alpn_protocols = protocols;
+        self
+    }
+
+    /// 设置是否启用 Happy Eyeballs，关闭后按解析顺序逐个尝试地址
+    pub fn happy_eyeballs(mut self, enabled: bool) -> Self {
+        self.happy_eyeballs = enabled;
+        self
+    }
+
+    /// 设置 TLS 会话缓存，None 时每次连接都进行完整握手
+    pub fn session_store(mut self, store: Option<Arc<dyn ClientSessionStore>>) -> Self {
+        self.session_store = store;
+        self
+    }
+
+    /// 设置允许协商的最低 TLS 版本
+    pub fn min_tls_version(mut self, version: TlsVersion) -> Self {
+        self.min_tls_version = Some(version);
+        self
+    }
+
+    /// 设置允许协商的最高 TLS 版本
+    pub fn max_tls_version(mut self, version: TlsVersion) -> Self {
+        self.max_tls_version = Some(version);
+        self
+    }
+}
+
+/// 创建默认大小的内存 TLS 会话缓存
+pub(crate) fn default_session_store() -> Arc<dyn ClientSessionStore> {
+    Arc::new(ClientSessionMemoryCache::new(SESSION_CACHE_SIZE))
+}
+
+/// 按连接选项建立 TCP 连接
+///
+/// 域名解析失败返回 `Error::Dns`，连接失败返回 `Error::Io`，由调用方补充上下文
+pub(crate) async fn connect_tcp(addr: &str, options: &ConnectOptions) -> Result<TcpStream> {
+    connect_tcp_timed(addr, options).await.map(|(stream, _)| stream)
+}
+
+/// 复制 TCP 连接的套接字句柄，流被包装（如 TLS）后仍可通过句柄调整套接字选项
+pub(crate) fn socket_handle(stream: TcpStream) -> Result<(TcpStream, std::net::TcpStream)> {
+    let std_stream = stream.into_std()?;
+    let handle = std_stream.try_clone()?;
+    Ok((TcpStream::from_std(std_stream)?, handle))
+}
+
+/// 按连接选项建立 TCP 连接，同时返回域名解析完成的时刻
+pub(crate) async fn connect_tcp_timed(addr: &str, options: &ConnectOptions) -> Result<(TcpStream, Instant)> {
+    let targets: Vec<SocketAddr> = tokio::net::lookup_host(addr)
+        .await
+        .map_err(|e| Error::dns(format!("Failed to resolve {}: {}", addr, e)))?
+        .collect();
+    if targets.is_empty() {
+        return Err(Error::dns(format!("No addresses found for {}", addr)));
+    }
+    let dns_resolved = Instant::now();
+
+    // 指定本地地址时只尝试与其协议族相同的目标地址
+    let targets: Vec<SocketAddr> = match options.local_address {
+        Some(local_address) => targets
+            .into_iter()
+            .filter(|target| target.is_ipv4() == local_address.is_ipv4())
+            .collect(),
+        None => targets,
+    };
+    if targets.is_empty() {
+        return Err(Error::Io(io::Error::new(
+            io::ErrorKind::AddrNotAvailable,
+            format!("No address of {} matches the local address family", addr),
+        )));
+    }
+
+    let result = if options.happy_eyeballs {
+        let local_address = options.local_address;
+        race_targets(interleave_families(targets), CONNECTION_ATTEMPT_DELAY, |target| {
+            connect_target(target, local_address)
+        })
+        .await
+    } else {
+        connect_sequential(targets, options.local_address).await
+    };
+    result.map(|stream| (stream, dns_resolved)).map_err(Error::Io)
+}
+
+/// 按顺序逐个尝试目标地址，返回第一个成功的连接
+async fn connect_sequential(targets: Vec<SocketAddr>, local_address: Option<IpAddr>) -> io::Result<TcpStream> {
+    let mut last_error = None;
+    for target in targets {
+        match connect_target(target, local_address).await {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(last_error.expect("targets must not be empty"))
+}
+
+/// 以第一个地址的协议族开始，交替排列 IPv6 和 IPv4 地址
+fn interleave_families(targets: Vec<SocketAddr>) -> Vec<SocketAddr> {
+    let first_is_ipv6 = targets[0].is_ipv6();
+    let (preferred, other): (Vec<_>, Vec<_>) = targets.into_iter().partition(|target| target.is_ipv6() == first_is_ipv6);
+
+    let mut interleaved = Vec::with_capacity(preferred.len() + other.len());
+    let (mut preferred, mut other) = (preferred.into_iter(), other.into_iter());
+    loop {
+        match (preferred.next(), other.next()) {
+            (None, None) => return interleaved,
+            (a, b) => interleaved.extend(a.into_iter().chain(b)),
+        }
+    }
+}
+
+/// 简化的 Happy Eyeballs：每隔 `delay` 启动下一个地址的连接尝试（上一个失败时立即启动），
+/// 使用最先成功的连接并放弃其余尝试
+async fn race_targets<F, Fut>(targets: Vec<SocketAddr>, delay: Duration, connect: F) -> io::Result<TcpStream>
+where
+    F: Fn(SocketAddr) -> Fut,
+    Fut: Future<Output = io::Result<TcpStream>>,
+{
+    let mut pending = targets.into_iter();
+    let mut attempts = FuturesUnordered::new();
+    let mut last_error = None;
+    // 计时器只创建一次，每启动一个尝试时重新设定，其他尝试失败不会推迟下一个尝试
+    let stagger = tokio::time::sleep(delay);
+    tokio::pin!(stagger);
+
+    loop {
+        if attempts.is_empty() {
+            match pending.next() {
+                Some(target) => {
+                    attempts.push(connect(target));
+                    stagger.as_mut().reset(tokio::time::Instant::now() + delay);
+                }
+                None => return Err(last_error.expect("targets must not be empty")),
+            }
+        }
+
+        tokio::select! {
+            Some(result) = attempts.next() => match result {
+                // 返回后其余尝试随 attempts 一起被丢弃
+                Ok(stream) => return Ok(stream),
+                Err(e) => last_error = Some(e),
+            },
+            _ = &mut stagger, if pending.len() > 0 => {
+                if let Some(target) = pending.next() {
+                    attempts.push(connect(target));
+                }
+                stagger.as_mut().reset(tokio::time::Instant::now() + delay);
+            }
+        }
+    }
+}
+
+/// 连接单个目标地址，必要时先绑定本地地址
+async fn connect_target(target: SocketAddr, local_address: Option<IpAddr>) -> io::Result<TcpStream> {
+    let Some(local_address) = local_address else {
+        return TcpStream::connect(target).await;
+    };
+
+    let socket = if target.is_ipv4() { TcpSocket::new_v4()? } else { TcpSocket::new_v6()? };
+    socket.bind(SocketAddr::new(local_address, 0))?;
+    socket.connect(target).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::AsyncHttpConnection;
+    use crate::utils::parse_host_port;
+
+    #[tokio::test]
+    async fn test_unresolvable_host_is_dns_error() {
+        let parsed_url = parse_host_port("http://does-not-exist.invalid/").unwrap();
+        let error = AsyncHttpConnection::direct(&parsed_url).await.err().unwrap();
+        assert!(error.is_dns(), "unexpected error: {}", error);
+    }
+
+    #[test]
+    fn test_interleave_families() {
+        let v6a: SocketAddr = "[::1]:80".parse().unwrap();
+        let v6b: SocketAddr = "[::2]:80".parse().unwrap();
+        let v4a: SocketAddr = "127.0.0.1:80".parse().unwrap();
+        let v4b: SocketAddr = "127.0.0.2:80".parse().unwrap();
+        assert_eq!(interleave_families(vec![v6a, v6b, v4a, v4b]), vec![v6a, v4a, v6b, v4b]);
+        assert_eq!(interleave_families(vec![v4a, v6a, v6b]), vec![v4a, v6a, v6b]);
+    }
+
+    #[tokio::test]
+    async fn test_happy_eyeballs_skips_unreachable_address() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let reachable = listener.local_addr().unwrap();
+        let unreachable: SocketAddr = "[2001:db8::1]:80".parse().unwrap();
+
+        // 模拟 IPv6 不可达：到该地址的连接尝试一直挂起
+        let connect = |target: SocketAddr| async move {
+            if target == unreachable {
+                std::future::pending::<()>().await;
+            }
+            TcpStream::connect(target).await
+        };
+
+        let started = std::time::Instant::now();
+        let stream = tokio::time::timeout(
+            Duration::from_secs(5),
+            race_targets(vec![unreachable, reachable], Duration::from_millis(50), connect),
+        )
+        .await
+        .expect("happy eyeballs should not wait for the unreachable address")
+        .unwrap();
+
+        assert_eq!(stream.peer_addr().unwrap(), reachable);
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_failed_attempt_does_not_delay_next_start() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let reachable = listener.local_addr().unwrap();
+        let stalled: SocketAddr = "[2001:db8::1]:80".parse().unwrap();
+        let failing: SocketAddr = "[2001:db8::2]:80".parse().unwrap();
+
+        // 第二个尝试在下一次计划启动前失败，不应推迟第三个尝试
+        let connect = |target: SocketAddr| async move {
+            if target == stalled {
+                std::future::pending::<()>().await;
+            }
+            if target == failing {
+                tokio::time::sleep(Duration::from_millis(280)).await;
+                return Err(io::Error::from(io::ErrorKind::ConnectionRefused));
+            }
+            TcpStream::connect(target).await
+        };
+
+        let started = std::time::Instant::now();
+        let stream = race_targets(vec![stalled, failing, reachable], Duration::from_millis(300), connect)
+            .await
+            .unwrap();
+        assert_eq!(stream.peer_addr().unwrap(), reachable);
+        // 第三个尝试在 600ms 时启动；计时器被失败重置时要到 880ms
+        assert!(started.elapsed() < Duration::from_millis(800), "{:?}", started.elapsed());
+    }
+
+    #[tokio::test]
+    async fn test_refused_connection_is_not_dns_error() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let parsed_url = parse_host_port(&format!("http://127.0.0.1:{}/", port)).unwrap();
+        let error = AsyncHttpConnection::direct(&parsed_url).await.err().unwrap();
+        assert!(matches!(error, Error::Connection(_)), "unexpected error: {}", error);
+    }
+}
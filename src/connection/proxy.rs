@@ -2,9 +2,12 @@
 //!
 //! 只负责异步代理服务器连接建立和隧道创建
 
+use crate::connection::{AsyncTlsManager, BoxedStream, ConnectOptions};
+use crate::connection::options::connect_tcp;
 use crate::error::{Error, Result};
+use crate::request::{build_request_line, RequestTarget};
+use crate::utils::ParsedUrl;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::TcpStream;
 use std::time::Duration;
 
 /// 代理类型枚举
@@ -25,6 +28,8 @@ pub struct ProxyConfig {
     pub port: u16,
     /// 连接超时
     pub timeout: Duration,
+    /// 客户端到代理服务器之间是否使用 TLS（https:// 代理）
+    pub proxy_tls: bool,
 }
 
 impl ProxyConfig {
@@ -35,9 +40,21 @@ impl ProxyConfig {
             host: host.to_string(),
             port,
             timeout: Duration::from_secs(30),
+            proxy_tls: false,
         }
     }
 
+    /// 创建HTTPS代理配置（到代理服务器的连接使用 TLS 加密）
+    pub fn https(host: &str, port: u16) -> Self {
+        Self::http(host, port).with_proxy_tls(true)
+    }
+
+    /// 设置到代理服务器的连接是否使用 TLS
+    pub fn with_proxy_tls(mut self, proxy_tls: bool) -> Self {
+        self.proxy_tls = proxy_tls;
+        self
+    }
+
     /// 设置超时时间
     pub fn with_timeout(mut self, timeout: Duration) -> Self {
         self.timeout = timeout;
@@ -45,7 +62,7 @@ impl ProxyConfig {
     }
 
     /// 从 URL 字符串创建代理配置
-    /// 支持格式：http://proxy.example.com:8080
+    /// 支持格式：http://proxy.example.com:8080、https://proxy.example.com:8443
     pub fn from_url(url: &str) -> Result<Self> {
         if url.is_empty() {
             return Err(crate::error::Error::connection("Proxy URL cannot be empty"));
@@ -57,48 +74,107 @@ impl ProxyConfig {
 
         // 检查协议
         let scheme = url.scheme();
-        if scheme != "http" {
-            return Err(crate::error::Error::connection(format!("Unsupported proxy protocol: {}", scheme)));
-        }
+        let proxy_tls = match scheme {
+            "http" => false,
+            "https" => true,
+            _ => {
+                return Err(crate::error::Error::connection(format!("Unsupported proxy protocol: {}", scheme)));
+            }
+        };
 
         // 获取主机和端口
         let host = url.host_str()
             .ok_or_else(|| crate::error::Error::connection("Proxy URL missing host"))?;
 
-        let port = url.port().unwrap_or(80); // HTTP 默认端口
+        // HTTP 默认端口 80，HTTPS 默认端口 443
+        let port = url.port().unwrap_or(if proxy_tls { 443 } else { 80 });
 
-        Ok(Self::http(host, port))
+        Ok(Self::http(host, port).with_proxy_tls(proxy_tls))
     }
 }
 
 /// 异步代理连接结构体
 /// 只负责异步连接到代理服务器并建立隧道
 pub struct AsyncProxyConnection {
-    /// 底层TCP连接
-    pub stream: TcpStream,
+    /// 到代理服务器的底层连接（TCP 或 TLS）
+    pub stream: BoxedStream,
 }
 
 impl AsyncProxyConnection {
     /// 创建到代理服务器的连接
+    /// `proxy_tls` 为 true 时先与代理服务器完成 TLS 握手
     pub async fn new(config: ProxyConfig) -> Result<Self> {
+        Self::with_options(config, &ConnectOptions::default()).await
+    }
+
+    /// 按连接选项创建到代理服务器的连接
+    pub async fn with_options(config: ProxyConfig, options: &ConnectOptions) -> Result<Self> {
         let addr = format!("{}:{}", config.host, config.port);
-        let stream = tokio::net::TcpStream::connect(&addr)
+        let stream = connect_tcp(&addr, options)
             .await
-            .map_err(|e| {
-                Error::connection(format!("Failed to connect to proxy {}: {}", addr, e))
+            .map_err(|e| match e {
+                Error::Io(e) => Error::connection(format!("Failed to connect to proxy {}: {}", addr, e)),
+                e => e,
             })?;
 
         stream.set_nodelay(true)
             .map_err(|e| Error::connection(format!("Failed to set TCP_NODELAY: {}", e)))?;
 
-        Ok(Self { stream })
+        if config.proxy_tls {
+            let tls_stream = AsyncTlsManager::from_options(options)
+                .create_tls_stream(stream, &config.host)
+                .await
+                .map_err(|e| Error::proxy(format!("TLS handshake with proxy {} failed: {}", addr, e)))?;
+            return Ok(Self { stream: Box::new(tls_stream) });
+        }
+
+        Ok(Self { stream: Box::new(stream) })
+    }
+
+    /// 依次经过多个代理建立连接，返回到最后一个代理的连接
+    ///
+    /// 连接第一个代理后，每个代理通过 CONNECT 建立到下一个代理的隧道；
+    /// 下一个代理启用 `proxy_tls` 时在隧道内与其完成 TLS 握手。错误信息中包含失败的代理序号
+    pub async fn chain(configs: &[ProxyConfig], options: &ConnectOptions) -> Result<Self> {
+        let (first, rest) = configs
+            .split_first()
+            .ok_or_else(|| Error::proxy("Proxy chain is empty"))?;
+        let mut connection = Self::with_options(first.clone(), options)
+            .await
+            .map_err(|e| hop_error(1, first, e))?;
+
+        for (index, (current, next)) in configs.iter().zip(rest).enumerate() {
+            connection
+                .establish_tunnel(&next.host, next.port)
+                .await
+                .map_err(|e| hop_error(index + 1, current, e))?;
+
+            if next.proxy_tls {
+                let tls_stream = AsyncTlsManager::from_options(options)
+                    .create_tls_stream(connection.stream, &next.host)
+                    .await
+                    .map_err(|e| hop_error(index + 2, next, e))?;
+                connection = Self { stream: Box::new(tls_stream) };
+            }
+        }
+
+        Ok(connection)
     }
 
     /// 建立到目标服务器的隧道
     pub async fn establish_tunnel(&mut self, target_host: &str, target_port: u16) -> Result<()> {
+        let authority = ParsedUrl {
+            hostname: target_host.to_string(),
+            port: target_port,
+            path: String::new(),
+            full_path: String::new(),
+            is_https: true,
+        };
         let request = format!(
-            "CONNECT {}:{} HTTP/1.1\r\nHost: {}:{}\r\nConnection: keep-alive\r\n\r\n",
-            target_host, target_port, target_host, target_port
+            "{}\r\nHost: {}:{}\r\nConnection: keep-alive\r\n\r\n",
+            build_request_line("CONNECT", &authority, RequestTarget::Authority, "HTTP/1.1"),
+            target_host,
+            target_port
         );
 
         self.stream.write_all(request.as_bytes()).await
@@ -106,50 +182,167 @@ impl AsyncProxyConnection {
         self.stream.flush().await
             .map_err(|e| Error::proxy(format!("Failed to flush CONNECT request: {}", e)))?;
 
-        // 读取并验证代理响应
-        let mut response = String::new();
+        // 读取并验证代理响应，跳过 1xx 中间响应直到收到最终响应
+        let mut response: Vec<u8> = Vec::new();
         let mut buffer = [0u8; 8192];
         let mut total_read = 0;
 
         loop {
+            // 检查是否收到完整的响应头
+            if let Some(header_end) = response.windows(4).position(|w| w == b"\r\n\r\n") {
+                let status_code = Self::parse_status_code(&response[..header_end])?;
+                response.drain(..header_end + 4);
+
+                if (100..200).contains(&status_code) {
+                    continue;
+                }
+
+                if status_code != 200 {
+                    return Err(Error::proxy(format!(
+                        "Proxy connection failed: {}",
+                        status_code
+                    )));
+                }
+
+                return Ok(());
+            }
+
             let n = self.stream.read(&mut buffer).await
                 .map_err(|e| Error::proxy(format!("Failed to read proxy response: {}", e)))?;
             if n == 0 {
                 break;
             }
 
-            response.push_str(&String::from_utf8_lossy(&buffer[..n]));
+            response.extend_from_slice(&buffer[..n]);
             total_read += n;
 
-            // 检查是否收到完整的响应头
-            if response.contains("\r\n\r\n") {
-                break;
-            }
-
             if total_read > 8192 {
                 return Err(Error::proxy("Proxy response too large"));
             }
         }
 
-        // 解析响应状态
-        let status_line = response.lines().next().unwrap_or("");
+        // 连接关闭时仍未收到完整的最终响应
+        if response.is_empty() {
+            return Err(Error::proxy("Proxy closed connection without response"));
+        }
+        let status_code = Self::parse_status_code(&response)?;
+        Err(Error::proxy(format!("Incomplete proxy response: {}", status_code)))
+    }
+
+    /// 从代理响应头中解析状态码
+    fn parse_status_code(head: &[u8]) -> Result<u16> {
+        let head = String::from_utf8_lossy(head);
+        let status_line = head.lines().next().unwrap_or("");
         let parts: Vec<&str> = status_line.split_whitespace().collect();
 
         if parts.len() < 2 {
             return Err(Error::proxy("Invalid proxy response"));
         }
 
-        let status_code: u16 = parts[1]
+        parts[1]
             .parse()
-            .map_err(|_| Error::proxy("Invalid status code in proxy response"))?;
+            .map_err(|_| Error::proxy("Invalid status code in proxy response"))
+    }
+}
 
-        if status_code != 200 {
-            return Err(Error::proxy(format!(
-                "Proxy connection failed: {}",
-                status_code
-            )));
-        }
+/// 为代理链中第 `hop` 个代理的错误补充位置信息
+pub(crate) fn hop_error(hop: usize, config: &ProxyConfig, error: Error) -> Error {
+    Error::proxy(format!(
+        "Proxy hop {} ({}:{}) failed: {}",
+        hop, config.host, config.port, error
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_from_url_https_proxy() {
+        let config = ProxyConfig::from_url("https://proxy.example.com").unwrap();
+        assert!(config.proxy_tls);
+        assert_eq!(config.port, 443);
+
+        let config = ProxyConfig::from_url("http://proxy.example.com").unwrap();
+        assert!(!config.proxy_tls);
+        assert_eq!(config.port, 80);
+
+        assert!(ProxyConfig::from_url("socks5://proxy.example.com").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tunnel_skips_interim_responses() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = [0u8; 1024];
+            let _ = stream.read(&mut buffer).await.unwrap();
+            stream.write_all(b"HTTP/1.1 100 Continue\r\n\r\n").await.unwrap();
+            stream.flush().await.unwrap();
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            stream
+                .write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n")
+                .await
+                .unwrap();
+            let _ = stream.read(&mut buffer).await;
+        });
+
+        let mut proxy = AsyncProxyConnection::new(ProxyConfig::http("127.0.0.1", port)).await.unwrap();
+        proxy.establish_tunnel("example.com", 443).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_tunnel_rejects_final_error_after_interim() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = [0u8; 1024];
+            let _ = stream.read(&mut buffer).await.unwrap();
+            stream
+                .write_all(b"HTTP/1.1 100 Continue\r\n\r\nHTTP/1.1 403 Forbidden\r\n\r\n")
+                .await
+                .unwrap();
+        });
 
-        Ok(())
+        let mut proxy = AsyncProxyConnection::new(ProxyConfig::http("127.0.0.1", port)).await.unwrap();
+        let error = proxy.establish_tunnel("example.com", 443).await.unwrap_err();
+        assert!(error.to_string().contains("403"));
+    }
+
+    #[tokio::test]
+    async fn test_https_proxy_receives_connect_over_tls() {
+        use crate::connection::tls::test_support::{localhost_acceptor, HostVerifier};
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let acceptor = localhost_acceptor(Vec::new());
+
+        // 代理在 TLS 层内读取 CONNECT 请求
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut stream = acceptor.accept(stream).await.unwrap();
+            let mut buffer = vec![0u8; 4096];
+            let n = stream.read(&mut buffer).await.unwrap();
+            stream
+                .write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n")
+                .await
+                .unwrap();
+            String::from_utf8_lossy(&buffer[..n]).lines().next().unwrap().to_string()
+        });
+
+        let options = ConnectOptions {
+            cert_verifier: Some(HostVerifier::new("localhost")),
+            ..ConnectOptions::default()
+        };
+        let mut proxy = AsyncProxyConnection::with_options(ProxyConfig::https("localhost", port), &options)
+            .await
+            .unwrap();
+        proxy.establish_tunnel("example.com", 443).await.unwrap();
+        assert_eq!(server.await.unwrap(), "CONNECT example.com:443 HTTP/1.1");
     }
 }
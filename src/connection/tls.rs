@@ -5,7 +5,12 @@
 use crate::error::{Error, Result};
 use tokio_rustls::{TlsConnector, client::TlsStream};
 use rustls::{ClientConfig, RootCertStore};
-use std::sync::Arc;
+use rustls::client::{ClientSessionStore, ResolvesClientCert, Resumption, WebPkiServerVerifier};
+use rustls::sign::CertifiedKey;
+use rustls::{SignatureScheme, SupportedProtocolVersion};
+use rustls::client::danger::ServerCertVerifier;
+use crate::connection::{ConnectOptions, TlsVersion};
+use std::sync::{Arc, OnceLock};
 use tokio::io::{AsyncRead, AsyncWrite};
 use webpki_roots::TLS_SERVER_ROOTS;
 
@@ -17,16 +22,54 @@ pub struct AsyncTlsManager {
 impl AsyncTlsManager {
     /// 创建新的异步 TLS 管理器
     pub fn new() -> Self {
-        let mut root_store = RootCertStore::empty();
-        root_store.extend(TLS_SERVER_ROOTS.iter().cloned());
+        Self::with_verifier(webpki_verifier())
+    }
 
-        let config = Arc::new(ClientConfig::builder()
-            .with_root_certificates(root_store)
-            .with_no_client_auth());
+    /// 使用自定义服务器证书校验器创建 TLS 管理器，不使用内置根证书
+    pub fn with_verifier(verifier: Arc<dyn ServerCertVerifier>) -> Self {
+        Self::with_versions(verifier, rustls::DEFAULT_VERSIONS)
+    }
 
-        let connector = TlsConnector::from(config);
+    /// 使用指定的服务器证书校验器和允许协商的 TLS 版本创建 TLS 管理器
+    fn with_versions(verifier: Arc<dyn ServerCertVerifier>, versions: &[&'static SupportedProtocolVersion]) -> Self {
+        let config = Arc::new(ClientConfig::builder_with_protocol_versions(versions)
+            .dangerous()
+            .with_custom_certificate_verifier(verifier)
+            .with_client_cert_resolver(no_client_cert()));
 
-        Self { connector }
+        Self { connector: TlsConnector::from(config) }
+    }
+
+    /// 设置 TLS 握手时通告的 ALPN 协议（按优先级排列）
+    pub fn with_alpn_protocols(self, protocols: Vec<Vec<u8>>) -> Self {
+        let mut config = ClientConfig::clone(self.connector.config());
+        config.alpn_protocols = protocols;
+        Self { connector: TlsConnector::from(Arc::new(config)) }
+    }
+
+    /// 设置 TLS 会话缓存，共享同一缓存的连接可以恢复之前的会话，None 时不恢复会话
+    pub fn with_session_store(self, store: Option<Arc<dyn ClientSessionStore>>) -> Self {
+        let mut config = ClientConfig::clone(self.connector.config());
+        config.resumption = match store {
+            Some(store) => Resumption::store(store),
+            None => Resumption::disabled(),
+        };
+        Self { connector: TlsConnector::from(Arc::new(config)) }
+    }
+
+    /// 按连接选项创建 TLS 管理器
+    pub(crate) fn from_options(options: &ConnectOptions) -> Self {
+        let verifier = options.cert_verifier.clone().unwrap_or_else(webpki_verifier);
+        let versions: Vec<&'static SupportedProtocolVersion> = rustls::ALL_VERSIONS
+            .iter()
+            .copied()
+            .filter(|version| {
+                let version = tls_version(version);
+                options.min_tls_version.is_none_or(|min| version >= min)
+                    && options.max_tls_version.is_none_or(|max| version <= max)
+            })
+            .collect();
+        Self::with_versions(verifier, &versions).with_session_store(options.session_store.clone())
     }
 
     /// 创建异步 TLS 流
@@ -47,4 +90,333 @@ impl AsyncTlsManager {
     }
 }
 
+// rustls 只在服务器证书校验器和客户端证书解析器是同一实例时恢复 TLS 会话，
+// 每次连接都会重新创建 ClientConfig，因此两者在进程内共享
+
+/// 使用内置根证书的服务器证书校验器
+fn webpki_verifier() -> Arc<dyn ServerCertVerifier> {
+    static VERIFIER: OnceLock<Arc<WebPkiServerVerifier>> = OnceLock::new();
+    VERIFIER
+        .get_or_init(|| {
+            let mut root_store = RootCertStore::empty();
+            root_store.extend(TLS_SERVER_ROOTS.iter().cloned());
+            WebPkiServerVerifier::builder(Arc::new(root_store))
+                .build()
+                .expect("built-in root certificates are valid")
+        })
+        .clone()
+}
+
+/// rustls 协议版本对应的 `TlsVersion`
+fn tls_version(version: &SupportedProtocolVersion) -> TlsVersion {
+    match version.version {
+        rustls::ProtocolVersion::TLSv1_3 => TlsVersion::Tls13,
+        _ => TlsVersion::Tls12,
+    }
+}
+
+/// 不提供客户端证书的解析器
+fn no_client_cert() -> Arc<dyn ResolvesClientCert> {
+    static RESOLVER: OnceLock<Arc<NoClientCert>> = OnceLock::new();
+    RESOLVER.get_or_init(|| Arc::new(NoClientCert)).clone()
+}
+
+#[derive(Debug)]
+struct NoClientCert;
+
+impl ResolvesClientCert for NoClientCert {
+    fn resolve(&self, _root_hint_subjects: &[&[u8]], _sigschemes: &[SignatureScheme]) -> Option<Arc<CertifiedKey>> {
+        None
+    }
+
+    fn has_certs(&self) -> bool {
+        false
+    }
+}
+
+impl Default for AsyncTlsManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 // 注意：使用 tokio-rustls 的 TlsStream 类型，不需要自定义包装器
+
+/// 测试用的本地 TLS 服务器支持
+#[cfg(test)]
+pub(crate) mod test_support {
+    use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+    use rustls::crypto::CryptoProvider;
+    use rustls::pki_types::pem::PemObject;
+    use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+    use rustls::{DigitallySignedStruct, SignatureScheme};
+    use std::sync::Arc;
+    use tokio_rustls::TlsAcceptor;
+
+    /// localhost 自签名证书（PEM）
+    pub(crate) const LOCALHOST_CERT: &[u8] =
+        include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/localhost-cert.pem"));
+    /// localhost 自签名证书的私钥（PKCS#8 PEM）
+    pub(crate) const LOCALHOST_KEY: &[u8] =
+        include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/localhost-key.pem"));
+
+    /// 创建使用 localhost 自签名证书的 TLS 接收器，`alpn_protocols` 为服务端支持的 ALPN 协议
+    pub(crate) fn localhost_acceptor(alpn_protocols: Vec<Vec<u8>>) -> TlsAcceptor {
+        crate::tls::init_crypto_provider().unwrap();
+        let cert = CertificateDer::from_pem_slice(LOCALHOST_CERT).unwrap();
+        let key = PrivateKeyDer::from_pem_slice(LOCALHOST_KEY).unwrap();
+        let mut config = rustls::ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(vec![cert], key)
+            .unwrap();
+        config.alpn_protocols = alpn_protocols;
+        TlsAcceptor::from(Arc::new(config))
+    }
+
+    /// 只信任指定 SNI 主机的校验器，签名仍按正常算法校验
+    #[derive(Debug)]
+    pub(crate) struct HostVerifier {
+        host: &'static str,
+        provider: Arc<CryptoProvider>,
+    }
+
+    impl HostVerifier {
+        pub(crate) fn new(host: &'static str) -> Arc<Self> {
+            Arc::new(Self {
+                host,
+                provider: Arc::new(rustls::crypto::aws_lc_rs::default_provider()),
+            })
+        }
+    }
+
+    impl ServerCertVerifier for HostVerifier {
+        fn verify_server_cert(
+            &self,
+            _end_entity: &CertificateDer<'_>,
+            _intermediates: &[CertificateDer<'_>],
+            server_name: &ServerName<'_>,
+            _ocsp_response: &[u8],
+            _now: UnixTime,
+        ) -> std::result::Result<ServerCertVerified, rustls::Error> {
+            match server_name {
+                ServerName::DnsName(name) if name.as_ref() == self.host => Ok(ServerCertVerified::assertion()),
+                _ => Err(rustls::Error::General("untrusted host".to_string())),
+            }
+        }
+
+        fn verify_tls12_signature(
+            &self,
+            message: &[u8],
+            cert: &CertificateDer<'_>,
+            dss: &DigitallySignedStruct,
+        ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+            rustls::crypto::verify_tls12_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+        }
+
+        fn verify_tls13_signature(
+            &self,
+            message: &[u8],
+            cert: &CertificateDer<'_>,
+            dss: &DigitallySignedStruct,
+        ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+            rustls::crypto::verify_tls13_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+        }
+
+        fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+            self.provider.signature_verification_algorithms.supported_schemes()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_support::{localhost_acceptor, HostVerifier};
+    use crate::{HttpClient, TlsVersion};
+    use rustls::{HandshakeKind, ProtocolVersion};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// 启动一个使用自签名证书的 HTTPS 服务器，处理一个连接
+    async fn spawn_https_server() -> u16 {
+        spawn_https_server_with_alpn(Vec::new()).await
+    }
+
+    /// 启动一个支持指定 ALPN 协议的 HTTPS 服务器，处理一个连接
+    async fn spawn_https_server_with_alpn(alpn_protocols: Vec<Vec<u8>>) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let acceptor = localhost_acceptor(alpn_protocols);
+
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let Ok(mut stream) = acceptor.accept(stream).await else {
+                return;
+            };
+            let mut buffer = vec![0u8; 4096];
+            let _ = stream.read(&mut buffer).await.unwrap();
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 6\r\n\r\nsecure")
+                .await
+                .unwrap();
+            let _ = stream.shutdown().await;
+        });
+
+        port
+    }
+
+    #[tokio::test]
+    async fn test_custom_cert_verifier_by_sni() {
+        let port = spawn_https_server().await;
+        let client = HttpClient::builder()
+            .custom_cert_verifier(HostVerifier::new("localhost"))
+            .build()
+            .unwrap();
+        let response = client.get(&format!("https://localhost:{}/", port)).send().await.unwrap();
+        assert_eq!(response.body, b"secure");
+
+        // 同一证书在校验器不信任该主机时被拒绝
+        let port = spawn_https_server().await;
+        let client = HttpClient::builder()
+            .custom_cert_verifier(HostVerifier::new("internal.example"))
+            .build()
+            .unwrap();
+        let error = client.get(&format!("https://localhost:{}/", port)).send().await.unwrap_err();
+        assert!(error.to_string().contains("untrusted host"), "{}", error);
+
+        // 默认根证书不信任自签名证书
+        let port = spawn_https_server().await;
+        let client = HttpClient::builder().build().unwrap();
+        assert!(client.get(&format!("https://localhost:{}/", port)).send().await.is_err());
+    }
+
+    /// 启动一个处理 `count` 个连接的 HTTPS 服务器，返回每个连接的握手类型
+    async fn spawn_handshake_recorder(count: usize) -> (u16, tokio::task::JoinHandle<Vec<Option<HandshakeKind>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let acceptor = localhost_acceptor(Vec::new());
+
+        let server = tokio::spawn(async move {
+            let mut kinds = Vec::new();
+            for _ in 0..count {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut stream = acceptor.accept(stream).await.unwrap();
+                kinds.push(stream.get_ref().1.handshake_kind());
+                let mut buffer = vec![0u8; 4096];
+                let _ = stream.read(&mut buffer).await.unwrap();
+                stream
+                    .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok")
+                    .await
+                    .unwrap();
+                let _ = stream.shutdown().await;
+            }
+            kinds
+        });
+
+        (port, server)
+    }
+
+    #[tokio::test]
+    async fn test_tls_session_resumed_on_second_connection() {
+        let (port, server) = spawn_handshake_recorder(2).await;
+        let client = HttpClient::builder()
+            .custom_cert_verifier(HostVerifier::new("localhost"))
+            .build()
+            .unwrap();
+        let url = format!("https://localhost:{}/", port);
+        assert_eq!(client.get(&url).send().await.unwrap().body, b"ok");
+        assert_eq!(client.get(&url).send().await.unwrap().body, b"ok");
+        assert_eq!(server.await.unwrap(), vec![Some(HandshakeKind::Full), Some(HandshakeKind::Resumed)]);
+
+        let (port, server) = spawn_handshake_recorder(2).await;
+        let client = HttpClient::builder()
+            .custom_cert_verifier(HostVerifier::new("localhost"))
+            .tls_session_cache(false)
+            .build()
+            .unwrap();
+        let url = format!("https://localhost:{}/", port);
+        client.get(&url).send().await.unwrap();
+        client.get(&url).send().await.unwrap();
+        assert_eq!(server.await.unwrap(), vec![Some(HandshakeKind::Full), Some(HandshakeKind::Full)]);
+    }
+
+    #[tokio::test]
+    async fn test_close_notify_sent_after_request() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let acceptor = localhost_acceptor(Vec::new());
+
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut stream = acceptor.accept(stream).await.unwrap();
+            let mut buffer = vec![0u8; 4096];
+            let _ = stream.read(&mut buffer).await.unwrap();
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok")
+                .await
+                .unwrap();
+            // 收到 close_notify 时读取返回 Ok(0)，未发送就断开则返回 UnexpectedEof 错误
+            matches!(stream.read(&mut buffer).await, Ok(0))
+        });
+
+        let client = HttpClient::builder()
+            .custom_cert_verifier(HostVerifier::new("localhost"))
+            .build()
+            .unwrap();
+        let response = client.get(&format!("https://localhost:{}/", port)).send().await.unwrap();
+        assert_eq!(response.body, b"ok");
+        assert!(server.await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_tls_version_limits() {
+        let negotiated = |builder: crate::ClientBuilder| async move {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let port = listener.local_addr().unwrap().port();
+            let acceptor = localhost_acceptor(Vec::new());
+            let server = tokio::spawn(async move {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut stream = acceptor.accept(stream).await.unwrap();
+                let version = stream.get_ref().1.protocol_version();
+                let mut buffer = vec![0u8; 4096];
+                let _ = stream.read(&mut buffer).await.unwrap();
+                stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok").await.unwrap();
+                let _ = stream.shutdown().await;
+                version
+            });
+            let client = builder.custom_cert_verifier(HostVerifier::new("localhost")).build().unwrap();
+            client.get(&format!("https://localhost:{}/", port)).send().await.unwrap();
+            server.await.unwrap()
+        };
+
+        assert_eq!(negotiated(HttpClient::builder()).await, Some(ProtocolVersion::TLSv1_3));
+        assert_eq!(
+            negotiated(HttpClient::builder().max_tls_version(TlsVersion::Tls12)).await,
+            Some(ProtocolVersion::TLSv1_2)
+        );
+        assert_eq!(
+            negotiated(HttpClient::builder().min_tls_version(TlsVersion::Tls13)).await,
+            Some(ProtocolVersion::TLSv1_3)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_custom_alpn_protocol_negotiated() {
+        let port = spawn_https_server_with_alpn(vec![b"custom/1".to_vec()]).await;
+        let client = HttpClient::builder()
+            .custom_cert_verifier(HostVerifier::new("localhost"))
+            .alpn_protocols(vec![b"other/2".to_vec(), b"custom/1".to_vec()])
+            .build()
+            .unwrap();
+        let response = client.get(&format!("https://localhost:{}/", port)).send().await.unwrap();
+        assert_eq!(response.alpn_protocol.as_deref(), Some(&b"custom/1"[..]));
+
+        // 默认不通告 ALPN
+        let port = spawn_https_server_with_alpn(vec![b"custom/1".to_vec()]).await;
+        let client = HttpClient::builder()
+            .custom_cert_verifier(HostVerifier::new("localhost"))
+            .build()
+            .unwrap();
+        let response = client.get(&format!("https://localhost:{}/", port)).send().await.unwrap();
+        assert_eq!(response.alpn_protocol, None);
+    }
+}
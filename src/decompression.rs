@@ -1,9 +1,10 @@
 //! 响应体解压缩模块
 //!
-//! 支持gzip、deflate、brotli等压缩格式的自动解压缩
+//! 支持gzip、deflate、brotli等压缩格式的自动解压缩，以及请求体的压缩
 
-use flate2::read::{GzDecoder, DeflateDecoder};
-use std::io::{Read, Cursor};
+use flate2::read::{MultiGzDecoder, DeflateDecoder, ZlibDecoder};
+use flate2::write::{GzEncoder, DeflateEncoder};
+use std::io::{Read, Write, Cursor};
 use brotli::BrotliDecompress;
 use crate::error::{Result, Error};
 
@@ -13,36 +14,165 @@ pub enum Compression {
     Gzip,
     Deflate,
     Brotli,
+    /// 已知但不支持解压的编码（如 compress），解压时返回错误
+    Unsupported(&'static str),
     None,
 }
 
 impl Compression {
     /// 从content-encoding头部值解析压缩格式
+    ///
+    /// 忽略大小写和首尾空白，并去掉 `;` 之后的参数（如 `br;q=1.0`）
     pub fn from_content_encoding(value: &str) -> Self {
-        match value.to_lowercase().as_str() {
+        let token = value.split(';').next().unwrap_or_default().trim();
+        match token.to_ascii_lowercase().as_str() {
             "gzip" => Compression::Gzip,
             "deflate" => Compression::Deflate,
             "br" => Compression::Brotli,
+            "compress" | "x-compress" => Compression::Unsupported("compress"),
             _ => Compression::None,
         }
     }
+
+    /// 根据数据开头的魔数推断压缩格式，用于缺少或错误标注 Content-Encoding 的响应
+    ///
+    /// 只识别 gzip（`1f 8b`）和 zlib 封装的 deflate；brotli 没有魔数，无法识别
+    pub fn sniff(data: &[u8]) -> Self {
+        if data.starts_with(&[0x1f, 0x8b]) {
+            Compression::Gzip
+        } else if is_zlib_header(data) {
+            Compression::Deflate
+        } else {
+            Compression::None
+        }
+    }
+
+    /// 获取对应的content-encoding头部值，None 时返回 None
+    pub fn as_content_encoding(&self) -> Option<&'static str> {
+        match self {
+            Compression::Gzip => Some("gzip"),
+            Compression::Deflate => Some("deflate"),
+            Compression::Brotli => Some("br"),
+            Compression::Unsupported(encoding) => Some(encoding),
+            Compression::None => None,
+        }
+    }
+}
+
+/// 判断数据是否以 zlib 头开始（CM=8 且头部两字节满足 FCHECK 校验）
+fn is_zlib_header(data: &[u8]) -> bool {
+    match data {
+        [cmf, flg, ..] => cmf & 0x0f == 8 && cmf >> 4 <= 7 && (u16::from(*cmf) << 8 | u16::from(*flg)) % 31 == 0,
+        _ => false,
+    }
+}
+
+/// 启用的响应体解码器
+///
+/// 决定客户端通告的 `Accept-Encoding` 以及自动解压哪些编码；
+/// 被关闭的编码不会出现在 `Accept-Encoding` 中，服务器仍然发送时响应体保持压缩状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Decoders {
+    pub gzip: bool,
+    pub deflate: bool,
+    pub brotli: bool,
+}
+
+impl Default for Decoders {
+    fn default() -> Self {
+        Self {
+            gzip: true,
+            deflate: true,
+            brotli: true,
+        }
+    }
+}
+
+impl Decoders {
+    /// 关闭全部解码器
+    pub const fn none() -> Self {
+        Self {
+            gzip: false,
+            deflate: false,
+            brotli: false,
+        }
+    }
+
+    /// 根据启用的解码器生成 `Accept-Encoding` 的值，全部关闭时返回 None
+    pub fn accept_encoding(&self) -> Option<String> {
+        let encodings: Vec<&str> = [
+            (self.gzip, Compression::Gzip),
+            (self.deflate, Compression::Deflate),
+            (self.brotli, Compression::Brotli),
+        ]
+        .into_iter()
+        .filter(|(enabled, _)| *enabled)
+        .filter_map(|(_, compression)| compression.as_content_encoding())
+        .collect();
+        (!encodings.is_empty()).then(|| encodings.join(", "))
+    }
+
+    /// 是否自动解压该编码的响应体（无法解压的已知编码总是交给 `decompress` 以返回错误）
+    pub fn decodes(&self, compression: Compression) -> bool {
+        match compression {
+            Compression::Gzip => self.gzip,
+            Compression::Deflate => self.deflate,
+            Compression::Brotli => self.brotli,
+            Compression::Unsupported(_) | Compression::None => true,
+        }
+    }
+}
+
+/// 压缩函数
+pub fn compress(data: &[u8], compression: Compression) -> Result<Vec<u8>> {
+    match compression {
+        Compression::Gzip => {
+            let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(data)
+                .and_then(|_| encoder.finish())
+                .map_err(|e| Error::Decompression(format!("gzip压缩失败: {}", e)))
+        }
+        Compression::Deflate => {
+            let mut encoder = DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(data)
+                .and_then(|_| encoder.finish())
+                .map_err(|e| Error::Decompression(format!("deflate压缩失败: {}", e)))
+        }
+        Compression::Brotli => {
+            let mut compressed = Vec::new();
+            let mut input = Cursor::new(data);
+            brotli::enc::BrotliCompress(&mut input, &mut compressed, &Default::default())
+                .map_err(|e| Error::Decompression(format!("brotli压缩失败: {}", e)))?;
+            Ok(compressed)
+        }
+        Compression::Unsupported(encoding) => Err(Error::Decompression(format!(
+            "unsupported content-encoding: {}",
+            encoding
+        ))),
+        Compression::None => Ok(data.to_vec()),
+    }
 }
 
 /// 解压缩函数
 pub fn decompress(data: &[u8], compression: Compression) -> Result<Vec<u8>> {
     match compression {
         Compression::Gzip => {
-            let mut decoder = GzDecoder::new(data);
+            // 使用 MultiGzDecoder 解码多个连续的 gzip member
+            let mut decoder = MultiGzDecoder::new(data);
             let mut decompressed = Vec::new();
             decoder.read_to_end(&mut decompressed)
                 .map_err(|e| Error::Decompression(format!("gzip解压缩失败: {}", e)))?;
             Ok(decompressed)
         }
         Compression::Deflate => {
-            let mut decoder = DeflateDecoder::new(data);
+            // RFC 9110 的 deflate 是 zlib 封装格式，但也有服务器直接发送裸 deflate 数据
             let mut decompressed = Vec::new();
-            decoder.read_to_end(&mut decompressed)
-                .map_err(|e| Error::Decompression(format!("deflate解压缩失败: {}", e)))?;
+            let result = if is_zlib_header(data) {
+                ZlibDecoder::new(data).read_to_end(&mut decompressed)
+            } else {
+                DeflateDecoder::new(data).read_to_end(&mut decompressed)
+            };
+            result.map_err(|e| Error::Decompression(format!("deflate解压缩失败: {}", e)))?;
             Ok(decompressed)
         }
         Compression::Brotli => {
@@ -52,6 +182,10 @@ pub fn decompress(data: &[u8], compression: Compression) -> Result<Vec<u8>> {
                 .map_err(|e| Error::Decompression(format!("brotli解压缩失败: {}", e)))?;
             Ok(decompressed)
         }
+        Compression::Unsupported(encoding) => Err(Error::Decompression(format!(
+            "unsupported content-encoding: {}",
+            encoding
+        ))),
         Compression::None => Ok(data.to_vec()),
     }
 }
@@ -66,7 +200,67 @@ mod tests {
         assert_eq!(Compression::from_content_encoding("deflate"), Compression::Deflate);
         assert_eq!(Compression::from_content_encoding("br"), Compression::Brotli);
         assert_eq!(Compression::from_content_encoding("unknown"), Compression::None);
+        assert_eq!(Compression::from_content_encoding("compress"), Compression::Unsupported("compress"));
+        assert_eq!(Compression::from_content_encoding("x-compress"), Compression::Unsupported("compress"));
         assert_eq!(Compression::from_content_encoding("GZIP"), Compression::Gzip); // 测试大小写不敏感
+        assert_eq!(Compression::from_content_encoding("gzip "), Compression::Gzip);
+        assert_eq!(Compression::from_content_encoding("BR"), Compression::Brotli);
+        assert_eq!(Compression::from_content_encoding("br;q=1.0"), Compression::Brotli);
+        assert_eq!(Compression::from_content_encoding(" Deflate ; q=0.5"), Compression::Deflate);
+    }
+
+    #[test]
+    fn test_decoders_accept_encoding() {
+        assert_eq!(Decoders::default().accept_encoding().as_deref(), Some("gzip, deflate, br"));
+
+        let decoders = Decoders { brotli: false, ..Decoders::default() };
+        assert_eq!(decoders.accept_encoding().as_deref(), Some("gzip, deflate"));
+        assert!(!decoders.decodes(Compression::Brotli));
+        assert!(decoders.decodes(Compression::Gzip));
+
+        assert_eq!(Decoders::none().accept_encoding(), None);
+        assert!(!Decoders::none().decodes(Compression::Gzip));
+    }
+
+    #[test]
+    fn test_sniff_compression() {
+        let data = b"sniffed body".repeat(10);
+        assert_eq!(Compression::sniff(&compress(&data, Compression::Gzip).unwrap()), Compression::Gzip);
+        assert_eq!(Compression::sniff(b"plain text"), Compression::None);
+        assert_eq!(Compression::sniff(&[0x1f]), Compression::None);
+
+        let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&data).unwrap();
+        let zlib = encoder.finish().unwrap();
+        assert_eq!(Compression::sniff(&zlib), Compression::Deflate);
+        assert_eq!(decompress(&zlib, Compression::Deflate).unwrap(), data);
+    }
+
+    #[test]
+    fn test_gzip_multiple_members() {
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        let mut data = Vec::new();
+        for part in [&b"Hello, "[..], &b"World!"[..]] {
+            let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(part).unwrap();
+            data.extend(encoder.finish().unwrap());
+        }
+
+        let result = decompress(&data, Compression::Gzip).expect("gzip解压失败");
+        assert_eq!(result, b"Hello, World!");
+    }
+
+    #[test]
+    fn test_compress_roundtrip() {
+        let data = b"Hello, World! ".repeat(100);
+        for compression in [Compression::Gzip, Compression::Deflate, Compression::Brotli] {
+            let compressed = compress(&data, compression).unwrap();
+            assert!(compressed.len() < data.len());
+            assert_eq!(decompress(&compressed, compression).unwrap(), data);
+        }
+        assert!(compress(&data, Compression::Unsupported("compress")).is_err());
     }
 
     #[test]
@@ -35,6 +35,17 @@ pub enum Error {
     #[error("Timeout error: {0}")]
     Timeout(String),
 
+    /// 域名解析错误
+    #[error("DNS error: {0}")]
+    Dns(String),
+
+    /// 熔断器打开，请求未发送
+    #[error("Circuit open for {host}:{port}")]
+    CircuitOpen {
+        host: String,
+        port: u16,
+    },
+
     /// 代理错误
     #[error("Proxy error: {0}")]
     Proxy(String),
@@ -57,6 +68,10 @@ pub enum Error {
     /// 解压缩错误
     #[error("Decompression error: {0}")]
     Decompression(String),
+
+    /// 序列化/反序列化错误
+    #[error("Serialization error: {0}")]
+    Serialization(String),
 }
 
 impl Error {
@@ -85,6 +100,26 @@ impl Error {
         Error::Timeout(msg.into())
     }
 
+    /// 创建域名解析错误
+    pub fn dns<S: Into<String>>(msg: S) -> Self {
+        Error::Dns(msg.into())
+    }
+
+    /// 是否为域名解析错误
+    pub fn is_dns(&self) -> bool {
+        matches!(self, Error::Dns(_))
+    }
+
+    /// 创建熔断错误
+    pub fn circuit_open<S: Into<String>>(host: S, port: u16) -> Self {
+        Error::CircuitOpen { host: host.into(), port }
+    }
+
+    /// 是否因熔断器打开而未发送请求
+    pub fn is_circuit_open(&self) -> bool {
+        matches!(self, Error::CircuitOpen { .. })
+    }
+
     /// 创建代理错误
     pub fn proxy<S: Into<String>>(msg: S) -> Self {
         Error::Proxy(msg.into())
@@ -109,4 +144,9 @@ impl Error {
     pub fn decompression<S: Into<String>>(msg: S) -> Self {
         Error::Decompression(msg.into())
     }
+
+    /// 创建序列化错误
+    pub fn serialization<S: Into<String>>(msg: S) -> Self {
+        Error::Serialization(msg.into())
+    }
 }
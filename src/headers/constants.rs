@@ -22,7 +22,7 @@ pub fn validate_header_name(name: &str) -> Result<()> {
 
     // 检查是否包含非法字符
     for (i, ch) in name.chars().enumerate() {
-        if ch.is_control() || ch == ' ' || ch == '\t' {
+        if ch.is_control() || ch == ' ' || ch == '\t' || ch == ':' {
             return Err(Error::http_parse(format!(
                 "Invalid character '{}' at position {} in header name",
                 ch, i
@@ -34,12 +34,13 @@ pub fn validate_header_name(name: &str) -> Result<()> {
 }
 
 /// 验证HTTP头值
+/// 头部值中不能出现 CR、LF 或 NUL，防止请求拆分和头部注入
 pub fn validate_header_value(value: &str) -> Result<()> {
-    // HTTP头值可以包含控制字符，但不能以空格或制表符开始（除非是多行）
-    if let Some(ch) = value.chars().next() {
-        if ch == '\r' || ch == '\n' {
-            return Err(Error::http_parse("Header value cannot start with CR or LF"));
-        }
+    if let Some(position) = value.find(['\r', '\n', '\0']) {
+        return Err(Error::http_parse(format!(
+            "Invalid control character at position {} in header value",
+            position
+        )));
     }
 
     Ok(())
@@ -74,50 +75,119 @@ pub mod content_types {
 }
 
 /// 浏览器请求头预设
+///
+/// 预设按真实浏览器的发送顺序排列
 pub mod browser_headers {
-    use std::collections::HashMap;
+    /// 有序的请求头预设：(名称, 值)
+    pub type HeaderPreset = Vec<(&'static str, &'static str)>;
 
-    /// Chrome 浏览器请求头
-    pub fn chrome() -> HashMap<String, String> {
-        let mut headers = HashMap::new();
+    /// 可模拟的浏览器类型
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub enum Browser {
+        /// Chrome 浏览器
+        #[default]
+        Chrome,
+        /// Firefox 浏览器
+        Firefox,
+    }
 
-        // 基础浏览器请求头
-        headers.insert(
-            "User-Agent".to_string(),
-            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36".to_string()
-        );
-        headers.insert(
-            "Accept".to_string(),
-            "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,image/apng,*/*;q=0.8,application/signed-exchange;v=b3;q=0.7".to_string()
-        );
-        headers.insert(
-            "Accept-Language".to_string(),
-            "zh-CN,zh;q=0.9,en;q=0.8".to_string()
-        );
-        headers.insert(
-            "Accept-Encoding".to_string(),
-            "gzip, deflate, br".to_string()
-        );
+    impl Browser {
+        /// 获取该浏览器的请求头预设（页面导航）
+        pub fn headers(&self) -> HeaderPreset {
+            match self {
+                Browser::Chrome => chrome(),
+                Browser::Firefox => firefox(),
+            }
+        }
+
+        /// 获取该浏览器在指定请求场景下的请求头预设
+        pub fn headers_for(&self, context: FetchContext) -> HeaderPreset {
+            let mut headers = self.headers();
+            context.apply(&mut headers);
+            headers
+        }
+    }
 
-        // 安全和隐私相关的头
-        headers.insert("DNT".to_string(), "1".to_string());
-        headers.insert("Upgrade-Insecure-Requests".to_string(), "1".to_string());
+    /// 模拟的浏览器请求场景，决定 Sec-Fetch-* 等 Fetch Metadata 请求头
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub enum FetchContext {
+        /// 用户在地址栏打开页面（`document`/`navigate`）
+        #[default]
+        Navigate,
+        /// 页面脚本发起的跨域 fetch/XHR 请求（`empty`/`cors`/`cross-site`）
+        Cors,
+        /// 不需要 CORS 的跨域子资源请求，如图片、脚本（`empty`/`no-cors`/`cross-site`）
+        NoCors,
+        /// 页面脚本向同源接口发起的 JSON 请求（`empty`/`cors`/`same-origin`）
+        Api,
+    }
 
-        // 客户端提示（Client Hints）
-        headers.insert(
-            "Sec-Ch-Ua".to_string(),
-            "\"Not_A Brand\";v=\"8\", \"Chromium\";v=\"120\", \"Google Chrome\";v=\"120\"".to_string()
-        );
-        headers.insert("Sec-Ch-Ua-Mobile".to_string(), "?0".to_string());
-        headers.insert("Sec-Ch-Ua-Platform".to_string(), "\"Windows\"".to_string());
+    impl FetchContext {
+        /// 按请求场景调整导航请求的预设：原位更新 Sec-Fetch-* 和 Accept，
+        /// 非导航请求不发送 Sec-Fetch-User 和 Upgrade-Insecure-Requests
+        fn apply(self, headers: &mut HeaderPreset) {
+            let (mode, site, accept) = match self {
+                FetchContext::Navigate => return,
+                FetchContext::Cors => ("cors", "cross-site", "*/*"),
+                FetchContext::NoCors => ("no-cors", "cross-site", "*/*"),
+                FetchContext::Api => ("cors", "same-origin", "application/json, text/plain, */*"),
+            };
+            headers.retain(|(name, _)| *name != "Sec-Fetch-User" && *name != "Upgrade-Insecure-Requests");
+            for (name, value) in headers.iter_mut() {
+                match *name {
+                    "Sec-Fetch-Dest" => *value = "empty",
+                    "Sec-Fetch-Mode" => *value = mode,
+                    "Sec-Fetch-Site" => *value = site,
+                    "Accept" => *value = accept,
+                    _ => {}
+                }
+            }
+        }
+    }
 
-        // Fetch Metadata
-        headers.insert("Sec-Fetch-Dest".to_string(), "document".to_string());
-        headers.insert("Sec-Fetch-Mode".to_string(), "navigate".to_string());
-        headers.insert("Sec-Fetch-Site".to_string(), "none".to_string());
-        headers.insert("Sec-Fetch-User".to_string(), "?1".to_string());
+    /// Chrome 浏览器请求头
+    pub fn chrome() -> HeaderPreset {
+        vec![
+            // 客户端提示（Client Hints）
+            ("Sec-Ch-Ua", "\"Not_A Brand\";v=\"8\", \"Chromium\";v=\"120\", \"Google Chrome\";v=\"120\""),
+            ("Sec-Ch-Ua-Mobile", "?0"),
+            ("Sec-Ch-Ua-Platform", "\"Windows\""),
+            // 安全和隐私相关的头
+            ("DNT", "1"),
+            ("Upgrade-Insecure-Requests", "1"),
+            // 基础浏览器请求头
+            ("User-Agent", user_agents::CHROME_WINDOWS),
+            (
+                "Accept",
+                "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,image/apng,*/*;q=0.8,application/signed-exchange;v=b3;q=0.7",
+            ),
+            // Fetch Metadata
+            ("Sec-Fetch-Site", "none"),
+            ("Sec-Fetch-Mode", "navigate"),
+            ("Sec-Fetch-User", "?1"),
+            ("Sec-Fetch-Dest", "document"),
+            ("Accept-Encoding", "gzip, deflate, br"),
+            ("Accept-Language", "zh-CN,zh;q=0.9,en;q=0.8"),
+        ]
+    }
 
-        headers
+    /// Firefox 浏览器请求头
+    ///
+    /// Firefox 不发送 Sec-Ch-Ua 系列客户端提示头
+    pub fn firefox() -> HeaderPreset {
+        vec![
+            // 基础浏览器请求头
+            ("User-Agent", user_agents::FIREFOX_WINDOWS),
+            ("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,*/*;q=0.8"),
+            ("Accept-Language", "en-US,en;q=0.5"),
+            ("Accept-Encoding", "gzip, deflate, br"),
+            ("Upgrade-Insecure-Requests", "1"),
+            // Fetch Metadata
+            ("Sec-Fetch-Dest", "document"),
+            ("Sec-Fetch-Mode", "navigate"),
+            ("Sec-Fetch-Site", "none"),
+            ("Sec-Fetch-User", "?1"),
+        ]
     }
 
     /// 获取浏览器的用户代理字符串
@@ -126,6 +196,9 @@ pub mod browser_headers {
         pub const CHROME_MAC: &str = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
         pub const CHROME_LINUX: &str = "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
         pub const CHROME_MOBILE: &str = "Mozilla/5.0 (iPhone; CPU iPhone OS 17_0 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) CriOS/120.0.0.0 Mobile/15E148 Safari/604.1";
+        pub const FIREFOX_WINDOWS: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:121.0) Gecko/20100101 Firefox/121.0";
+        pub const FIREFOX_MAC: &str = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10.15; rv:121.0) Gecko/20100101 Firefox/121.0";
+        pub const FIREFOX_LINUX: &str = "Mozilla/5.0 (X11; Linux x86_64; rv:121.0) Gecko/20100101 Firefox/121.0";
     }
 }
 
@@ -148,5 +221,39 @@ mod tests {
 
         assert!(validate_header_value("text/html").is_ok());
         assert!(validate_header_value("\r\nmalicious").is_err());
+        assert!(validate_header_value("ok\r\nX-Injected: 1").is_err());
+        assert!(validate_header_name("X-Test: injected").is_err());
+    }
+
+    #[test]
+    fn test_firefox_preset_has_no_client_hints() {
+        let headers = browser_headers::firefox();
+        let value = |name: &str| headers.iter().find(|(key, _)| *key == name).map(|(_, value)| *value);
+        assert!(value("User-Agent").unwrap().contains("Firefox/"));
+        assert_eq!(value("Accept-Language"), Some("en-US,en;q=0.5"));
+        assert!(!headers.iter().any(|(key, _)| key.to_lowercase().starts_with("sec-ch-ua")));
+        assert_eq!(browser_headers::Browser::Firefox.headers(), headers);
+    }
+
+    #[test]
+    fn test_presets_keep_browser_order() {
+        use browser_headers::{Browser, FetchContext};
+
+        let names: Vec<&str> = Browser::Firefox.headers().into_iter().map(|(name, _)| name).collect();
+        assert_eq!(&names[..4], ["User-Agent", "Accept", "Accept-Language", "Accept-Encoding"]);
+
+        // 按场景调整时保持其余请求头的位置
+        let names: Vec<&str> = Browser::Chrome
+            .headers_for(FetchContext::Cors)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "Sec-Ch-Ua", "Sec-Ch-Ua-Mobile", "Sec-Ch-Ua-Platform", "DNT", "User-Agent", "Accept",
+                "Sec-Fetch-Site", "Sec-Fetch-Mode", "Sec-Fetch-Dest", "Accept-Encoding", "Accept-Language",
+            ]
+        );
     }
 }
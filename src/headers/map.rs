@@ -2,7 +2,7 @@
 //!
 //! 提供HeaderMap结构体，兼容reqwest::header::HeaderMap的API
 
-use crate::error::Result;
+use crate::error::{Error, Result};
 use crate::headers::constants::{validate_header_name, validate_header_value, normalize_header_name};
 use std::collections::hash_map::Iter;
 use std::collections::HashMap;
@@ -12,6 +12,8 @@ use std::collections::HashMap;
 #[derive(Debug, Clone, Default)]
 pub struct HeaderMap {
     inner: HashMap<String, String>,
+    /// 非 UTF-8 头部值的原始字节
+    raw_values: HashMap<String, Vec<u8>>,
 }
 
 impl HeaderMap {
@@ -19,6 +21,7 @@ impl HeaderMap {
     pub fn new() -> Self {
         Self {
             inner: HashMap::new(),
+            raw_values: HashMap::new(),
         }
     }
 
@@ -39,15 +42,51 @@ impl HeaderMap {
         // 标准化键名（转为小写）
         let normalized_key = normalize_header_name(&key);
 
+        self.raw_values.remove(&normalized_key);
         Ok(self.inner.insert(normalized_key, value))
     }
 
+    /// 以原始字节插入头信息，用于非 UTF-8 的头部值
+    /// 字符串 API 获取到的是有损解码后的值，原始字节可通过 `get_bytes` 获取
+    pub fn insert_bytes<K, V>(&mut self, key: K, value: V) -> Result<Option<String>>
+    where
+        K: Into<String>,
+        V: Into<Vec<u8>>,
+    {
+        let key = key.into();
+        let value = value.into();
+
+        validate_header_name(&key)?;
+        if value.iter().any(|b| matches!(b, b'\r' | b'\n' | b'\0')) {
+            return Err(Error::http_parse("Header value cannot contain CR, LF or NUL"));
+        }
+
+        let normalized_key = normalize_header_name(&key);
+        let lossy_value = String::from_utf8_lossy(&value).into_owned();
+
+        if std::str::from_utf8(&value).is_ok() {
+            self.raw_values.remove(&normalized_key);
+        } else {
+            self.raw_values.insert(normalized_key.clone(), value);
+        }
+        Ok(self.inner.insert(normalized_key, lossy_value))
+    }
+
     /// 获取头信息的值
     pub fn get(&self, key: &str) -> Option<&String> {
         let normalized_key = normalize_header_name(key);
         self.inner.get(&normalized_key)
     }
 
+    /// 获取头信息值的原始字节
+    pub fn get_bytes(&self, key: &str) -> Option<&[u8]> {
+        let normalized_key = normalize_header_name(key);
+        self.raw_values
+            .get(&normalized_key)
+            .map(|value| value.as_slice())
+            .or_else(|| self.inner.get(&normalized_key).map(|value| value.as_bytes()))
+    }
+
     /// 检查是否包含指定的头
     pub fn contains_key(&self, key: &str) -> bool {
         let normalized_key = normalize_header_name(key);
@@ -57,6 +96,7 @@ impl HeaderMap {
     /// 移除指定的头
     pub fn remove(&mut self, key: &str) -> Option<String> {
         let normalized_key = normalize_header_name(key);
+        self.raw_values.remove(&normalized_key);
         self.inner.remove(&normalized_key)
     }
 
@@ -72,12 +112,17 @@ impl HeaderMap {
     pub fn merge(&mut self, other: &HeaderMap) {
         for (key, value) in &other.inner {
             self.inner.insert(key.clone(), value.clone());
+            match other.raw_values.get(key) {
+                Some(raw) => self.raw_values.insert(key.clone(), raw.clone()),
+                None => self.raw_values.remove(key),
+            };
         }
     }
 
     /// 清空所有头信息
     pub fn clear(&mut self) {
-        self.inner.clear()
+        self.inner.clear();
+        self.raw_values.clear();
     }
 
     /// 获取头信息的数量
@@ -92,9 +137,19 @@ impl HeaderMap {
 
     /// 从现有的 HashMap 创建 HeaderMap
     pub fn from_hashmap(hashmap: HashMap<String, String>) -> Result<Self> {
+        Self::try_from_iter(hashmap)
+    }
+
+    /// 从键值对迭代器创建，每个头信息都会经过校验，遇到无效头信息时返回错误
+    pub fn try_from_iter<K, V, I>(headers: I) -> Result<Self>
+    where
+        K: Into<String>,
+        V: Into<String>,
+        I: IntoIterator<Item = (K, V)>,
+    {
         let mut header_map = Self::new();
 
-        for (key, value) in hashmap {
+        for (key, value) in headers {
             header_map.insert(key, value)?;
         }
 
@@ -112,6 +167,19 @@ impl HeaderMap {
     }
 }
 
+/// 从键值对数组创建：`HeaderMap::try_from([("Accept", "application/json")])?`
+impl<K, V, const N: usize> TryFrom<[(K, V); N]> for HeaderMap
+where
+    K: Into<String>,
+    V: Into<String>,
+{
+    type Error = Error;
+
+    fn try_from(headers: [(K, V); N]) -> Result<Self> {
+        Self::try_from_iter(headers)
+    }
+}
+
 /// HeaderMap 的迭代器
 pub struct HeaderMapIter<'a> {
     inner: Iter<'a, String, String>,
@@ -129,6 +197,20 @@ impl<'a> Iterator for HeaderMapIter<'a> {
 mod header_map_tests {
     use super::*;
 
+    #[test]
+    fn test_header_map_try_from_array() {
+        let headers = HeaderMap::try_from([("Accept", "application/json"), ("X-Trace-ID", "abc")]).unwrap();
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers.get("accept").unwrap(), "application/json");
+        assert_eq!(headers.inner().get("x-trace-id").unwrap(), "abc");
+
+        let headers = HeaderMap::try_from_iter(vec![("A".to_string(), "1".to_string())]).unwrap();
+        assert_eq!(headers.get("a").unwrap(), "1");
+
+        assert!(HeaderMap::try_from([("Bad Name", "x")]).is_err());
+        assert!(HeaderMap::try_from([("X-Ok", "line\r\nbreak")]).is_err());
+    }
+
     #[test]
     fn test_header_map_new() {
         let headers = HeaderMap::new();
@@ -183,6 +265,21 @@ mod header_map_tests {
         assert_eq!(headers1.get("user-agent").unwrap(), "test-agent");
     }
 
+    #[test]
+    fn test_header_map_insert_bytes() {
+        let mut headers = HeaderMap::new();
+
+        // latin-1 编码的 "café"
+        headers.insert_bytes("X-Name", b"caf\xE9".to_vec()).unwrap();
+        assert_eq!(headers.get_bytes("x-name").unwrap(), b"caf\xE9");
+        assert_eq!(headers.get("x-name").unwrap(), "caf\u{FFFD}");
+
+        headers.insert("X-Name", "plain").unwrap();
+        assert_eq!(headers.get_bytes("x-name").unwrap(), b"plain");
+
+        assert!(headers.insert_bytes("X-Bad", b"a\r\nb".to_vec()).is_err());
+    }
+
     #[test]
     fn test_header_map_invalid_name() {
         let mut headers = HeaderMap::new();
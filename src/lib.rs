@@ -44,12 +44,18 @@ pub mod connection;
 pub mod headers;
 pub mod tls;
 pub mod decompression;
+pub mod timings;
 pub mod chunked;
+pub mod websocket;
+pub mod blocking;
 
-pub use client::{HttpClient, ClientBuilder};
-pub use response::{Response, StatusCode};
+pub use client::{HttpClient, ClientBuilder, RedirectPolicy};
+pub use response::{Response, StatusCode, DEFAULT_MAX_HEADER_COUNT};
 pub use error::{Error, Result};
-pub use connection::{AsyncConnection, AsyncHttpConnection, ProxyConfig, ProxyType, AsyncTlsManager, AsyncProxyConnection};
-pub use request::AsyncRequestBuilder;
+pub use connection::{AsyncConnection, AsyncHttpConnection, ProxyConfig, ProxyType, AsyncTlsManager, AsyncProxyConnection, ConnectOptions, Connector, MockResponse, MockTransport, TlsVersion};
+pub use request::{AsyncRequestBuilder, CancellationToken, ContentLengthPolicy, JsonOptions};
 pub use headers::HeaderMap;
-pub use decompression::{Compression, decompress};
+pub use headers::browser_headers::{Browser, FetchContext};
+pub use decompression::{Compression, Decoders, compress, decompress};
+pub use websocket::WebSocketUpgrade;
+pub use timings::Timings;
@@ -3,15 +3,23 @@
 //! 提供流畅的请求构建API
 
 use bytes::Bytes;
-use crate::error::Result;
+use crate::error::{Error, Result};
 use crate::response::Response;
+use tokio_util::sync::CancellationToken;
 use super::model::Request;
-use super::types::Method;
+use super::types::{ContentLengthPolicy, JsonOptions, Method, Version};
+use crate::headers::{validate_header_name, validate_header_value};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::Path;
+use tokio::sync::mpsc;
 
 /// 异步请求构建器模式
 pub struct AsyncRequestBuilder<'a> {
     request: Request,
     client: &'a crate::client::HttpClient,
+    cancel_token: Option<CancellationToken>,
+    error: Option<Error>,
 }
 
 impl<'a> AsyncRequestBuilder<'a> {
@@ -22,27 +30,54 @@ impl<'a> AsyncRequestBuilder<'a> {
         Self {
             request,
             client,
+            cancel_token: None,
+            error: None,
         }
     }
 
     /// 设置请求头
+    ///
+    /// 无效的请求头不会被设置，错误在 `send()` 时返回
     pub fn header<K, V>(mut self, key: K, value: V) -> Self
     where
         K: Into<String>,
         V: Into<String>,
     {
-        self.request = self.request.header(key, value);
+        let (key, value) = (key.into(), value.into());
+        match validate_header_name(&key).and_then(|_| validate_header_value(&value)) {
+            Ok(()) => self.request = self.request.header(key, value),
+            Err(e) => {
+                self.error.get_or_insert(e);
+            }
+        }
         self
     }
 
+    /// 设置请求头，无效的请求头立即返回错误
+    pub fn try_header<K, V>(mut self, key: K, value: V) -> Result<Self>
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        let (key, value) = (key.into(), value.into());
+        validate_header_name(&key)?;
+        validate_header_value(&value)?;
+        self.request = self.request.header(key, value);
+        Ok(self)
+    }
+
     /// 设置多个请求头
+    ///
+    /// 无效的请求头不会被设置，错误在 `send()` 时返回
     pub fn headers<K, V, I>(mut self, headers: I) -> Self
     where
         K: Into<String>,
         V: Into<String>,
         I: IntoIterator<Item = (K, V)>,
     {
-        self.request = self.request.headers(headers);
+        for (key, value) in headers {
+            self = self.header(key, value);
+        }
         self
     }
 
@@ -54,19 +89,632 @@ impl<'a> AsyncRequestBuilder<'a> {
         self
     }
 
+    /// 不发送默认的 User-Agent、Accept、Connection 请求头
+    /// 客户端级别的默认请求头仍会被合并
+    pub fn no_default_headers(mut self) -> Self {
+        self.request = self.request.without_default_headers();
+        self
+    }
+
+    /// 从通道接收请求体，每个分块作为一个 chunked 帧发送，通道关闭后结束请求体
+    ///
+    /// 会替换已设置的请求体；请求体无法重放，因此不跟随重定向
+    pub fn body_channel(mut self, rx: mpsc::Receiver<Bytes>) -> Self {
+        self.request = self.request.body_channel(rx);
+        self
+    }
+
+    /// 克隆构建器，用于以相同配置多次发送请求
+    ///
+    /// 请求体来自 `body_channel` 时通道无法复制，返回 None；存在待返回的构建错误时同样返回 None
+    pub fn try_clone(&self) -> Option<Self> {
+        if self.error.is_some() {
+            return None;
+        }
+        Some(Self {
+            request: self.request.try_clone()?,
+            client: self.client,
+            cancel_token: self.cancel_token.clone(),
+            error: None,
+        })
+    }
+
+    /// 将客户端 IP 追加到 X-Forwarded-For 请求头，已有值时以逗号连接
+    pub fn forwarded_for(self, ip: IpAddr) -> Self {
+        self.append_header("X-Forwarded-For", ip.to_string())
+    }
+
+    /// 追加一组标准 Forwarded 请求头参数（如 `for=192.0.2.60;proto=https`），已有值时以逗号连接
+    pub fn forwarded(self, params: impl Into<String>) -> Self {
+        self.append_header("Forwarded", params)
+    }
+
+    /// 设置 `TE: trailers`，声明客户端接受 chunked 响应末尾的 trailer（如 gRPC-web 的状态信息）
+    ///
+    /// 收到的 trailer 通过 `Response::trailers()` 获取
+    pub fn expect_trailers(self) -> Self {
+        let accepted = self.request.headers.iter().any(|(name, value)| {
+            name.eq_ignore_ascii_case("te") && value.split(',').any(|token| token.trim().eq_ignore_ascii_case("trailers"))
+        });
+        if accepted {
+            return self;
+        }
+        self.append_header("TE", "trailers")
+    }
+
+    /// 向列表型请求头追加一个元素（请求头名称不区分大小写）
+    fn append_header(mut self, key: &str, value: impl Into<String>) -> Self {
+        let value = value.into();
+        let existing: Vec<String> = self
+            .request
+            .headers
+            .iter()
+            .filter(|(name, _)| name.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value.clone())
+            .collect();
+        self.request.headers.retain(|name, _| !name.eq_ignore_ascii_case(key));
+
+        let combined = existing.into_iter().chain(std::iter::once(value)).collect::<Vec<_>>().join(", ");
+        self.header(key, combined)
+    }
+
+    /// 覆盖请求中的 Host 头，用于测试虚拟主机路由或域前置
+    ///
+    /// 只改变发送的 Host 头，TCP 连接和 TLS SNI 仍使用 URL 中的主机
+    pub fn host_header(mut self, value: impl Into<String>) -> Self {
+        self.request.headers.retain(|key, _| !key.eq_ignore_ascii_case("host"));
+        self.header("Host", value)
+    }
+
+    /// 发送 HTTP/1.0 请求时不自动添加 Host 头，用于无法处理 Host 的老旧或嵌入式设备
+    ///
+    /// 需要同时通过 `version(Version::Http1_0)` 设置版本；通过 `host_header` 显式设置的 Host 仍会发送
+    pub fn omit_host(mut self) -> Self {
+        self.request = self.request.omit_host();
+        self
+    }
+
+    /// 使用星号形式的请求目标，发送针对整个服务器的 `OPTIONS * HTTP/1.1`
+    ///
+    /// 请求方法改为 OPTIONS，Host 头和连接目标取自 URL 中的主机，URL 的路径和查询参数被忽略
+    pub fn asterisk_form(mut self) -> Self {
+        self.request = self.request.asterisk_form();
+        match url::Url::parse(&self.request.url) {
+            Ok(mut url) => {
+                url.set_path("/*");
+                url.set_query(None);
+                url.set_fragment(None);
+                self.request.url = url.to_string();
+            }
+            Err(e) => {
+                self.error.get_or_insert(Error::url_parse(format!("Invalid request URL {}: {}", self.request.url, e)));
+            }
+        }
+        self
+    }
+
+    /// 设置本次请求的 Accept-Language，优先于客户端默认值和浏览器预设
+    pub fn accept_language(mut self, value: impl Into<String>) -> Self {
+        self.request.headers.retain(|key, _| !key.eq_ignore_ascii_case("accept-language"));
+        self.request = self.request.header("Accept-Language", value);
+        self
+    }
+
+    /// 设置 `Idempotency-Key` 请求头，并将请求标记为可以安全重试
+    ///
+    /// 启用 `ClientBuilder::max_retries` 后，即使是 POST 也会在连接失败时重试，每次重试都发送同一个键
+    pub fn idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.request = self.request.retry_safe(true);
+        self.header("Idempotency-Key", key)
+    }
+
+    /// 设置本次请求所用连接的 TCP_NODELAY（连接默认启用）
+    ///
+    /// 流式读取小帧（如 SSE）时保持启用以降低延迟，批量上传时可关闭以合并小包；
+    /// 覆盖过设置的连接只用于本次请求及其重定向，不会交给其他请求复用
+    pub fn tcp_nodelay(mut self, nodelay: bool) -> Self {
+        self.request = self.request.tcp_nodelay(nodelay);
+        self
+    }
+
+    /// 设置显式 Content-Length 与请求体长度不一致时的处理方式
+    pub fn content_length_policy(mut self, policy: ContentLengthPolicy) -> Self {
+        self.request = self.request.content_length_policy(policy);
+        self
+    }
+
+    /// 设置请求行中的 HTTP 版本，`Version::Custom` 可写入任意版本标记
+    pub fn version(mut self, version: Version) -> Self {
+        self.request.version = version;
+        self
+    }
+
+    /// 设置 Range 请求头，用于断点续传等部分内容请求
+    pub fn range(mut self, start: u64, end: Option<u64>) -> Self {
+        self.request = self.request.range(start, end);
+        self
+    }
+
+    /// 设置空请求体，发送 `Content-Length: 0`，用于没有内容的 POST 等请求
+    pub fn empty_body(mut self) -> Self {
+        self.request = self.request.empty_body();
+        self
+    }
+
     /// 设置请求体
     pub fn body<B: Into<Bytes>>(mut self, body: B) -> Self {
         self.request = self.request.body(body);
         self
     }
 
-    /// 构建请求
+    /// 使用文件内容作为请求体，并根据扩展名推断 Content-Type
+    ///
+    /// 文件在 `send()` 时异步读取，读取失败时返回错误；已显式设置 Content-Type 时保留原值，
+    /// 无法识别的扩展名使用 `application/octet-stream`
+    pub fn file(mut self, path: impl AsRef<Path>) -> Self {
+        self.request = self.request.file(path);
+        self
+    }
+
+    /// 设置JSON请求体，任何请求方法（包括 GET、DELETE）都可以携带
+    pub fn json<T: serde::Serialize>(mut self, data: &T) -> Result<Self> {
+        self.request = self.request.json(data)?;
+        Ok(self)
+    }
+
+    /// 设置 `application/x-www-form-urlencoded` 表单请求体，不需要 serde
+    pub fn form_map(mut self, fields: &HashMap<String, String>) -> Self {
+        self.request = self.request.form_map(fields);
+        self
+    }
+
+    /// 设置带换行和缩进的JSON请求体，便于在请求日志中阅读
+    pub fn json_pretty<T: serde::Serialize>(self, data: &T) -> Result<Self> {
+        self.json_with(data, &JsonOptions::pretty())
+    }
+
+    /// 按指定格式设置JSON请求体
+    pub fn json_with<T: serde::Serialize>(mut self, data: &T, options: &JsonOptions) -> Result<Self> {
+        self.request = self.request.json_with(data, options)?;
+        Ok(self)
+    }
+
+    /// 设置取消令牌，令牌被取消时请求立即中止并关闭连接
+    pub fn cancel_token(mut self, token: CancellationToken) -> Self {
+        self.cancel_token = Some(token);
+        self
+    }
+
+    /// 构建请求（无效的请求头已被忽略）
     pub fn build(self) -> Request {
         self.request
     }
 
+    /// 返回发送时将写入连接的请求字节，不建立连接
+    ///
+    /// 包含请求行、Host 以及客户端合并的默认请求头；使用 `body_channel` 时只包含请求头部分，
+    /// 使用 `file` 时在调用线程上同步读取文件
+    pub fn preview(self) -> Result<Vec<u8>> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        let streaming = self.request.has_body_channel();
+        self.client.preview_request(self.request, streaming)
+    }
+
     /// 异步发送请求
+    ///
+    /// 丢弃返回的 future 或取消令牌都会中止请求，底层连接随之关闭
     pub async fn send(self) -> Result<Response> {
-        self.client.send_request(self.request).await
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+
+        let client = self.client;
+        let request = self.request;
+        let response = client.send_request(request);
+
+        match self.cancel_token {
+            Some(token) => {
+                tokio::select! {
+                    biased;
+                    _ = token.cancelled() => Err(Error::other("request cancelled")),
+                    result = response => result,
+                }
+            }
+            None => response.await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::HttpClient;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_range_header() {
+        let client = HttpClient::new();
+        let request = client.get("http://example.com/file").range(0, Some(1023)).build();
+        assert_eq!(request.headers.get("Range").unwrap(), "bytes=0-1023");
+
+        let request = client.get("http://example.com/file").range(2048, None).build();
+        assert_eq!(request.headers.get("Range").unwrap(), "bytes=2048-");
+    }
+
+    #[tokio::test]
+    async fn test_header_injection_rejected() {
+        let client = HttpClient::new();
+        let result = client
+            .get("http://127.0.0.1:1/")
+            .header("X-Test", "ok\r\nX-Injected: 1")
+            .send()
+            .await;
+        assert!(matches!(result, Err(Error::HttpParse(_))));
+
+        assert!(client.get("http://example.com/").try_header("X-Bad\nName", "v").is_err());
+        let request = client.get("http://example.com/").try_header("X-Good", "v").unwrap().build();
+        assert_eq!(request.headers.get("X-Good").unwrap(), "v");
+
+        // 直接构造的请求在序列化时同样会被拒绝
+        let request = Request::get("http://example.com/").header("X-Test", "a\r\nb");
+        assert!(request.serialize().is_err());
+    }
+
+    #[test]
+    fn test_forwarding_headers_append() {
+        let client = HttpClient::new();
+        let request = client
+            .get("http://example.com/")
+            .header("x-forwarded-for", "198.51.100.7")
+            .forwarded_for("203.0.113.1".parse().unwrap())
+            .forwarded_for("2001:db8::1".parse().unwrap())
+            .forwarded("for=192.0.2.60;proto=http")
+            .forwarded("for=\"[2001:db8::1]\"")
+            .build();
+
+        assert_eq!(
+            request.headers.get("X-Forwarded-For").unwrap(),
+            "198.51.100.7, 203.0.113.1, 2001:db8::1"
+        );
+        assert!(!request.headers.contains_key("x-forwarded-for"));
+        assert_eq!(
+            request.headers.get("Forwarded").unwrap(),
+            "for=192.0.2.60;proto=http, for=\"[2001:db8::1]\""
+        );
+    }
+
+    #[test]
+    fn test_delete_with_json_body() {
+        let client = HttpClient::new();
+        let request = client
+            .delete("http://example.com/items")
+            .json(&serde_json::json!({"ids": [1, 2]}))
+            .unwrap()
+            .build();
+        assert_eq!(request.body.as_deref(), Some(&br#"{"ids":[1,2]}"#[..]));
+        assert_eq!(request.headers.get("Content-Type").unwrap(), "application/json");
+    }
+
+    #[test]
+    fn test_try_clone_buffered_and_streaming_bodies() {
+        let client = HttpClient::new();
+        let builder = client.post("http://example.com/upload").body("buffered");
+        let cloned = builder.try_clone().unwrap();
+        assert_eq!(cloned.build().body.as_deref(), Some(&b"buffered"[..]));
+        assert_eq!(builder.build().body.as_deref(), Some(&b"buffered"[..]));
+
+        let request = client.put("http://example.com/item").body("data").build();
+        assert_eq!(request.try_clone().unwrap().body, request.body);
+
+        let (_tx, rx) = mpsc::channel(1);
+        let streaming = client.post("http://example.com/upload").body_channel(rx);
+        assert!(streaming.try_clone().is_none());
+        assert!(streaming.build().try_clone().is_none());
+    }
+
+    #[test]
+    fn test_asterisk_form_request_line() {
+        let client = HttpClient::builder().browser_headers(false).build().unwrap();
+        let preview = client.get("http://example.com:8080/ignored?x=1").asterisk_form().preview().unwrap();
+        let preview = String::from_utf8(preview).unwrap();
+        assert!(preview.starts_with("OPTIONS * HTTP/1.1\r\nHost: example.com:8080\r\n"), "{}", preview);
+
+        let client = HttpClient::builder().base_url("https://api.example.com/v1/").build().unwrap();
+        let preview = String::from_utf8(client.options("*").preview().unwrap()).unwrap();
+        assert!(preview.starts_with("OPTIONS * HTTP/1.1\r\nHost: api.example.com\r\n"), "{}", preview);
+
+        let preview = String::from_utf8(client.options("items").preview().unwrap()).unwrap();
+        assert!(preview.starts_with("OPTIONS /v1/items HTTP/1.1\r\n"), "{}", preview);
+
+        let error = HttpClient::new().options("*").preview().unwrap_err();
+        assert!(matches!(error, Error::UrlParse(_)), "{}", error);
+    }
+
+    #[test]
+    fn test_omit_host_for_http10() {
+        let client = HttpClient::builder().browser_headers(false).build().unwrap();
+        let preview = client.get("http://192.168.1.20/status").version(Version::Http1_0).omit_host().preview().unwrap();
+        let preview = String::from_utf8(preview).unwrap();
+        assert!(preview.starts_with("GET /status HTTP/1.0\r\n"), "{}", preview);
+        assert!(!preview.to_ascii_lowercase().contains("host:"), "{}", preview);
+
+        // 显式设置的 Host 仍会发送
+        let preview = client
+            .get("http://192.168.1.20/status")
+            .version(Version::Http1_0)
+            .omit_host()
+            .host_header("device.local")
+            .preview()
+            .unwrap();
+        assert!(String::from_utf8(preview).unwrap().contains("\r\nHost: device.local\r\n"));
+
+        // HTTP/1.1 必须携带 Host
+        let preview = client.get("http://192.168.1.20/status").omit_host().preview().unwrap();
+        assert!(String::from_utf8(preview).unwrap().contains("\r\nHost: 192.168.1.20\r\n"));
+    }
+
+    #[test]
+    fn test_preview_serialized_request() {
+        let mut default_headers = crate::headers::HeaderMap::new();
+        default_headers.insert("X-Client", "rr").unwrap();
+        let client = HttpClient::builder().default_headers(default_headers).build().unwrap();
+        let bytes = client
+            .post("http://example.com:8080/items?page=1")
+            .header("X-Trace", "abc")
+            .body("data")
+            .preview()
+            .unwrap();
+        let preview = String::from_utf8(bytes).unwrap();
+
+        assert!(preview.starts_with("POST /items?page=1 HTTP/1.1\r\n"));
+        assert!(preview.contains("\r\nHost: example.com:8080\r\n"));
+        assert!(preview.contains("\r\nx-client: rr\r\n"));
+        assert!(preview.contains("\r\nX-Trace: abc\r\n"));
+        assert!(preview.contains("\r\nContent-Length: 4\r\n"));
+        assert!(preview.ends_with("\r\n\r\ndata"));
+
+        let error = client.get("http://example.com/").header("Bad\nName", "x").preview();
+        assert!(error.is_err());
+    }
+
+    #[test]
+    fn test_empty_post_sends_content_length_zero() {
+        let client = HttpClient::builder().no_browser_headers().build().unwrap();
+        let preview = String::from_utf8(client.post("http://example.com/trigger").empty_body().preview().unwrap()).unwrap();
+        assert!(preview.starts_with("POST /trigger HTTP/1.1\r\n"));
+        assert!(preview.ends_with("\r\nContent-Length: 0\r\n\r\n"), "{}", preview);
+
+        let preview = String::from_utf8(client.get("http://example.com/").preview().unwrap()).unwrap();
+        assert!(!preview.to_ascii_lowercase().contains("content-length"), "{}", preview);
+    }
+
+    #[test]
+    fn test_form_map_body() {
+        let client = HttpClient::new();
+        let fields = HashMap::from([
+            ("user".to_string(), "alice".to_string()),
+            ("note".to_string(), "a&b c".to_string()),
+        ]);
+        let request = client.post("http://example.com/login").form_map(&fields).build();
+
+        assert_eq!(request.headers.get("Content-Type").unwrap(), "application/x-www-form-urlencoded");
+        let body = String::from_utf8(request.body.unwrap().to_vec()).unwrap();
+        let mut pairs: Vec<&str> = body.split('&').collect();
+        pairs.sort();
+        assert_eq!(pairs, vec!["note=a%26b%20c", "user=alice"]);
+    }
+
+    #[test]
+    fn test_json_pretty_body() {
+        let client = HttpClient::new();
+        let data = serde_json::json!({"name": "rr", "tags": ["http"]});
+
+        let compact = client.post("http://example.com/").json(&data).unwrap().build();
+        assert_eq!(compact.body.as_deref(), Some(&br#"{"name":"rr","tags":["http"]}"#[..]));
+
+        let pretty = client.post("http://example.com/").json_pretty(&data).unwrap().build();
+        let body = String::from_utf8(pretty.body.unwrap().to_vec()).unwrap();
+        assert_eq!(body, "{\n  \"name\": \"rr\",\n  \"tags\": [\n    \"http\"\n  ]\n}");
+        assert_eq!(pretty.headers.get("Content-Type").unwrap(), "application/json");
+
+        let tabs = client
+            .post("http://example.com/")
+            .json_with(&data, &JsonOptions::compact().indent("\t"))
+            .unwrap()
+            .build();
+        assert!(tabs.body.unwrap().starts_with(b"{\n\t\"name\""));
+    }
+
+    #[tokio::test]
+    async fn test_file_body_guesses_content_type() {
+        let path = std::env::temp_dir().join(format!("rr-upload-{}.json", std::process::id()));
+        std::fs::write(&path, br#"{"name":"rr"}"#).unwrap();
+
+        // 构建时只记录路径，发送时才读取文件
+        let client = HttpClient::builder().no_browser_headers().build().unwrap();
+        let request = client.put("http://example.com/upload").file(&path).build();
+        assert_eq!(request.headers.get("Content-Type").unwrap(), "application/json");
+        assert!(request.body.is_none());
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut received = Vec::new();
+            let mut buffer = vec![0u8; 4096];
+            while !received.ends_with(b"}") {
+                let n = stream.read(&mut buffer).await.unwrap();
+                assert!(n > 0, "connection closed before body");
+                received.extend_from_slice(&buffer[..n]);
+            }
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+                .await
+                .unwrap();
+            String::from_utf8(received).unwrap()
+        });
+        client.put(&format!("http://127.0.0.1:{}/upload", port)).file(&path).send().await.unwrap();
+        let received = server.await.unwrap();
+        assert!(received.contains("Content-Type: application/json\r\n"), "{}", received);
+        assert!(received.ends_with("\r\n\r\n{\"name\":\"rr\"}"), "{}", received);
+
+        // 显式设置的 Content-Type 不被覆盖
+        let request = client
+            .put("http://example.com/upload")
+            .header("content-type", "text/plain")
+            .file(&path)
+            .build();
+        assert_eq!(request.headers.get("content-type").unwrap(), "text/plain");
+        assert!(!request.headers.contains_key("Content-Type"));
+        std::fs::remove_file(&path).unwrap();
+
+        let error = client.put("http://127.0.0.1:9/upload").file(&path).send().await.unwrap_err();
+        assert!(error.to_string().contains("Failed to read"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_body_channel_sends_chunk_frames() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        // 读取到结束块后再响应
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut received = Vec::new();
+            let mut buffer = vec![0u8; 4096];
+            while !received.ends_with(b"0\r\n\r\n") {
+                let n = stream.read(&mut buffer).await.unwrap();
+                assert!(n > 0, "connection closed before last chunk");
+                received.extend_from_slice(&buffer[..n]);
+            }
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+                .await
+                .unwrap();
+            String::from_utf8(received).unwrap()
+        });
+
+        let (tx, rx) = mpsc::channel(4);
+        tokio::spawn(async move {
+            for chunk in ["hello", " ", "streaming world"] {
+                tx.send(Bytes::from(chunk)).await.unwrap();
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+        });
+
+        let client = HttpClient::new();
+        let response = client
+            .post(&format!("http://127.0.0.1:{}/upload", port))
+            .body_channel(rx)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status_code, 200);
+
+        let received = server.await.unwrap();
+        let (head, body) = received.split_once("\r\n\r\n").unwrap();
+        assert!(head.to_ascii_lowercase().contains("transfer-encoding: chunked"));
+        assert!(!head.to_ascii_lowercase().contains("content-length"));
+        assert_eq!(body, "5\r\nhello\r\n1\r\n \r\nf\r\nstreaming world\r\n0\r\n\r\n");
+    }
+
+    #[tokio::test]
+    async fn test_expect_trailers() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = vec![0u8; 4096];
+            let n = stream.read(&mut buffer).await.unwrap();
+            stream
+                .write_all(
+                    b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\nTrailer: grpc-status\r\n\r\n\
+                      4\r\ndata\r\n0\r\ngrpc-status: 0\r\n\r\n",
+                )
+                .await
+                .unwrap();
+            String::from_utf8_lossy(&buffer[..n]).to_string()
+        });
+
+        let client = HttpClient::new();
+        let response = client
+            .get(&format!("http://127.0.0.1:{}/rpc", port))
+            .expect_trailers()
+            .expect_trailers()
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.body, b"data");
+        assert_eq!(response.trailers().get("grpc-status").unwrap(), "0");
+        assert!(response.missing_trailers().is_empty());
+
+        let request = server.await.unwrap();
+        assert_eq!(request.matches("TE: trailers\r\n").count(), 1, "{}", request);
+    }
+
+    #[tokio::test]
+    async fn test_host_header_override() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = vec![0u8; 4096];
+            let n = stream.read(&mut buffer).await.unwrap();
+            stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n").await.unwrap();
+            String::from_utf8_lossy(&buffer[..n]).to_string()
+        });
+
+        let client = HttpClient::new();
+        let response = client
+            .get(&format!("http://127.0.0.1:{}/route", port))
+            .header("HOST", "ignored.example")
+            .host_header("tenant.internal")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status_code, 200);
+
+        let request = server.await.unwrap();
+        assert!(request.contains("\r\nHost: tenant.internal\r\n"), "{}", request);
+        assert!(!request.contains("127.0.0.1"), "{}", request);
+        assert!(!request.to_ascii_lowercase().contains("ignored.example"), "{}", request);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_in_flight_request() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        // 服务器读取请求后不响应，直到客户端关闭连接
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = vec![0u8; 4096];
+            let _ = stream.read(&mut buffer).await.unwrap();
+            stream.read(&mut buffer).await.unwrap()
+        });
+
+        let token = CancellationToken::new();
+        let canceller = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            canceller.cancel();
+        });
+
+        let client = HttpClient::new();
+        let result = client
+            .get(&format!("http://127.0.0.1:{}/slow", port))
+            .cancel_token(token.clone())
+            .send()
+            .await;
+
+        assert!(token.is_cancelled());
+        assert!(result.unwrap_err().to_string().contains("request cancelled"));
+        // 连接已被关闭而不是保留，服务器读到 EOF
+        assert_eq!(server.await.unwrap(), 0);
     }
 }
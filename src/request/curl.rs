@@ -0,0 +1,225 @@
+//! curl 命令解析
+//!
+//! 将 curl 命令行转换为 Request，便于移植文档中的 API 示例
+
+use crate::error::{Error, Result};
+use crate::utils::base64_encode;
+
+use super::model::Request;
+use super::types::Method;
+
+impl Request {
+    /// 从 curl 命令字符串构造请求
+    ///
+    /// 支持 `-X/--request`、`-H/--header`（可重复）、`-d/--data/--data-raw`（隐含 POST）、
+    /// `-u/--user`（Basic 认证）以及 URL，遇到不支持的参数时返回错误。
+    /// 请求不带 `Request::new` 的默认请求头，只包含命令中指定的内容
+    pub fn from_curl(command: &str) -> Result<Request> {
+        let mut args = split_command(command)?.into_iter();
+        if args.next().as_deref() != Some("curl") {
+            return Err(Error::other("curl command must start with `curl`"));
+        }
+
+        let mut method = None;
+        let mut url = None;
+        let mut headers = Vec::new();
+        let mut data: Vec<String> = Vec::new();
+        let mut user = None;
+
+        while let Some(arg) = args.next() {
+            if !arg.starts_with('-') || arg == "-" {
+                if url.replace(arg).is_some() {
+                    return Err(Error::other("curl command contains more than one URL"));
+                }
+                continue;
+            }
+
+            // 拆分 `--flag=value` 和 `-Xvalue` 形式
+            // 短选项按字符拆分，避免在多字节字符中间切片
+            let short_split = arg.char_indices().nth(2).map(|(index, _)| index);
+            let (flag, inline_value) = match (arg.split_once('='), short_split) {
+                (Some((flag, value)), _) if arg.starts_with("--") => (flag.to_string(), Some(value.to_string())),
+                (_, Some(index)) if !arg.starts_with("--") => (arg[..index].to_string(), Some(arg[index..].to_string())),
+                _ => (arg.clone(), None),
+            };
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| Error::other(format!("curl option {} requires a value", flag)))
+            };
+
+            match flag.as_str() {
+                "-X" | "--request" => method = Some(value()?),
+                "-H" | "--header" => headers.push(value()?),
+                "-d" | "--data" | "--data-raw" | "--data-ascii" => data.push(value()?),
+                "-u" | "--user" => user = Some(value()?),
+                "--url" => {
+                    if url.replace(value()?).is_some() {
+                        return Err(Error::other("curl command contains more than one URL"));
+                    }
+                }
+                _ => return Err(Error::other(format!("unsupported curl option: {}", flag))),
+            }
+        }
+
+        let url = url.ok_or_else(|| Error::other("curl command is missing a URL"))?;
+        let method = match method {
+            Some(name) => {
+                let method = Method::from(name.as_str());
+                if !method.as_str().eq_ignore_ascii_case(&name) {
+                    return Err(Error::other(format!("unsupported HTTP method: {}", name)));
+                }
+                method
+            }
+            None if !data.is_empty() => Method::POST,
+            None => Method::GET,
+        };
+
+        let mut request = Request::minimal(method, &url);
+        if !data.is_empty() {
+            // 与 curl 一致：多个 -d 以 & 连接，默认使用表单类型
+            request = request.header("Content-Type", "application/x-www-form-urlencoded");
+        }
+        if let Some(user) = user {
+            request = request.header("Authorization", format!("Basic {}", base64_encode(user.as_bytes())));
+        }
+        for header in headers {
+            let (key, value) = header
+                .split_once(':')
+                .ok_or_else(|| Error::other(format!("invalid curl header: {}", header)))?;
+            // 显式请求头覆盖同名的隐含请求头
+            request.headers.retain(|existing, _| !existing.eq_ignore_ascii_case(key.trim()));
+            request = request.header(key.trim(), value.trim());
+        }
+        if !data.is_empty() {
+            request = request.body(data.join("&"));
+        }
+
+        Ok(request)
+    }
+}
+
+/// 按 shell 规则拆分命令行参数，支持单引号、双引号、反斜杠转义和续行
+fn split_command(command: &str) -> Result<Vec<String>> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut chars = command.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_arg = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => return Err(Error::other("unterminated single quote in curl command")),
+                    }
+                }
+            }
+            '"' => {
+                in_arg = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => current.push(c),
+                            Some('\n') => {}
+                            Some(c) => {
+                                current.push('\\');
+                                current.push(c);
+                            }
+                            None => return Err(Error::other("unterminated double quote in curl command")),
+                        },
+                        Some(c) => current.push(c),
+                        None => return Err(Error::other("unterminated double quote in curl command")),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                // 反斜杠加换行为续行
+                Some('\n') => {}
+                Some(c) => {
+                    in_arg = true;
+                    current.push(c);
+                }
+                None => {}
+            },
+            c if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            c => {
+                in_arg = true;
+                current.push(c);
+            }
+        }
+    }
+    if in_arg {
+        args.push(current);
+    }
+
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_curl_get_with_headers() {
+        let request = Request::from_curl(
+            "curl 'https://api.example.com/items?page=2' \\\n  -H 'Accept: application/json' -H \"X-Token: a b\"",
+        )
+        .unwrap();
+
+        assert_eq!(request.method, Method::GET);
+        assert_eq!(request.url, "https://api.example.com/items?page=2");
+        assert_eq!(request.headers.get("Accept").unwrap(), "application/json");
+        assert_eq!(request.headers.get("X-Token").unwrap(), "a b");
+        assert!(request.body.is_none());
+    }
+
+    #[test]
+    fn test_from_curl_post_with_data_and_auth() {
+        let request = Request::from_curl(
+            "curl -u user:pass -d name=rr --data 'v=1' https://api.example.com/items",
+        )
+        .unwrap();
+
+        assert_eq!(request.method, Method::POST);
+        assert_eq!(request.headers.get("Authorization").unwrap(), "Basic dXNlcjpwYXNz");
+        assert_eq!(
+            request.headers.get("Content-Type").unwrap(),
+            "application/x-www-form-urlencoded"
+        );
+        assert_eq!(request.body.as_deref(), Some(&b"name=rr&v=1"[..]));
+
+        let request = Request::from_curl(
+            "curl -XPUT --header='content-type: application/json' --data-raw '{\"a\":1}' https://example.com",
+        )
+        .unwrap();
+        assert_eq!(request.method, Method::PUT);
+        assert_eq!(request.headers.len(), 2);
+        assert_eq!(request.headers.get("content-type").unwrap(), "application/json");
+    }
+
+    #[test]
+    fn test_from_curl_rejects_unsupported() {
+        assert!(Request::from_curl("curl --compressed https://example.com").is_err());
+        assert!(Request::from_curl("curl -X BREW https://example.com").is_err());
+        assert!(Request::from_curl("curl -H 'Accept: */*'").is_err());
+        assert!(Request::from_curl("wget https://example.com").is_err());
+        assert!(Request::from_curl("curl 'https://example.com").is_err());
+    }
+
+    #[test]
+    fn test_from_curl_multibyte_short_option() {
+        assert!(Request::from_curl("curl -é http://x").is_err());
+        assert!(Request::from_curl("curl -éX http://x").is_err());
+    }
+}
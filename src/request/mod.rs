@@ -5,8 +5,10 @@
 pub mod types;
 pub mod model;
 pub mod builder;
+mod curl;
 
 // 导出主要类型
-pub use types::{Method, Version};
-pub use model::Request;
+pub use types::{ContentLengthPolicy, JsonOptions, Method, RequestTarget, Version};
+pub use model::{build_request_line, Request};
 pub use builder::AsyncRequestBuilder;
+pub use tokio_util::sync::CancellationToken;
@@ -3,11 +3,23 @@
 //! 包含Request结构体的定义和实现
 
 use crate::error::{Error, Result};
-use crate::utils::parse_host_port;
+use crate::headers::{content_types, normalize_header_value, validate_header_name, validate_header_value, HeaderMap};
+use crate::utils::{build_query_string, guess_mime_type, parse_host_port};
 use bytes::Bytes;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
 
-use super::types::{Method, Version};
+use super::types::{ContentLengthPolicy, JsonOptions, Method, RequestTarget, Version};
+use crate::utils::ParsedUrl;
+
+/// `Request::new` 默认添加的请求头
+const DEFAULT_HEADERS: [(&str, &str); 3] = [
+    ("User-Agent", "rust-my-request/0.1.0"),
+    ("Accept", "*/*"),
+    ("Connection", "close"),
+];
 
 /// HTTP请求结构体
 #[derive(Debug, Clone)]
@@ -22,6 +34,41 @@ pub struct Request {
     pub headers: HashMap<String, String>,
     /// 请求体
     pub body: Option<Bytes>,
+    /// 显式 Content-Length 与请求体长度不一致时的处理方式
+    pub content_length_policy: ContentLengthPolicy,
+    /// 非幂等方法的请求是否也可以安全重试（如带 Idempotency-Key 的 POST）
+    pub retry_safe: bool,
+    /// 覆盖本次请求所用连接的 TCP_NODELAY，None 时保持建立连接时的设置（启用）
+    pub tcp_nodelay: Option<bool>,
+    /// HTTP/1.0 请求是否省略自动添加的 Host 头
+    pub omit_host: bool,
+    /// OPTIONS 请求是否使用星号形式的请求目标（`OPTIONS * HTTP/1.1`）
+    pub asterisk_form: bool,
+    /// 来自通道的请求体，只能发送一次
+    pub(crate) body_stream: Option<BodyStream>,
+    /// 发送时读取的请求体文件
+    pub(crate) body_file: Option<PathBuf>,
+    /// 请求头的发送顺序（小写名称），未列出的请求头排在其后
+    pub(crate) header_order: Vec<String>,
+    /// 仍为 `Request::new` 内置默认值、未被显式设置过的请求头名称
+    pub(crate) builtin_headers: Vec<&'static str>,
+}
+
+/// 来自通道的请求体，克隆的请求共享同一个通道，只有第一次发送能取出
+#[derive(Clone)]
+pub(crate) struct BodyStream(Arc<Mutex<Option<mpsc::Receiver<Bytes>>>>);
+
+impl BodyStream {
+    /// 取出通道，已被取出时返回 None
+    pub(crate) fn take(&self) -> Option<mpsc::Receiver<Bytes>> {
+        self.0.lock().unwrap().take()
+    }
+}
+
+impl std::fmt::Debug for BodyStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("BodyStream")
+    }
 }
 
 impl Request {
@@ -47,32 +94,53 @@ impl Request {
 
     /// 创建新的请求
     pub fn new(method: Method, url: &str) -> Self {
-        let mut headers = HashMap::new();
-
         // 设置默认请求头
-        headers.insert(
-            "User-Agent".to_string(),
-            "rust-my-request/0.1.0".to_string(),
-        );
-        headers.insert("Accept".to_string(), "*/*".to_string());
-        headers.insert("Connection".to_string(), "close".to_string());
+        let mut request = Self::minimal(method, url).headers(DEFAULT_HEADERS);
+        request.builtin_headers = DEFAULT_HEADERS.iter().map(|(key, _)| *key).collect();
+        request
+    }
 
+    /// 创建不带任何默认请求头的请求
+    /// 序列化时只包含请求行、Host 以及显式设置的请求头
+    pub fn minimal(method: Method, url: &str) -> Self {
         Self {
             method,
             url: url.to_string(),
             version: Version::default(),
-            headers,
+            headers: HashMap::new(),
             body: None,
+            content_length_policy: ContentLengthPolicy::default(),
+            retry_safe: false,
+            tcp_nodelay: None,
+            omit_host: false,
+            asterisk_form: false,
+            body_stream: None,
+            body_file: None,
+            header_order: Vec::new(),
+            builtin_headers: Vec::new(),
         }
     }
 
+    /// 移除 `Request::new` 添加的默认请求头（User-Agent、Accept、Connection）
+    /// 已被显式修改过的同名请求头会被保留
+    pub fn without_default_headers(mut self) -> Self {
+        for (key, value) in DEFAULT_HEADERS {
+            if self.headers.get(key).is_some_and(|current| current == value) {
+                self.headers.remove(key);
+            }
+        }
+        self
+    }
+
     /// 设置请求头
     pub fn header<K, V>(mut self, key: K, value: V) -> Self
     where
         K: Into<String>,
         V: Into<String>,
     {
-        self.headers.insert(key.into(), value.into());
+        let key = key.into();
+        self.builtin_headers.retain(|name| !name.eq_ignore_ascii_case(&key));
+        self.headers.insert(key, value.into());
         self
     }
 
@@ -84,31 +152,205 @@ impl Request {
         I: IntoIterator<Item = (K, V)>,
     {
         for (key, value) in headers {
-            self.headers.insert(key.into(), value.into());
+            self = self.header(key, value);
         }
         self
     }
 
+    /// 设置 Range 请求头，请求 `start` 到 `end`（包含）的字节范围
+    /// `end` 为 None 时请求从 `start` 开始的全部内容
+    pub fn range(self, start: u64, end: Option<u64>) -> Self {
+        let value = match end {
+            Some(end) => format!("bytes={}-{}", start, end),
+            None => format!("bytes={}-", start),
+        };
+        self.header("Range", value)
+    }
+
+    /// 合并默认请求头（请求头名称不区分大小写）
+    /// 请求中不存在该请求头，或其值仍为 `Request::new` 的内置默认值且未被显式设置过时写入 `value`
+    pub(crate) fn merge_default_header(&mut self, key: &str, value: &str) {
+        let existing = self
+            .headers
+            .iter()
+            .find(|(existing_key, _)| existing_key.eq_ignore_ascii_case(key))
+            .map(|(existing_key, existing_value)| (existing_key.clone(), existing_value.clone()));
+
+        match existing {
+            None => {
+                self.headers.insert(key.to_string(), value.to_string());
+            }
+            Some((existing_key, existing_value)) => {
+                let is_builtin_default = self.builtin_headers.iter().any(|name| name.eq_ignore_ascii_case(key))
+                    && DEFAULT_HEADERS.iter().any(|(default_key, default_value)| {
+                        default_key.eq_ignore_ascii_case(key) && *default_value == existing_value
+                    });
+                if is_builtin_default {
+                    self.headers.remove(&existing_key);
+                    self.headers.insert(key.to_string(), value.to_string());
+                }
+            }
+        }
+    }
+
+    /// 获取请求头的 HeaderMap 表示
+    /// 存在无法通过校验的请求头时返回错误
+    pub fn header_map(&self) -> Result<HeaderMap> {
+        let mut header_map = HeaderMap::new();
+        for (key, value) in &self.headers {
+            header_map.insert(key.clone(), value.clone())?;
+        }
+        Ok(header_map)
+    }
+
+    /// 使用 HeaderMap 替换全部请求头
+    pub fn set_header_map(&mut self, headers: HeaderMap) {
+        self.headers = headers
+            .iter()
+            .map(|(key, value)| (key.clone(), normalize_header_value(value)))
+            .collect();
+    }
+
+    /// 设置空请求体，发送 `Content-Length: 0`（与 `body("")` 相同）
+    ///
+    /// 未设置请求体时不发送 Content-Length，部分服务器收到不带 Content-Length 的 POST 会一直等待请求体
+    pub fn empty_body(self) -> Self {
+        self.body(Bytes::new())
+    }
+
     /// 设置请求体
     pub fn body<B: Into<Bytes>>(mut self, body: B) -> Self {
         let body = body.into();
-        self.body = Some(body.clone());
+        let length = body.len();
+        self.body = Some(body);
+        self.body_file = None;
 
         // 如果设置了请求体，自动设置Content-Length
         if !self.headers.contains_key("Content-Length") {
             self.headers
-                .insert("Content-Length".to_string(), body.len().to_string());
+                .insert("Content-Length".to_string(), length.to_string());
         }
 
         self
     }
 
-    /// 设置JSON请求体
-    pub fn json<T: serde::Serialize>(self, _data: &T) -> Result<Self> {
-        // TODO: 添加serde_json依赖后实现
-        Err(Error::other(
-            "JSON serialization not implemented yet. Add serde_json dependency.",
-        ))
+    /// 设置显式 Content-Length 与请求体长度不一致时的处理方式（默认使用实际长度）
+    pub fn content_length_policy(mut self, policy: ContentLengthPolicy) -> Self {
+        self.content_length_policy = policy;
+        self
+    }
+
+    /// 标记请求可以安全重试，即使请求方法不是幂等的
+    pub fn retry_safe(mut self, retry_safe: bool) -> Self {
+        self.retry_safe = retry_safe;
+        self
+    }
+
+    /// 设置本次请求所用连接的 TCP_NODELAY
+    pub fn tcp_nodelay(mut self, nodelay: bool) -> Self {
+        self.tcp_nodelay = Some(nodelay);
+        self
+    }
+
+    /// HTTP/1.0 请求不自动添加 Host 头，显式设置的 Host 仍会发送
+    ///
+    /// HTTP/1.1 要求必须携带 Host，其他版本的请求不受影响
+    pub fn omit_host(mut self) -> Self {
+        self.omit_host = true;
+        self
+    }
+
+    /// 使用星号形式的请求目标，请求方法改为 OPTIONS，URL 只用于确定 Host 和连接目标
+    pub fn asterisk_form(mut self) -> Self {
+        self.method = Method::OPTIONS;
+        self.asterisk_form = true;
+        self
+    }
+
+    /// 克隆请求，用于重试和重定向时重新发送
+    ///
+    /// 请求体来自 `body_channel` 时无法重放，返回 None
+    pub fn try_clone(&self) -> Option<Request> {
+        if self.body_stream.is_some() {
+            return None;
+        }
+        Some(self.clone())
+    }
+
+    /// 从通道接收请求体，每个分块作为一个 chunked 帧发送，通道关闭后结束请求体
+    ///
+    /// 会替换已设置的请求体；请求体只能发送一次，因此不会重试，也不跟随重定向
+    pub fn body_channel(mut self, rx: mpsc::Receiver<Bytes>) -> Self {
+        self.body = None;
+        self.body_file = None;
+        self.body_stream = Some(BodyStream(Arc::new(Mutex::new(Some(rx)))));
+        self
+    }
+
+    /// 使用文件内容作为请求体，并根据扩展名推断 Content-Type
+    ///
+    /// 文件在发送时通过 `tokio::fs` 读取，会替换已设置的请求体；
+    /// 已显式设置 Content-Type 时保留原值，无法识别的扩展名使用 `application/octet-stream`
+    pub fn file(mut self, path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        if !self.headers.keys().any(|key| key.eq_ignore_ascii_case("content-type")) {
+            self.headers.insert("Content-Type".to_string(), guess_mime_type(path).to_string());
+        }
+        self.body = None;
+        self.body_stream = None;
+        self.body_file = Some(path.to_path_buf());
+        self
+    }
+
+    /// 读取 `file` 指定的文件作为请求体
+    pub(crate) async fn load_body_file(&mut self) -> Result<()> {
+        if let Some(path) = self.body_file.take() {
+            let contents = tokio::fs::read(&path).await;
+            self.set_file_body(&path, contents)?;
+        }
+        Ok(())
+    }
+
+    /// 同步读取 `file` 指定的文件作为请求体，只用于不发送请求的预览
+    pub(crate) fn load_body_file_sync(&mut self) -> Result<()> {
+        if let Some(path) = self.body_file.take() {
+            let contents = std::fs::read(&path);
+            self.set_file_body(&path, contents)?;
+        }
+        Ok(())
+    }
+
+    fn set_file_body(&mut self, path: &Path, contents: std::io::Result<Vec<u8>>) -> Result<()> {
+        let contents = contents.map_err(|e| Error::other(format!("Failed to read {}: {}", path.display(), e)))?;
+        self.headers
+            .entry("Content-Length".to_string())
+            .or_insert_with(|| contents.len().to_string());
+        self.body = Some(contents.into());
+        Ok(())
+    }
+
+    /// 请求体是否来自通道
+    pub fn has_body_channel(&self) -> bool {
+        self.body_stream.is_some()
+    }
+
+    /// 请求失败时是否可以重试：幂等方法或显式标记为可重试的请求
+    pub fn is_retry_safe(&self) -> bool {
+        self.method.is_idempotent() || self.retry_safe
+    }
+
+    /// 设置JSON请求体，并设置 Content-Type 为 application/json
+    /// 任何请求方法都可以携带请求体（如 Elasticsearch 的 GET 查询）
+    pub fn json<T: serde::Serialize>(self, data: &T) -> Result<Self> {
+        self.json_with(data, &JsonOptions::compact())
+    }
+
+    /// 按指定格式设置JSON请求体，并设置 Content-Type 为 application/json
+    pub fn json_with<T: serde::Serialize>(self, data: &T, options: &JsonOptions) -> Result<Self> {
+        let body = options
+            .to_vec(data)
+            .map_err(|e| Error::other(format!("JSON serialization failed: {}", e)))?;
+        Ok(self.header("Content-Type", "application/json").body(body))
     }
 
     /// 设置表单数据请求体
@@ -119,43 +361,109 @@ impl Request {
         ))
     }
 
+    /// 设置 `application/x-www-form-urlencoded` 表单请求体，编码方式与 `utils::build_query_string` 相同
+    ///
+    /// HashMap 的遍历顺序不确定，因此字段顺序也不确定
+    pub fn form_map(self, fields: &HashMap<String, String>) -> Self {
+        let body = build_query_string(fields).trim_start_matches('?').to_string();
+        self.header("Content-Type", content_types::FORM).body(body)
+    }
+
     /// 序列化请求为字节流
     pub fn serialize(&self) -> Result<Vec<u8>> {
         let parsed_url = parse_host_port(&self.url)?;
-        let request_str = self.serialize_to_string(&parsed_url)?;
-        Ok(request_str.into_bytes())
+        self.build_request_bytes(&parsed_url, RequestTarget::Origin)
+    }
+
+    /// 序列化为 HTTP 线路格式的字节（请求目标为 origin-form），用于录制和回放
+    ///
+    /// 与 `serialize` 相同，请求体按原始字节写入，不做有损转换
+    pub fn to_raw_bytes(&self) -> Result<Vec<u8>> {
+        self.serialize()
     }
 
     /// 序列化请求为字符串
     pub fn serialize_to_string(&self, parsed_url: &crate::utils::ParsedUrl) -> Result<String> {
-        let mut request_str = format!(
-            "{} {} {}\r\n",
-            self.method.as_str(),
-            parsed_url.full_path,
-            self.version.as_str()
-        );
+        self.build_request_string(parsed_url, RequestTarget::Origin)
+    }
 
-        // 添加Host头
-        request_str.push_str(&format!("Host: {}\r\n", parsed_url.hostname));
+    /// 构建请求字符串，请求行使用 `target` 指定的请求目标形式
+    /// 非 UTF-8 的请求体会被有损转换，发送请求应使用 `build_request_bytes`
+    pub fn build_request_string(&self, parsed_url: &ParsedUrl, target: RequestTarget) -> Result<String> {
+        let bytes = self.build_request_bytes(parsed_url, target)?;
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
 
-        // 添加其他请求头
-        for (key, value) in &self.headers {
-            request_str.push_str(&format!("{}: {}\r\n", key, value));
+    /// 构建请求字节流，请求体按原始字节写入，请求行使用 `target` 指定的请求目标形式
+    pub fn build_request_bytes(&self, parsed_url: &ParsedUrl, target: RequestTarget) -> Result<Vec<u8>> {
+        // 自定义版本标记不能包含换行，防止请求拆分
+        if self.version.as_str().contains(['\r', '\n']) {
+            return Err(Error::http_parse("Invalid character in HTTP version"));
+        }
+
+        let mut request_str = build_request_line(self.method.as_str(), parsed_url, target, self.version.as_str());
+        request_str.push_str("\r\n");
+
+        // 添加Host头，显式设置的 Host 优先于 URL 中的主机（连接和 SNI 仍使用 URL 中的主机）
+        let explicit_host = self.headers.iter().find(|(key, _)| key.eq_ignore_ascii_case("host"));
+        let host = match explicit_host {
+            Some((_, value)) => {
+                validate_header_value(value)?;
+                Some(value.clone())
+            }
+            // HTTP/1.0 中 Host 是可选的，部分老旧设备无法处理
+            None if self.omit_host && self.version == Version::Http1_0 => None,
+            None => Some(parsed_url.host_header()),
+        };
+        if let Some(host) = host {
+            request_str.push_str(&format!("Host: {}\r\n", host));
         }
 
-        // 添加Connection头
-        request_str.push_str("Connection: close\r\n");
+        // 添加其他请求头（有请求体时 Content-Length 按实际长度单独写入，避免重复），
+        // 设置了发送顺序时按该顺序排列
+        let mut headers: Vec<(&String, &String)> = self.headers.iter().collect();
+        if !self.header_order.is_empty() {
+            headers.sort_by_key(|(key, _)| {
+                self.header_order
+                    .iter()
+                    .position(|name| key.eq_ignore_ascii_case(name))
+                    .unwrap_or(usize::MAX)
+            });
+        }
+        for (key, value) in headers {
+            if key.eq_ignore_ascii_case("host") {
+                continue;
+            }
+            if let Some(body) = &self.body
+                && key.eq_ignore_ascii_case("content-length")
+            {
+                if self.content_length_policy == ContentLengthPolicy::Error && value.trim() != body.len().to_string() {
+                    return Err(Error::http_parse(format!(
+                        "Content-Length {} does not match body length {}",
+                        value,
+                        body.len()
+                    )));
+                }
+                continue;
+            }
+            // 拒绝可能导致请求拆分的请求头
+            validate_header_name(key)?;
+            validate_header_value(value)?;
+            request_str.push_str(&format!("{}: {}\r\n", key, value));
+        }
 
         // 添加请求体（如果有）
         if let Some(body) = &self.body {
             request_str.push_str(&format!("Content-Length: {}\r\n", body.len()));
-            request_str.push_str("\r\n");
-            request_str.push_str(&String::from_utf8_lossy(body));
-        } else {
-            request_str.push_str("\r\n");
         }
+        request_str.push_str("\r\n");
 
-        Ok(request_str)
+        let mut request_bytes = request_str.into_bytes();
+        if let Some(body) = &self.body {
+            request_bytes.extend_from_slice(body);
+        }
+
+        Ok(request_bytes)
     }
 
     /// 获取请求体的长度
@@ -164,8 +472,212 @@ impl Request {
     }
 }
 
+/// 构建请求行（不含结尾的 CRLF），请求目标按 `target` 指定的形式生成
+pub fn build_request_line(method: &str, parsed_url: &ParsedUrl, target: RequestTarget, version: &str) -> String {
+    let target = match target {
+        RequestTarget::Origin => parsed_url.full_path.clone(),
+        RequestTarget::Absolute => format!(
+            "{}://{}{}",
+            if parsed_url.is_https { "https" } else { "http" },
+            parsed_url.host_header(),
+            parsed_url.full_path
+        ),
+        RequestTarget::Authority => format!("{}:{}", parsed_url.hostname, parsed_url.port),
+        RequestTarget::Asterisk => "*".to_string(),
+    };
+    format!("{} {} {}", method, target, version)
+}
+
+/// 输出请求行（`METHOD URL VERSION`），便于日志检索
+impl std::fmt::Display for Request {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.method.as_str(), self.url, self.version.as_str())
+    }
+}
+
 impl Default for Request {
     fn default() -> Self {
         Self::new(Method::GET, "http://example.com")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_map_roundtrip() {
+        let mut headers = HeaderMap::new();
+        headers.insert("Content-Type", "application/json").unwrap();
+        headers.insert("X-Custom", " value ").unwrap();
+        assert!(headers.insert("Bad\nName", "value").is_err());
+
+        let mut request = Request::get("http://example.com/path");
+        request.set_header_map(headers);
+
+        let header_map = request.header_map().unwrap();
+        assert_eq!(header_map.len(), 2);
+        assert_eq!(header_map.get("Content-Type").unwrap(), "application/json");
+
+        let serialized = String::from_utf8(request.serialize().unwrap()).unwrap();
+        assert!(serialized.starts_with("GET /path HTTP/1.1\r\n"));
+        assert!(serialized.contains("content-type: application/json\r\n"));
+        assert!(serialized.contains("x-custom: value\r\n"));
+        assert!(!serialized.contains("User-Agent"));
+    }
+
+    #[test]
+    fn test_header_map_rejects_invalid_header() {
+        let request = Request::get("http://example.com/").header("Bad Name", "value");
+        assert!(matches!(request.header_map(), Err(Error::HttpParse(_))));
+    }
+
+    #[test]
+    fn test_minimal_request_serialization() {
+        let request = Request::minimal(Method::GET, "http://example.com/path").header("X-Test", "1");
+        let serialized = String::from_utf8(request.serialize().unwrap()).unwrap();
+        assert_eq!(serialized, "GET /path HTTP/1.1\r\nHost: example.com\r\nX-Test: 1\r\n\r\n");
+
+        let request = Request::get("http://example.com/path")
+            .header("Accept", "text/html")
+            .without_default_headers();
+        assert_eq!(request.headers.len(), 1);
+        assert_eq!(request.headers.get("Accept").unwrap(), "text/html");
+    }
+
+    #[test]
+    fn test_body_sets_content_length() {
+        let payload = vec![7u8; 4096];
+        let request = Request::post("http://example.com/upload").body(payload.clone());
+
+        assert_eq!(request.headers.get("Content-Length").unwrap(), "4096");
+        assert_eq!(request.content_length(), 4096);
+        assert_eq!(request.body.as_deref(), Some(payload.as_slice()));
+    }
+
+    #[test]
+    fn test_mismatched_content_length() {
+        let request = Request::post("http://example.com/upload")
+            .header("content-length", "999")
+            .body("hello");
+        let serialized = String::from_utf8(request.serialize().unwrap()).unwrap();
+        assert_eq!(serialized.to_lowercase().matches("content-length").count(), 1);
+        assert!(serialized.contains("Content-Length: 5\r\n"));
+
+        let request = request.content_length_policy(ContentLengthPolicy::Error);
+        assert!(matches!(request.serialize(), Err(Error::HttpParse(_))));
+
+        let request = Request::post("http://example.com/upload")
+            .header("Content-Length", "5")
+            .body("hello")
+            .content_length_policy(ContentLengthPolicy::Error);
+        assert!(request.serialize().is_ok());
+    }
+
+    #[test]
+    fn test_custom_version_in_request_line() {
+        let mut request = Request::minimal(Method::GET, "http://example.com/");
+        request.version = Version::Custom("HTTP/7.3".to_string());
+        let serialized = String::from_utf8(request.serialize().unwrap()).unwrap();
+        assert!(serialized.starts_with("GET / HTTP/7.3\r\n"));
+
+        request.version = Version::Custom("HTTP/1.1\r\nX-Injected: 1".to_string());
+        assert!(request.serialize().is_err());
+    }
+
+    #[test]
+    fn test_display_request_line() {
+        let request = Request::post("http://example.com/a?b=1");
+        assert_eq!(request.to_string(), "POST http://example.com/a?b=1 HTTP/1.1");
+    }
+
+    #[test]
+    fn test_get_with_json_body() {
+        let request = Request::new(Method::GET, "http://localhost:9200/index/_search")
+            .json(&serde_json::json!({"query": {"match_all": {}}}))
+            .unwrap();
+        let parsed_url = parse_host_port(&request.url).unwrap();
+        let serialized = request.serialize_to_string(&parsed_url).unwrap();
+        let body = r#"{"query":{"match_all":{}}}"#;
+
+        assert!(serialized.starts_with("GET /index/_search HTTP/1.1\r\n"));
+        assert!(serialized.contains("Content-Type: application/json\r\n"));
+        assert_eq!(serialized.matches("Content-Length").count(), 1);
+        assert!(serialized.contains(&format!("Content-Length: {}\r\n", body.len())));
+        assert!(serialized.ends_with(&format!("\r\n\r\n{}", body)));
+    }
+
+    #[test]
+    fn test_empty_body_distinct_from_no_body() {
+        let serialized = String::from_utf8(Request::minimal(Method::POST, "http://example.com/").serialize().unwrap()).unwrap();
+        assert!(!serialized.contains("Content-Length"), "{}", serialized);
+
+        for request in [
+            Request::minimal(Method::POST, "http://example.com/").empty_body(),
+            Request::minimal(Method::POST, "http://example.com/").body(""),
+        ] {
+            let serialized = String::from_utf8(request.serialize().unwrap()).unwrap();
+            assert!(serialized.ends_with("\r\nContent-Length: 0\r\n\r\n"), "{}", serialized);
+            assert_eq!(serialized.matches("Content-Length").count(), 1);
+        }
+    }
+
+    #[test]
+    fn test_explicit_host_header() {
+        let request = Request::minimal(Method::GET, "http://127.0.0.1:8080/").header("host", "api.internal");
+        let serialized = String::from_utf8(request.serialize().unwrap()).unwrap();
+        assert!(serialized.starts_with("GET / HTTP/1.1\r\nHost: api.internal\r\n"), "{}", serialized);
+        assert_eq!(serialized.matches("ost: ").count(), 1);
+    }
+
+    #[test]
+    fn test_host_header_port() {
+        let serialized = String::from_utf8(Request::get("http://x.com:8443/").serialize().unwrap()).unwrap();
+        assert!(serialized.contains("Host: x.com:8443\r\n"));
+
+        let serialized = String::from_utf8(Request::get("https://x.com/").serialize().unwrap()).unwrap();
+        assert!(serialized.contains("Host: x.com\r\n"));
+    }
+
+    #[test]
+    fn test_absolute_form_request_line() {
+        let request = Request::get("http://example.com:8080/path?q=1");
+        let parsed_url = parse_host_port(&request.url).unwrap();
+
+        let origin = request.build_request_string(&parsed_url, RequestTarget::Origin).unwrap();
+        assert!(origin.starts_with("GET /path?q=1 HTTP/1.1\r\n"));
+
+        let absolute = request.build_request_string(&parsed_url, RequestTarget::Absolute).unwrap();
+        assert!(absolute.starts_with("GET http://example.com:8080/path?q=1 HTTP/1.1\r\n"));
+        assert!(absolute.contains("Host: example.com:8080\r\n"));
+    }
+
+    #[test]
+    fn test_build_request_line_forms() {
+        let parsed_url = parse_host_port("https://example.com/a/b?c=d").unwrap();
+        let line = |method, target| build_request_line(method, &parsed_url, target, "HTTP/1.1");
+
+        assert_eq!(line("GET", RequestTarget::Origin), "GET /a/b?c=d HTTP/1.1");
+        assert_eq!(line("GET", RequestTarget::Absolute), "GET https://example.com/a/b?c=d HTTP/1.1");
+        assert_eq!(line("CONNECT", RequestTarget::Authority), "CONNECT example.com:443 HTTP/1.1");
+        assert_eq!(line("OPTIONS", RequestTarget::Asterisk), "OPTIONS * HTTP/1.1");
+    }
+
+    #[test]
+    fn test_request_target_selection() {
+        let request = Request::minimal(Method::GET, "http://example.com/items");
+        assert_eq!(RequestTarget::for_request(&request, false), RequestTarget::Origin);
+        assert_eq!(RequestTarget::for_request(&request, true), RequestTarget::Absolute);
+
+        // 路径恰好为 `/*` 的 OPTIONS 请求仍使用路径形式
+        let request = Request::minimal(Method::OPTIONS, "http://example.com/*");
+        assert_eq!(RequestTarget::for_request(&request, false), RequestTarget::Origin);
+
+        let parsed_url = parse_host_port("http://example.com/").unwrap();
+        let request = Request::minimal(Method::GET, "http://example.com/").asterisk_form();
+        assert_eq!(request.method, Method::OPTIONS);
+        assert_eq!(RequestTarget::for_request(&request, false), RequestTarget::Asterisk);
+        let serialized = String::from_utf8(request.build_request_bytes(&parsed_url, RequestTarget::Asterisk).unwrap()).unwrap();
+        assert!(serialized.starts_with("OPTIONS * HTTP/1.1\r\nHost: example.com\r\n"));
+    }
+}
@@ -31,6 +31,16 @@ impl Method {
             Method::TRACE => "TRACE",
         }
     }
+
+    /// 是否为幂等方法（重复发送与发送一次效果相同，可安全重试）
+    pub fn is_idempotent(&self) -> bool {
+        !matches!(self, Method::POST | Method::PATCH)
+    }
+
+    /// 是否为安全方法（不会修改服务器状态）
+    pub fn is_safe(&self) -> bool {
+        matches!(self, Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE)
+    }
 }
 
 impl From<&str> for Method {
@@ -49,23 +59,148 @@ impl From<&str> for Method {
 }
 
 /// HTTP版本枚举
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub enum Version {
+    Http0_9,
     Http1_0,
+    #[default]
     Http1_1,
+    Http2,
+    /// 自定义版本标记，原样写入请求行（用于测试服务器的版本处理）
+    Custom(String),
 }
 
 impl Version {
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         match self {
+            Version::Http0_9 => "HTTP/0.9",
             Version::Http1_0 => "HTTP/1.0",
             Version::Http1_1 => "HTTP/1.1",
+            Version::Http2 => "HTTP/2",
+            Version::Custom(version) => version,
+        }
+    }
+}
+
+impl From<&str> for Version {
+    /// 解析版本字符串，无法识别的版本回退为 HTTP/1.1
+    fn from(s: &str) -> Self {
+        match s.trim().to_uppercase().as_str() {
+            "HTTP/0.9" => Version::Http0_9,
+            "HTTP/1.0" => Version::Http1_0,
+            "HTTP/2" | "HTTP/2.0" => Version::Http2,
+            _ => Version::Http1_1,
         }
     }
 }
 
-impl Default for Version {
-    fn default() -> Self {
-        Version::Http1_1
+/// 请求行中请求目标的形式（RFC 9112 第 3.2 节）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RequestTarget {
+    /// 路径和查询参数（`/path?query`），直接发送给源服务器时使用
+    #[default]
+    Origin,
+    /// 完整 URL，经 HTTP 代理发送明文请求时使用
+    Absolute,
+    /// `host:port`，用于 CONNECT 请求
+    Authority,
+    /// `*`，用于针对整个服务器的 OPTIONS 请求
+    Asterisk,
+}
+
+impl RequestTarget {
+    /// 为请求选择请求目标形式
+    ///
+    /// 经 HTTP 代理发送明文请求时使用完整 URL；通过 `asterisk_form` 显式要求的 OPTIONS 请求使用 `*`；
+    /// 其余情况使用路径形式
+    pub fn for_request(request: &super::Request, via_http_proxy: bool) -> Self {
+        if via_http_proxy {
+            RequestTarget::Absolute
+        } else if request.method == Method::OPTIONS && request.asterisk_form {
+            RequestTarget::Asterisk
+        } else {
+            RequestTarget::Origin
+        }
+    }
+}
+
+/// JSON 请求体的序列化格式
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct JsonOptions {
+    /// 缩进字符串，为 None 时输出紧凑格式
+    indent: Option<String>,
+}
+
+impl JsonOptions {
+    /// 紧凑格式（无空白），默认值
+    pub fn compact() -> Self {
+        Self::default()
+    }
+
+    /// 带换行和两个空格缩进的格式
+    pub fn pretty() -> Self {
+        Self::compact().indent("  ")
+    }
+
+    /// 使用指定的缩进字符串（如 `"\t"`）输出带换行的格式
+    pub fn indent(mut self, indent: impl Into<String>) -> Self {
+        self.indent = Some(indent.into());
+        self
+    }
+
+    /// 按格式序列化为 JSON 字节
+    pub fn to_vec<T: serde::Serialize + ?Sized>(&self, data: &T) -> serde_json::Result<Vec<u8>> {
+        let Some(indent) = &self.indent else {
+            return serde_json::to_vec(data);
+        };
+        let mut body = Vec::new();
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+        let mut serializer = serde_json::Serializer::with_formatter(&mut body, formatter);
+        data.serialize(&mut serializer)?;
+        Ok(body)
+    }
+}
+
+/// 显式设置的 Content-Length 与请求体长度不一致时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ContentLengthPolicy {
+    /// 使用请求体的实际长度
+    #[default]
+    Override,
+    /// 序列化时返回错误
+    Error,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_method_semantics() {
+        let cases = [
+            (Method::GET, true, true),
+            (Method::HEAD, true, true),
+            (Method::OPTIONS, true, true),
+            (Method::TRACE, true, true),
+            (Method::PUT, true, false),
+            (Method::DELETE, true, false),
+            (Method::POST, false, false),
+            (Method::PATCH, false, false),
+        ];
+
+        for (method, idempotent, safe) in cases {
+            assert_eq!(method.is_idempotent(), idempotent, "{:?}", method);
+            assert_eq!(method.is_safe(), safe, "{:?}", method);
+        }
+    }
+
+    #[test]
+    fn test_version_from_str() {
+        assert_eq!(Version::from("HTTP/0.9"), Version::Http0_9);
+        assert_eq!(Version::from("HTTP/1.0"), Version::Http1_0);
+        assert_eq!(Version::from("HTTP/1.1"), Version::Http1_1);
+        assert_eq!(Version::from("HTTP/2"), Version::Http2);
+        assert_eq!(Version::from("HTTP/2.0"), Version::Http2);
+        assert_eq!(Version::from("HTTP/9.9"), Version::Http1_1);
     }
 }
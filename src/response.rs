@@ -1,8 +1,19 @@
 use std::collections::HashMap;
 use std::fmt;
 use crate::{error::Result, Error};
-use crate::decompression::{Compression, decompress};
+use crate::decompression::{Compression, Decoders, decompress};
 use crate::chunked::ChunkedParser;
+use crate::headers::HeaderMap;
+use crate::request::{Method, Version};
+use crate::timings::Timings;
+use crate::utils::parse_http_date;
+use std::time::{Duration, SystemTime};
+use tokio::io::AsyncRead;
+use encoding_rs::{Encoding, UTF_8, WINDOWS_1252};
+use bytes::Bytes;
+
+/// 默认的响应头部行数上限
+pub const DEFAULT_MAX_HEADER_COUNT: usize = 100;
 
 /// HTTP 状态码结构体（兼容 reqwest::StatusCode）
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -35,6 +46,42 @@ impl StatusCode {
     pub fn as_u16(&self) -> u16 {
         self.code
     }
+
+    /// 获取状态码对应的标准原因短语
+    pub fn canonical_reason(&self) -> Option<&'static str> {
+        let reason = match self.code {
+            100 => "Continue",
+            101 => "Switching Protocols",
+            200 => "OK",
+            201 => "Created",
+            202 => "Accepted",
+            204 => "No Content",
+            206 => "Partial Content",
+            301 => "Moved Permanently",
+            302 => "Found",
+            303 => "See Other",
+            304 => "Not Modified",
+            307 => "Temporary Redirect",
+            308 => "Permanent Redirect",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            403 => "Forbidden",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            408 => "Request Timeout",
+            409 => "Conflict",
+            413 => "Payload Too Large",
+            415 => "Unsupported Media Type",
+            429 => "Too Many Requests",
+            500 => "Internal Server Error",
+            501 => "Not Implemented",
+            502 => "Bad Gateway",
+            503 => "Service Unavailable",
+            504 => "Gateway Timeout",
+            _ => return None,
+        };
+        Some(reason)
+    }
 }
 
 impl fmt::Display for StatusCode {
@@ -54,13 +101,61 @@ pub struct Response {
     pub status_message: String,
     /// 响应头部
     pub headers: HashMap<String, String>,
+    /// 非 UTF-8 响应头部值的原始字节（键为小写头部名称）
+    pub raw_header_values: HashMap<String, Vec<u8>>,
     /// 响应体 (原始字节数据)
     pub body: Vec<u8>,
+    /// 响应是否因连接中断而不完整
+    pub truncated: bool,
+    /// chunked 响应结束后发送的 trailer headers
+    pub trailers: HeaderMap,
+    /// TLS 握手协商的 ALPN 协议，明文连接或未协商时为 None
+    pub alpn_protocol: Option<Vec<u8>>,
+    /// 请求各阶段耗时，只有通过客户端发送的请求才有
+    pub(crate) timings: Option<Timings>,
 }
 
 impl Response {
     /// 从原始 HTTP 响应字节流创建 Response 实例
+    ///
+    /// 只解析响应并去除 chunked 传输编码，压缩的响应体和 Content-Encoding 保持不变，
+    /// 需要解压时调用 `decompress_body`
     pub fn from_raw_bytes(raw_response: Vec<u8>) -> Result<Self> {
+        Self::parse_raw_bytes(raw_response, false, DEFAULT_MAX_HEADER_COUNT)
+    }
+
+    /// 从原始 HTTP 响应字节流创建 Response 实例，头部行数超过 `max_header_count` 时返回错误
+    pub fn from_raw_bytes_with_max_headers(raw_response: Vec<u8>, max_header_count: usize) -> Result<Self> {
+        Self::parse_raw_bytes(raw_response, false, max_header_count)
+    }
+
+    /// 将没有状态行和头部的 HTTP/0.9 响应创建为 Response 实例
+    ///
+    /// 整个响应作为响应体，状态码为 200，版本为 `HTTP/0.9`
+    pub fn from_http09_bytes(body: Vec<u8>) -> Self {
+        Response {
+            version: "HTTP/0.9".to_string(),
+            status_code: 200,
+            status_message: "OK".to_string(),
+            headers: HashMap::new(),
+            raw_header_values: HashMap::new(),
+            body,
+            truncated: false,
+            trailers: HeaderMap::new(),
+            alpn_protocol: None,
+            timings: None,
+        }
+    }
+
+    /// 从被截断的原始响应创建 Response 实例
+    ///
+    /// 头部必须完整，响应体保留已接收的部分，`truncated` 会被标记为 true
+    pub fn from_partial_bytes(raw_response: Vec<u8>) -> Result<Self> {
+        Self::parse_raw_bytes(raw_response, true, DEFAULT_MAX_HEADER_COUNT)
+    }
+
+    /// 解析原始响应，不解压响应体
+    pub(crate) fn parse_raw_bytes(raw_response: Vec<u8>, truncated: bool, max_header_count: usize) -> Result<Self> {
         // 首先找到头部结束的位置（\r\n\r\n）
         let header_end = raw_response.windows(4).position(|w| w == b"\r\n\r\n")
             .ok_or(Error::Response("Invalid HTTP response format".to_string()))?;
@@ -69,11 +164,14 @@ impl Response {
         let header_bytes = &raw_response[..header_end];
         let body_bytes = &raw_response[header_end + 4..];
 
-        // 解析头部
-        let header_str = String::from_utf8_lossy(header_bytes);
-        let mut lines = header_str.lines();
+        // 逐行解析头部，只接受 CRLF 作为行结束符，裸 LF/CR 会在逐行校验时被拒绝
+        let mut lines = split_crlf(header_bytes).into_iter();
 
         let status_line = lines.next().ok_or(Error::Response("Empty response".to_string()))?;
+        if status_line.iter().any(|b| matches!(b, b'\r' | b'\n' | b'\0')) {
+            return Err(Error::Response("Invalid character in status line".to_string()));
+        }
+        let status_line = String::from_utf8_lossy(status_line);
 
         // 解析状态行: "HTTP/1.1 200 OK"
         let status_parts: Vec<&str> = status_line.split_whitespace().collect();
@@ -86,51 +184,141 @@ impl Response {
         let status_message = status_parts[2..].join(" ");
 
         // 解析头部
-        let mut headers = HashMap::new();
-        for line in lines {
+        let mut headers: HashMap<String, String> = HashMap::new();
+        let mut raw_header_values = HashMap::new();
+        for (index, line) in lines.enumerate() {
             if line.is_empty() {
                 break;
             }
+            // 限制头部行数，防止服务器发送大量细小头部
+            if index >= max_header_count {
+                return Err(Error::http_parse("too many response headers"));
+            }
+            Self::validate_header_line(line)?;
             // 解析头部行: "Content-Type: text/html"
-            if let Some((key, value)) = line.split_once(':') {
-                let key = key.trim().to_lowercase();
-                let value = value.trim().to_string();
-                headers.insert(key, value);
+            if let Some(colon) = line.iter().position(|&b| b == b':') {
+                // 头部名称必须是 ASCII，避免有损解码导致不同名称被合并
+                let name = &line[..colon];
+                if !name.is_ascii() {
+                    return Err(Error::Response(format!(
+                        "Invalid non-ASCII header name: {:?}",
+                        String::from_utf8_lossy(name)
+                    )));
+                }
+                let key = String::from_utf8_lossy(name).trim().to_lowercase();
+                let raw_value = line[colon + 1..].trim_ascii();
+                // 多个取值不同的 Content-Length 可被用于响应走私，相同的重复值可以接受
+                if key == "content-length" && headers.get(&key).is_some_and(|existing| existing.as_bytes() != raw_value) {
+                    return Err(Error::http_parse("conflicting Content-Length headers"));
+                }
+                match std::str::from_utf8(raw_value) {
+                    Ok(value) => {
+                        raw_header_values.remove(&key);
+                        headers.insert(key, value.to_string());
+                    }
+                    Err(_) => {
+                        // 非 UTF-8 的头部值保留原始字节，字符串形式为有损解码
+                        headers.insert(key.clone(), String::from_utf8_lossy(raw_value).into_owned());
+                        raw_header_values.insert(key, raw_value.to_vec());
+                    }
+                }
             }
         }
 
-        // 处理响应体：先处理 chunked，然后处理压缩
-        let processed_body = Self::process_response_body(&headers, body_bytes)?;
+        // 同时存在 chunked 和 Content-Length 时以 chunked 为准（RFC 7230 3.3.3），
+        // 移除 Content-Length 以免调用方按错误的长度处理响应体
+        if ChunkedParser::is_chunked(&headers) {
+            headers.remove("content-length");
+            raw_header_values.remove("content-length");
+        }
+
+        // 声明了 Content-Length 时只取声明长度的响应体（包括 `Content-Length: 0`），
+        // 之后的数据属于下一个响应
+        let declared_length = headers.get("content-length").and_then(|value| value.trim().parse::<usize>().ok());
+        let body_bytes = match declared_length {
+            Some(length) => &body_bytes[..length.min(body_bytes.len())],
+            None => body_bytes,
+        };
+
+        // 处理响应体的 chunked 传输编码
+        let (processed_body, trailers) = Self::process_response_body(status_code, &headers, body_bytes, truncated)?;
 
         Ok(Response {
             version,
             status_code,
             status_message,
             headers,
+            raw_header_values,
             body: processed_body,
+            truncated,
+            trailers,
+            alpn_protocol: None,
+            timings: None,
         })
     }
 
-    /// 处理响应体：支持 chunked 传输和压缩
-    fn process_response_body(headers: &HashMap<String, String>, body_bytes: &[u8]) -> Result<Vec<u8>> {
+    /// 校验单个头部行，防止响应拆分和头部注入
+    fn validate_header_line(line: &[u8]) -> Result<()> {
+        if line.starts_with(b" ") || line.starts_with(b"\t") {
+            return Err(Error::Response("Obsolete header line folding is not allowed".to_string()));
+        }
+
+        if line.iter().any(|b| matches!(b, b'\r' | b'\n' | b'\0')) {
+            return Err(Error::Response(format!(
+                "Invalid character in header line: {:?}",
+                String::from_utf8_lossy(line)
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// 处理响应体的 chunked 传输编码
+    /// 返回处理后的响应体以及 chunked trailer headers，1xx、204、304 响应没有响应体
+    fn process_response_body(
+        status_code: u16,
+        headers: &HashMap<String, String>,
+        body_bytes: &[u8],
+        truncated: bool,
+    ) -> Result<(Vec<u8>, HeaderMap)> {
         let mut processed_data = body_bytes.to_vec();
+        let mut trailers = HeaderMap::new();
+
+        if (100..200).contains(&status_code) || status_code == 204 || status_code == 304 {
+            return Ok((processed_data, trailers));
+        }
 
-        // 第一步：处理 chunked 传输编码
         if ChunkedParser::is_chunked(headers) {
-            processed_data = ChunkedParser::parse(&processed_data)?;
+            if truncated {
+                processed_data = ChunkedParser::parse_partial(&processed_data)?;
+            } else {
+                (processed_data, trailers) = ChunkedParser::parse_with_trailers(&processed_data)?;
+            }
         }
 
-        // 第二步：处理内容压缩
-        let content_encoding = headers.get("content-encoding")
-            .map(|v| v.as_str())
-            .unwrap_or("");
+        Ok((processed_data, trailers))
+    }
 
+    /// 按 Content-Encoding 解压响应体，`decoders` 中关闭的编码保持压缩
+    ///
+    /// 解压后移除描述压缩数据的 Content-Encoding 和 Content-Length，使头部与响应体一致；
+    /// 1xx、204、304 响应和空响应体（如 HEAD 响应）不做解压
+    pub fn decompress_body(&mut self, decoders: Decoders) -> Result<()> {
+        if self.body.is_empty() || (100..200).contains(&self.status_code) || matches!(self.status_code, 204 | 304) {
+            return Ok(());
+        }
+        let content_encoding = self.get_header("content-encoding").map_or("", |v| v.as_str());
         let compression = Compression::from_content_encoding(content_encoding);
-        if compression != Compression::None {
-            processed_data = decompress(&processed_data, compression)?;
+        if compression == Compression::None || !decoders.decodes(compression) {
+            return Ok(());
         }
 
-        Ok(processed_data)
+        self.body = decompress(&self.body, compression)?;
+        for key in ["content-encoding", "content-length"] {
+            self.headers.remove(key);
+            self.raw_header_values.remove(key);
+        }
+        Ok(())
     }
 
     /// 从原始 HTTP 响应字符串创建 Response 实例（向后兼容）
@@ -138,11 +326,165 @@ impl Response {
         Self::from_raw_bytes(raw_response.into_bytes())
     }
 
+    /// 响应未声明可识别的 Content-Encoding 时，根据响应体开头的魔数检测并解压 gzip/zlib 数据
+    ///
+    /// 用于压缩了响应体却没有（或错误）标注 Content-Encoding 的服务器。解压后与 `decompress_body`
+    /// 一样移除 Content-Encoding 和 Content-Length；检测不到压缩格式或解压失败时保留原始响应体，
+    /// 返回是否进行了解压
+    pub fn decompress_sniffed(&mut self) -> bool {
+        let declared = self.headers.get("content-encoding").map(|v| v.as_str()).unwrap_or("");
+        if Compression::from_content_encoding(declared) != Compression::None {
+            return false;
+        }
+
+        match Compression::sniff(&self.body) {
+            Compression::None => false,
+            compression => match decompress(&self.body, compression) {
+                Ok(body) => {
+                    self.body = body;
+                    for key in ["content-encoding", "content-length"] {
+                        self.headers.remove(key);
+                        self.raw_header_values.remove(key);
+                    }
+                    true
+                }
+                Err(_) => false,
+            },
+        }
+    }
+
     /// 获取指定头部的值
     pub fn get_header(&self, key: &str) -> Option<&String> {
         self.headers.get(&key.to_lowercase())
     }
 
+    /// 获取指定头部值的原始字节
+    ///
+    /// 非 UTF-8 的头部值返回服务器发送的原始字节，而不是有损解码后的字符串
+    pub fn get_header_bytes(&self, key: &str) -> Option<&[u8]> {
+        let key = key.to_lowercase();
+        self.raw_header_values
+            .get(&key)
+            .map(|value| value.as_slice())
+            .or_else(|| self.headers.get(&key).map(|value| value.as_bytes()))
+    }
+
+    /// 获取按名称排序的响应头，名称转换为首字母大写的规范格式
+    ///
+    /// 输出顺序稳定，适用于快照测试、日志对比和复现请求
+    pub fn headers_sorted(&self) -> Vec<(String, String)> {
+        let mut headers: Vec<(&String, &String)> = self.headers.iter().collect();
+        headers.sort();
+        headers
+            .into_iter()
+            .map(|(key, value)| (capitalize_header(key), value.clone()))
+            .collect()
+    }
+
+    /// 获取所有响应头名称，按名称排序并转换为首字母大写的规范格式
+    pub fn header_names(&self) -> Vec<String> {
+        let mut names: Vec<&String> = self.headers.keys().collect();
+        names.sort();
+        names.into_iter().map(|name| capitalize_header(name)).collect()
+    }
+
+    /// 获取响应头的 HeaderMap 表示
+    /// 非 UTF-8 的头部值以原始字节保存，可通过 `HeaderMap::get_bytes` 获取
+    pub fn header_map(&self) -> HeaderMap {
+        let mut header_map = HeaderMap::new();
+        for (key, value) in &self.headers {
+            let _ = match self.raw_header_values.get(key) {
+                Some(raw) => header_map.insert_bytes(key.clone(), raw.clone()),
+                None => header_map.insert(key.clone(), value.clone()),
+            };
+        }
+        header_map
+    }
+
+    /// 将响应拆分为状态码、HTTP 版本、状态消息、响应头和响应体
+    ///
+    /// trailer 和截断标记不包含在拆分结果中
+    pub fn into_parts(self) -> (StatusCode, String, String, HeaderMap, Vec<u8>) {
+        let headers = self.header_map();
+        (self.status(), self.version, self.status_message, headers, self.body)
+    }
+
+    /// 由状态码、HTTP 版本、状态消息、响应头和响应体组装响应，不重新解析字节流
+    ///
+    /// 状态消息按传入的原样保存，可用 `StatusCode::canonical_reason` 获取标准原因短语
+    pub fn from_parts(
+        status: StatusCode,
+        version: String,
+        status_message: String,
+        headers: HeaderMap,
+        body: Vec<u8>,
+    ) -> Self {
+        let mut header_values = HashMap::new();
+        let mut raw_header_values = HashMap::new();
+        for (key, value) in headers.iter() {
+            if let Some(raw) = headers.get_bytes(key).filter(|raw| *raw != value.as_bytes()) {
+                raw_header_values.insert(key.clone(), raw.to_vec());
+            }
+            header_values.insert(key.clone(), value.clone());
+        }
+
+        Response {
+            version,
+            status_code: status.code,
+            status_message,
+            headers: header_values,
+            raw_header_values,
+            body,
+            truncated: false,
+            trailers: HeaderMap::new(),
+            alpn_protocol: None,
+            timings: None,
+        }
+    }
+
+    /// 获取请求各阶段的耗时（DNS、连接、TLS 握手、首字节、总计）
+    ///
+    /// 只有通过 `HttpClient` 发送的请求才有，直接解析原始字节得到的响应返回 None
+    pub fn timings(&self) -> Option<Timings> {
+        self.timings
+    }
+
+    /// 获取 chunked 响应的 trailer headers
+    pub fn trailers(&self) -> &HeaderMap {
+        &self.trailers
+    }
+
+    /// 获取 `Trailer` 响应头预告的 trailer 名称（小写）
+    pub fn announced_trailers(&self) -> Vec<String> {
+        self.get_header("trailer")
+            .map(|value| {
+                value
+                    .split(',')
+                    .map(|name| name.trim().to_ascii_lowercase())
+                    .filter(|name| !name.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// 获取已预告但没有收到的 trailer 名称（小写）
+    pub fn missing_trailers(&self) -> Vec<String> {
+        self.announced_trailers()
+            .into_iter()
+            .filter(|name| !self.trailers.contains_key(name))
+            .collect()
+    }
+
+    /// 获取解析后的 HTTP 版本，无法识别的版本回退为 HTTP/1.1
+    pub fn http_version(&self) -> Version {
+        Version::from(self.version.as_str())
+    }
+
+    /// 检查响应是否完整接收
+    pub fn is_complete(&self) -> bool {
+        !self.truncated
+    }
+
     /// 检查是否为成功的响应 (状态码 200-299)
     pub fn is_success(&self) -> bool {
         self.status_code >= 200 && self.status_code < 300
@@ -163,6 +505,22 @@ impl Response {
         self.status_code >= 500 && self.status_code < 600
     }
 
+    /// 状态码为 4xx 或 5xx 时返回 `Error::Http`，否则原样返回响应（兼容 reqwest::Response::error_for_status()）
+    pub fn error_for_status(self) -> Result<Self> {
+        if self.is_client_error() || self.is_server_error() {
+            return Err(Error::http_status(self.status_code, self.status_message));
+        }
+        Ok(self)
+    }
+
+    /// 根据 HTTP 版本和 Connection 响应头判断服务器是否保持连接
+    ///
+    /// HTTP/1.1 默认保持连接，除非包含 `Connection: close`；HTTP/1.0 仅在包含
+    /// `Connection: keep-alive` 时保持连接
+    pub fn is_keep_alive(&self) -> bool {
+        is_keep_alive(&self.version, self.get_header("connection").map(String::as_str))
+    }
+
     /// 获取响应的完整状态行
     pub fn status_line(&self) -> String {
         format!("{} {} {}", self.version, self.status_code, self.status_message)
@@ -176,18 +534,90 @@ impl Response {
     }
 
     /// 获取响应体文本（兼容 reqwest::Response::text()）
+    ///
+    /// 编码取自 BOM 或 Content-Type 的 charset（默认 UTF-8），解码结果不包含 BOM；
+    /// 响应体包含该编码下的无效字节时返回错误，需要容错解码时使用 `text_utf8`
     pub async fn text(self) -> Result<String> {
-        String::from_utf8(self.body).map_err(|e| Error::other(format!("Invalid UTF-8: {}", e)))
+        decode_text(&self.body, self.charset().as_deref())
+    }
+
+    /// 将响应体转码为 UTF-8 字符串，结果保证是有效的 UTF-8，不会返回错误
+    ///
+    /// 编码依次取自 BOM、Content-Type 的 charset 和内容检测，支持 WHATWG Encoding 标准中的全部编码
+    /// （如 Shift_JIS、GBK、windows-1252）；未声明编码且不是有效 UTF-8 时按 windows-1252 解码，
+    /// 无法识别的字符集按 UTF-8 解码，无法解码的字节替换为 U+FFFD
+    pub fn text_utf8(&self) -> String {
+        transcode_to_utf8(&self.body, self.charset().as_deref())
+    }
+
+    /// Content-Type 中声明的字符集（小写），未声明时为 None
+    pub fn charset(&self) -> Option<String> {
+        split_header_params(self.content_type()?)
+            .into_iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("charset"))
+            .map(|(_, value)| unquote(&value).trim().to_ascii_lowercase())
+    }
+
+    /// 将响应体反序列化为 JSON（兼容 reqwest::Response::json()）
+    ///
+    /// 按 `text()` 的规则根据 BOM 和 charset 解码，失败时返回 `Error::Serialization`
+    pub async fn json<T: serde::de::DeserializeOwned>(self) -> Result<T> {
+        parse_json(&self.body, self.charset().as_deref())
+    }
+
+    /// 将响应体解析为无类型的 JSON 值
+    ///
+    /// 与 `json` 的解码规则相同，解析失败时返回 `Error::Serialization`，错误信息包含出错位置的字节偏移
+    pub fn json_value(self) -> Result<serde_json::Value> {
+        parse_json(&self.body, self.charset().as_deref())
     }
 
     /// 获取响应体的字节流（兼容 reqwest::Response::bytes_stream()）
-    pub fn bytes_stream(self) -> impl futures_util::Stream<Item = Result<Vec<u8>>> {
+    ///
+    /// 响应体已完整接收，各分块是同一块内存的切片，不会复制响应体
+    pub fn bytes_stream(self) -> impl futures_util::Stream<Item = Result<Bytes>> {
         use futures_util::stream;
 
-        // 将已有的响应体数据转换为字节流
-        let chunks = self.body.chunks(8192).map(|chunk| Ok(chunk.to_vec())).collect::<Vec<_>>();
+        let body = Bytes::from(self.body);
+        let starts = (0..body.len()).step_by(8192);
+        stream::iter(starts.map(move |start| Ok(body.slice(start..(start + 8192).min(body.len())))))
+    }
+
+    /// 按行获取响应体（适用于 Server-Sent Events 和 NDJSON）
+    ///
+    /// 每行以 `\n` 结尾，返回的行不包含行尾的 `\n` 或 `\r\n`；末尾没有换行的最后一行也会返回。
+    /// 响应体在调用前已完整接收到内存中，逐行返回不会减少内存占用
+    pub fn lines(self) -> impl futures_util::Stream<Item = Result<String>> {
+        split_lines(self.bytes_stream())
+    }
 
-        stream::iter(chunks)
+    /// 逐个反序列化响应体中的 JSON 值，适用于大型 JSON 数组和 NDJSON
+    ///
+    /// Content-Type 为 NDJSON（`application/x-ndjson`、`application/jsonl` 等）或响应体不以 `[` 开头时，
+    /// 每个非空行解析为一个值；否则按顶层 JSON 数组逐个返回元素。
+    /// 响应体在调用前已完整接收到内存中，逐个解析只避免一次性构建所有值
+    pub fn json_stream<T: serde::de::DeserializeOwned>(self) -> impl futures_util::Stream<Item = Result<T>> {
+        use futures_util::{future, StreamExt};
+
+        let ndjson = self.content_type().is_some_and(|content_type| {
+            let mime = content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+            mime.ends_with("ndjson") || mime.ends_with("jsonl") || mime.ends_with("json-seq")
+        });
+        let array = !ndjson && self.body.iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'[');
+
+        let values = if array {
+            split_json_array(self.bytes_stream()).left_stream()
+        } else {
+            split_lines(self.bytes_stream())
+                .filter(|line| future::ready(!matches!(line, Ok(line) if line.trim().is_empty())))
+                .map(|line| line.map(String::into_bytes))
+                .right_stream()
+        };
+        values.map(|value| {
+            value.and_then(|value| {
+                serde_json::from_slice(&value).map_err(|e| Error::serialization(format!("Invalid JSON value: {}", e)))
+            })
+        })
     }
 
     /// 获取内容长度
@@ -196,40 +626,472 @@ impl Response {
             .and_then(|s| s.parse().ok())
     }
 
+    /// 获取便于阅读的中文响应摘要
+    pub fn summary(&self) -> String {
+        let status = if self.is_success() {
+            "成功"
+        } else if self.is_client_error() {
+            "客户端错误"
+        } else if self.is_server_error() {
+            "服务器错误"
+        } else if self.is_redirect() {
+            "重定向"
+        } else {
+            "未知"
+        };
+
+        format!(
+            "HTTP响应: {}\n状态: {}\n内容长度: {} 字节\n内容类型: {}\n响应体长度: {} 字节",
+            self.status_line(),
+            status,
+            self.content_length().unwrap_or(0),
+            self.content_type().map(|s| s.as_str()).unwrap_or("未知"),
+            self.body.len()
+        )
+    }
+
     /// 获取内容类型
     pub fn content_type(&self) -> Option<&String> {
         self.get_header("content-type")
     }
 
-    /// 获取响应的原始字符串表示
+    /// 解析 Content-Range 响应头，返回 (start, end, total)
+    /// 总长度未知（`*`）时 total 为 None
+    pub fn content_range(&self) -> Option<(u64, u64, Option<u64>)> {
+        let value = self.get_header("content-range")?;
+        let range = value.trim().strip_prefix("bytes")?.trim_start();
+        let (span, total) = range.split_once('/')?;
+        let (start, end) = span.trim().split_once('-')?;
+
+        let start = start.trim().parse().ok()?;
+        let end = end.trim().parse().ok()?;
+        let total = match total.trim() {
+            "*" => None,
+            total => Some(total.parse().ok()?),
+        };
+
+        Some((start, end, total))
+    }
+
+    /// 解析 Retry-After 响应头（常见于 429 和 503 响应）
+    ///
+    /// 支持秒数和 HTTP 日期两种格式，日期已过去时返回零时长
+    pub fn retry_after(&self) -> Option<Duration> {
+        let value = self.get_header("retry-after")?.trim();
+        if let Ok(seconds) = value.parse::<u64>() {
+            return Some(Duration::from_secs(seconds));
+        }
+
+        let date = parse_http_date(value)?;
+        Some(date.duration_since(SystemTime::now()).unwrap_or(Duration::ZERO))
+    }
+
+    /// 从 Content-Disposition 响应头获取服务器建议的文件名
+    ///
+    /// 优先使用 RFC 5987 的 `filename*=UTF-8''...` 形式（百分号编码，支持 UTF-8 和 ISO-8859-1），
+    /// 其次使用 `filename=`，带引号的值会去除引号和转义
+    pub fn content_disposition_filename(&self) -> Option<String> {
+        let params = split_header_params(self.get_header("content-disposition")?);
+
+        let extended = params
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("filename*"))
+            .and_then(|(_, value)| decode_ext_value(value));
+        extended.or_else(|| {
+            params
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case("filename"))
+                .map(|(_, value)| unquote(value))
+        })
+    }
+
+    /// 解析 Last-Modified 响应头
+    ///
+    /// 支持 IMF-fixdate 以及过时的 RFC 850 和 asctime 格式，缺失或无法解析时返回 None
+    pub fn last_modified(&self) -> Option<SystemTime> {
+        self.date_header("last-modified")
+    }
+
+    /// 解析 Date 响应头
+    pub fn date(&self) -> Option<SystemTime> {
+        self.date_header("date")
+    }
+
+    /// 解析 Expires 响应头，无效日期（如 `0`）返回 None
+    pub fn expires(&self) -> Option<SystemTime> {
+        self.date_header("expires")
+    }
+
+    /// 将指定响应头解析为 HTTP 日期
+    fn date_header(&self, key: &str) -> Option<SystemTime> {
+        parse_http_date(self.get_header(key)?.trim())
+    }
+
+    /// 序列化为 HTTP 线路格式的字节，用于录制和回放
+    ///
+    /// 响应体按原始字节写入，非 UTF-8 的头部值使用原始字节；chunked 响应写成单个分块并附带 trailer。
+    /// `from_raw_bytes` 解析结果与原响应相同
+    pub fn to_raw_bytes(&self) -> Vec<u8> {
+        let mut raw = self.raw_head();
+        if !ChunkedParser::is_chunked(&self.headers) {
+            raw.extend_from_slice(&self.body);
+            return raw;
+        }
+
+        if !self.body.is_empty() {
+            raw.extend_from_slice(format!("{:x}\r\n", self.body.len()).as_bytes());
+            raw.extend_from_slice(&self.body);
+            raw.extend_from_slice(b"\r\n");
+        }
+        raw.extend_from_slice(b"0\r\n");
+        for (key, value) in self.trailers.iter() {
+            raw.extend_from_slice(format!("{}: {}\r\n", capitalize_header(key), value).as_bytes());
+        }
+        raw.extend_from_slice(b"\r\n");
+        raw
+    }
+
+    /// 获取响应的原始字符串表示，内容与 `to_raw_bytes` 相同（chunked 响应重新分块）
+    ///
+    /// 状态行、头部和响应体都是有效 UTF-8 时与线路格式逐字节一致，否则有损转换；
+    /// 二进制响应体（如图片）需要保留原始字节时使用 `to_raw_bytes`
     pub fn to_raw_string(&self) -> String {
-        let mut raw = format!("{} {} {}\r\n", self.version, self.status_code, self.status_message);
+        String::from_utf8_lossy(&self.to_raw_bytes()).into_owned()
+    }
 
+    /// 以字节形式写出状态行和头部（以空行结尾），非 UTF-8 的头部值使用原始字节
+    fn raw_head(&self) -> Vec<u8> {
+        let mut raw = format!("{} {} {}\r\n", self.version, self.status_code, self.status_message).into_bytes();
         for (key, value) in &self.headers {
-            raw.push_str(&format!("{}: {}\r\n", capitalize_header(key), value));
+            raw.extend_from_slice(capitalize_header(key).as_bytes());
+            raw.extend_from_slice(b": ");
+            raw.extend_from_slice(self.raw_header_values.get(key).map_or(value.as_bytes(), Vec::as_slice));
+            raw.extend_from_slice(b"\r\n");
         }
+        raw.extend_from_slice(b"\r\n");
+        raw
+    }
+}
 
-        raw.push_str("\r\n");
-        raw.push_str(&String::from_utf8_lossy(&self.body));
+/// 将 `type; name=value; ...` 形式的头部值拆分为参数列表，引号内的 `;` 不作为分隔符
+fn split_header_params(value: &str) -> Vec<(String, String)> {
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut escaped = false;
+    for c in value.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            ';' if !in_quotes => {
+                segments.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    segments.push(current);
 
-        raw
+    segments
+        .iter()
+        .filter_map(|segment| segment.split_once('='))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect()
+}
+
+/// 去除带引号字符串的引号和反斜杠转义，未加引号的值原样返回
+fn unquote(value: &str) -> String {
+    let Some(inner) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) else {
+        return value.to_string();
+    };
+    let mut result = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => result.extend(chars.next()),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+/// 解码 RFC 5987 扩展参数值（`charset'language'percent-encoded`）
+fn decode_ext_value(value: &str) -> Option<String> {
+    let mut parts = value.splitn(3, '\'');
+    let charset = parts.next()?;
+    let _language = parts.next()?;
+    let bytes = urlencoding::decode_binary(parts.next()?.as_bytes()).into_owned();
+
+    if charset.eq_ignore_ascii_case("UTF-8") {
+        String::from_utf8(bytes).ok()
+    } else if charset.eq_ignore_ascii_case("ISO-8859-1") {
+        Some(bytes.into_iter().map(char::from).collect())
+    } else {
+        None
     }
 }
 
+/// 根据 HTTP 版本和 Connection 头部值判断连接是否保持，`close` 优先于 `keep-alive`
+pub(crate) fn is_keep_alive(version: &str, connection: Option<&str>) -> bool {
+    let has_token = |token: &str| {
+        connection.is_some_and(|value| value.split(',').any(|item| item.trim().eq_ignore_ascii_case(token)))
+    };
+    if has_token("close") {
+        return false;
+    }
+    version.eq_ignore_ascii_case("HTTP/1.1") || has_token("keep-alive")
+}
+
+/// 将分块到达的字节流按行切分，跨分块的不完整行会缓存到后续分块到达后再返回
+pub fn split_lines<S, B>(chunks: S) -> impl futures_util::Stream<Item = Result<String>>
+where
+    S: futures_util::Stream<Item = Result<B>> + Unpin,
+    B: AsRef<[u8]>,
+{
+    use futures_util::{stream, StreamExt};
+
+    stream::unfold((chunks, Vec::new(), false), |(mut chunks, mut buffer, mut finished)| async move {
+        loop {
+            if let Some(end) = buffer.iter().position(|&b| b == b'\n') {
+                let rest = buffer.split_off(end + 1);
+                let line = std::mem::replace(&mut buffer, rest);
+                return Some((decode_line(line), (chunks, buffer, finished)));
+            }
+            if finished {
+                // 最后一行没有换行结尾
+                if buffer.is_empty() {
+                    return None;
+                }
+                let line = std::mem::take(&mut buffer);
+                return Some((decode_line(line), (chunks, buffer, finished)));
+            }
+            match chunks.next().await {
+                Some(Ok(chunk)) => buffer.extend_from_slice(chunk.as_ref()),
+                Some(Err(e)) => {
+                    finished = true;
+                    buffer.clear();
+                    return Some((Err(e), (chunks, buffer, finished)));
+                }
+                None => finished = true,
+            }
+        }
+    })
+}
+
+/// 将分块到达的顶层 JSON 数组切分为各个元素的原始字节，跨分块的元素会缓存到完整后再返回
+pub fn split_json_array<S, B>(chunks: S) -> impl futures_util::Stream<Item = Result<Vec<u8>>>
+where
+    S: futures_util::Stream<Item = Result<B>> + Unpin,
+    B: AsRef<[u8]>,
+{
+    use futures_util::{stream, StreamExt};
+    use std::collections::VecDeque;
+
+    let state = (chunks, JsonArraySplitter::default(), VecDeque::new(), false);
+    stream::unfold(state, |(mut chunks, mut splitter, mut ready, mut finished)| async move {
+        loop {
+            if let Some(element) = ready.pop_front() {
+                return Some((Ok(element), (chunks, splitter, ready, finished)));
+            }
+            if finished {
+                return None;
+            }
+            let error = match chunks.next().await {
+                Some(Ok(chunk)) => chunk.as_ref().iter().try_for_each(|&byte| {
+                    splitter.push(byte).map(|element| ready.extend(element))
+                }).err(),
+                Some(Err(e)) => Some(e),
+                None if splitter.is_closed() => {
+                    finished = true;
+                    None
+                }
+                None => Some(Error::serialization("unexpected end of JSON array")),
+            };
+            if let Some(e) = error {
+                finished = true;
+                ready.clear();
+                return Some((Err(e), (chunks, splitter, ready, finished)));
+            }
+        }
+    })
+}
+
+/// 顶层 JSON 数组的逐字节扫描状态，只缓存当前元素
+#[derive(Default)]
+struct JsonArraySplitter {
+    opened: bool,
+    closed: bool,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    element: Vec<u8>,
+}
+
+impl JsonArraySplitter {
+    fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// 处理一个字节，遇到顶层的 `,` 或 `]` 时返回已完成的元素
+    fn push(&mut self, byte: u8) -> Result<Option<Vec<u8>>> {
+        if !self.opened || self.closed {
+            return match byte {
+                _ if byte.is_ascii_whitespace() => Ok(None),
+                b'[' if !self.opened => {
+                    self.opened = true;
+                    Ok(None)
+                }
+                _ => Err(Error::serialization("unexpected data outside JSON array")),
+            };
+        }
+
+        if self.in_string {
+            if self.escaped {
+                self.escaped = false;
+            } else if byte == b'\\' {
+                self.escaped = true;
+            } else if byte == b'"' {
+                self.in_string = false;
+            }
+            self.element.push(byte);
+            return Ok(None);
+        }
+
+        match byte {
+            b',' | b']' if self.depth == 0 => {
+                self.closed = byte == b']';
+                let element = std::mem::take(&mut self.element);
+                if !element.iter().all(u8::is_ascii_whitespace) {
+                    return Ok(Some(element));
+                }
+                if byte == b',' {
+                    return Err(Error::serialization("empty element in JSON array"));
+                }
+                return Ok(None);
+            }
+            b'"' => self.in_string = true,
+            b'[' | b'{' => self.depth += 1,
+            b']' | b'}' => self.depth = self.depth.saturating_sub(1),
+            _ => {}
+        }
+        self.element.push(byte);
+        Ok(None)
+    }
+}
+
+/// 去掉行尾的 `\n` 或 `\r\n` 并按 UTF-8 解码
+fn decode_line(mut line: Vec<u8>) -> Result<String> {
+    if line.ends_with(b"\n") {
+        line.pop();
+        if line.ends_with(b"\r") {
+            line.pop();
+        }
+    }
+    String::from_utf8(line).map_err(|e| Error::other(format!("Invalid UTF-8 in line: {}", e)))
+}
+
+/// 输出状态行和关键头部，每行一个 `key: value`，便于日志检索
 impl fmt::Display for Response {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "HTTP响应: {} {} {}\n状态: {}\n内容长度: {} 字节\n内容类型: {}\n响应体长度: {} 字节",
-            self.version,
-            self.status_code,
-            self.status_message,
-            if self.is_success() { "成功" } else if self.is_client_error() { "客户端错误" } else if self.is_server_error() { "服务器错误" } else if self.is_redirect() { "重定向" } else { "未知" },
-            self.content_length().unwrap_or(0),
-            self.content_type().unwrap_or(&"未知".to_string()),
-            self.body.len()
-        )
+        write!(f, "{}", self.status_line())?;
+        if let Some(content_type) = self.content_type() {
+            write!(f, "\ncontent-type: {}", content_type)?;
+        }
+        if let Some(content_length) = self.content_length() {
+            write!(f, "\ncontent-length: {}", content_length)?;
+        }
+        write!(f, "\nbody-length: {}", self.body.len())
+    }
+}
+
+/// 从任意异步读取端读取一个完整的原始 HTTP 响应
+///
+/// 先读取响应头，再按 Content-Length、chunked 编码或连接关闭确定响应体结束位置；
+/// `method` 为 HEAD 时不读取响应体。返回的字节可交给 `Response::from_raw_bytes` 解析
+pub async fn read_response<R: AsyncRead + Unpin>(reader: &mut R, method: &Method) -> Result<Vec<u8>> {
+    let (response, _, _) = crate::connection::connection::read_response(
+        reader,
+        &mut Vec::new(),
+        *method == Method::HEAD,
+        false,
+        false,
+        crate::connection::DEFAULT_MAX_HEADER_SIZE,
+        None,
+    )
+    .await?;
+    Ok(response)
+}
+
+/// 将 serde_json 报告的行号和列号（均从 1 开始）换算为字节偏移
+fn byte_offset(data: &[u8], line: usize, column: usize) -> usize {
+    let line_start = data
+        .iter()
+        .enumerate()
+        .filter(|(_, byte)| **byte == b'\n')
+        .nth(line.saturating_sub(2))
+        .map(|(index, _)| index + 1)
+        .filter(|_| line > 1)
+        .unwrap_or(0);
+    (line_start + column.saturating_sub(1)).min(data.len())
+}
+
+/// 按 CRLF 拆分字节数据
+fn split_crlf(data: &[u8]) -> Vec<&[u8]> {
+    let mut lines = Vec::new();
+    let mut remaining = data;
+    while let Some(pos) = remaining.windows(2).position(|w| w == b"\r\n") {
+        lines.push(&remaining[..pos]);
+        remaining = &remaining[pos + 2..];
+    }
+    lines.push(remaining);
+    lines
+}
+
+/// 确定响应体的编码和 BOM 长度：BOM 优先于声明的字符集，无法识别的字符集按 UTF-8 处理
+fn body_encoding(body: &[u8], charset: Option<&str>) -> (&'static Encoding, usize) {
+    if let Some(bom) = Encoding::for_bom(body) {
+        return bom;
     }
+    let encoding = charset.and_then(|label| Encoding::for_label(label.as_bytes())).unwrap_or(UTF_8);
+    (encoding, 0)
+}
+
+/// 按 BOM 和声明的字符集将响应体解码为字符串，遇到无效字节时返回错误
+fn decode_text(body: &[u8], charset: Option<&str>) -> Result<String> {
+    let (encoding, bom_len) = body_encoding(body, charset);
+    encoding
+        .decode_without_bom_handling_and_without_replacement(&body[bom_len..])
+        .map(|text| text.into_owned())
+        .ok_or_else(|| Error::other(format!("Invalid {} in response body", encoding.name())))
+}
+
+/// 按 BOM 和声明的字符集解码后解析 JSON，错误信息包含解码后文本中出错位置的字节偏移
+fn parse_json<T: serde::de::DeserializeOwned>(body: &[u8], charset: Option<&str>) -> Result<T> {
+    let (encoding, bom_len) = body_encoding(body, charset);
+    let decoded;
+    let text = if encoding == UTF_8 {
+        &body[bom_len..]
+    } else {
+        decoded = decode_text(body, charset).map_err(|e| Error::serialization(e.to_string()))?;
+        decoded.as_bytes()
+    };
+    serde_json::from_slice(text).map_err(|e| {
+        Error::serialization(format!("Invalid JSON at byte offset {}: {}", byte_offset(text, e.line(), e.column()), e))
+    })
+}
+
+/// 按 BOM、声明的字符集和内容检测将字节转码为 UTF-8，无法解码的字节替换为 U+FFFD
+fn transcode_to_utf8(body: &[u8], charset: Option<&str>) -> String {
+    let (encoding, bom_len) = body_encoding(body, charset);
+    // 未声明编码、没有 BOM 且不是有效 UTF-8 时按 windows-1252 解码
+    let encoding = if charset.is_none() && bom_len == 0 && std::str::from_utf8(body).is_err() {
+        WINDOWS_1252
+    } else {
+        encoding
+    };
+    encoding.decode_without_bom_handling(&body[bom_len..]).0.into_owned()
 }
 
 /// 将头部键转换为首字母大写的格式
@@ -247,6 +1109,7 @@ fn capitalize_header(key: &str) -> String {
 }
 
 #[cfg(test)]
+#[allow(clippy::useless_format)]
 mod tests {
     use super::*;
 
@@ -276,6 +1139,368 @@ mod tests {
         assert_eq!(response.status_line(), "HTTP/1.1 404 Not Found");
     }
 
+    #[test]
+    fn test_http_version() {
+        let cases = [
+            ("HTTP/1.0 200 OK", Version::Http1_0),
+            ("HTTP/1.1 200 OK", Version::Http1_1),
+            ("HTTP/2 200 OK", Version::Http2),
+        ];
+
+        for (status_line, expected) in cases {
+            let raw = format!("{}\r\nContent-Length: 0\r\n\r\n", status_line);
+            let response = Response::from_raw_response(raw).unwrap();
+            assert_eq!(response.http_version(), expected);
+        }
+    }
+
+    #[test]
+    fn test_non_utf8_header_value_bytes() {
+        // "café" 的 latin-1 编码
+        let mut raw = b"HTTP/1.1 200 OK\r\nX-Name: caf".to_vec();
+        raw.push(0xE9);
+        raw.extend_from_slice(b"\r\nContent-Type: text/plain\r\n\r\n");
+
+        let response = Response::from_raw_bytes(raw).unwrap();
+        assert_eq!(response.get_header_bytes("X-Name").unwrap(), b"caf\xE9");
+        assert_eq!(response.get_header("x-name").unwrap(), "caf\u{FFFD}");
+        assert_eq!(response.get_header_bytes("content-type").unwrap(), b"text/plain");
+
+        let header_map = response.header_map();
+        assert_eq!(header_map.get_bytes("x-name").unwrap(), b"caf\xE9");
+        assert_eq!(header_map.get_bytes("content-type").unwrap(), b"text/plain");
+    }
+
+    #[test]
+    fn test_reject_non_ascii_header_name() {
+        let mut raw = b"HTTP/1.1 200 OK\r\nX-".to_vec();
+        raw.push(0xFF);
+        raw.extend_from_slice(b": value\r\n\r\n");
+        assert!(Response::from_raw_bytes(raw).is_err());
+    }
+
+    #[test]
+    fn test_content_range() {
+        let raw = "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes 0-1023/2048\r\n\r\n".to_string();
+        let response = Response::from_raw_response(raw).unwrap();
+        assert!(response.is_success());
+        assert_eq!(response.content_range(), Some((0, 1023, Some(2048))));
+
+        let raw = "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes 100-199/*\r\n\r\n".to_string();
+        let response = Response::from_raw_response(raw).unwrap();
+        assert_eq!(response.content_range(), Some((100, 199, None)));
+
+        let raw = "HTTP/1.1 200 OK\r\n\r\n".to_string();
+        let response = Response::from_raw_response(raw).unwrap();
+        assert_eq!(response.content_range(), None);
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let raw = "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 120\r\n\r\n".to_string();
+        let response = Response::from_raw_response(raw).unwrap();
+        assert_eq!(response.retry_after(), Some(Duration::from_secs(120)));
+
+        let date = crate::utils::format_http_date(SystemTime::now() + Duration::from_secs(120));
+        let raw = format!("HTTP/1.1 503 Service Unavailable\r\nRetry-After: {}\r\n\r\n", date);
+        let response = Response::from_raw_response(raw).unwrap();
+        let retry_after = response.retry_after().unwrap();
+        assert!(retry_after > Duration::from_secs(115) && retry_after <= Duration::from_secs(120));
+
+        let raw = "HTTP/1.1 503 Service Unavailable\r\nRetry-After: Sun, 06 Nov 1994 08:49:37 GMT\r\n\r\n".to_string();
+        let response = Response::from_raw_response(raw).unwrap();
+        assert_eq!(response.retry_after(), Some(Duration::ZERO));
+    }
+
+    #[tokio::test]
+    async fn test_json_stream_ndjson() {
+        use futures_util::StreamExt;
+
+        #[derive(serde::Deserialize, Debug, PartialEq)]
+        struct Event {
+            id: u32,
+            name: String,
+        }
+
+        let body = "{\"id\":1,\"name\":\"a\"}\n{\"id\":2,\"name\":\"b\"}\r\n\n{\"id\":3,\"name\":\"c\"}";
+        let raw = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/x-ndjson\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        let events: Vec<Event> = Response::from_raw_response(raw)
+            .unwrap()
+            .json_stream()
+            .map(|event| event.unwrap())
+            .collect()
+            .await;
+        assert_eq!(
+            events,
+            vec![
+                Event { id: 1, name: "a".into() },
+                Event { id: 2, name: "b".into() },
+                Event { id: 3, name: "c".into() },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_split_json_array_across_chunks() {
+        use futures_util::{stream, StreamExt};
+
+        let chunks = stream::iter(vec![
+            Ok(b" [1, {\"a\": [2, \"],\\\"\"]".to_vec()),
+            Ok(b"}, \"x\" ,[]] ".to_vec()),
+        ]);
+        let elements: Vec<String> = split_json_array(chunks)
+            .map(|element| String::from_utf8(element.unwrap()).unwrap())
+            .collect()
+            .await;
+        assert_eq!(elements, vec!["1", " {\"a\": [2, \"],\\\"\"]}", " \"x\" ", "[]"]);
+
+        let values: Vec<Result<u32>> = split_json_array(stream::iter(vec![Ok(b"[1, 2".to_vec())]))
+            .map(|element| element.and_then(|e| serde_json::from_slice(&e).map_err(|e| Error::serialization(e.to_string()))))
+            .collect()
+            .await;
+        assert_eq!(values.len(), 2);
+        assert_eq!(*values[0].as_ref().unwrap(), 1);
+        assert!(values[1].is_err());
+
+        let raw = "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 9\r\n\r\n[[1],[2]]".to_string();
+        let values: Vec<Vec<u32>> = Response::from_raw_response(raw)
+            .unwrap()
+            .json_stream()
+            .map(|value| value.unwrap())
+            .collect()
+            .await;
+        assert_eq!(values, vec![vec![1], vec![2]]);
+    }
+
+    #[tokio::test]
+    async fn test_bytes_stream_chunks() {
+        use futures_util::StreamExt;
+
+        let body = "x".repeat(20000);
+        let raw = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}", body.len(), body);
+        let chunks: Vec<Bytes> = Response::from_raw_response(raw)
+            .unwrap()
+            .bytes_stream()
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+        assert_eq!(chunks.iter().map(Bytes::len).collect::<Vec<_>>(), vec![8192, 8192, 3616]);
+        assert_eq!(chunks.concat(), body.as_bytes());
+    }
+
+    #[tokio::test]
+    async fn test_split_lines_across_chunks() {
+        use futures_util::{stream, StreamExt};
+
+        let chunks = stream::iter(vec![
+            Ok(b"data: first\ndata: sec".to_vec()),
+            Ok(b"ond\r\n\nlast".to_vec()),
+        ]);
+        let lines: Vec<String> = split_lines(chunks).map(|line| line.unwrap()).collect().await;
+        assert_eq!(lines, vec!["data: first", "data: second", "", "last"]);
+
+        let response = Response::from_raw_response(
+            "HTTP/1.1 200 OK\r\nContent-Length: 12\r\n\r\n{\"a\":1}\n{}\n".to_string(),
+        )
+        .unwrap();
+        let lines: Vec<String> = response.lines().map(|line| line.unwrap()).collect().await;
+        assert_eq!(lines, vec!["{\"a\":1}", "{}"]);
+    }
+
+    #[test]
+    fn test_content_disposition_filename() {
+        let response = |disposition: &str| {
+            Response::from_raw_response(format!("HTTP/1.1 200 OK\r\nContent-Disposition: {}\r\n\r\n", disposition))
+                .unwrap()
+        };
+
+        let plain = response(r#"attachment; filename="report; final.zip""#);
+        assert_eq!(plain.content_disposition_filename().as_deref(), Some("report; final.zip"));
+
+        let bare = response("attachment; filename=data.csv");
+        assert_eq!(bare.content_disposition_filename().as_deref(), Some("data.csv"));
+
+        let encoded = response(r#"attachment; filename="fallback.txt"; filename*=UTF-8''%E6%8A%A5%E5%91%8A%20v2.txt"#);
+        assert_eq!(encoded.content_disposition_filename().as_deref(), Some("报告 v2.txt"));
+
+        let latin1 = response("attachment; filename*=iso-8859-1'en'caf%E9.txt");
+        assert_eq!(latin1.content_disposition_filename().as_deref(), Some("café.txt"));
+
+        assert_eq!(response("inline").content_disposition_filename(), None);
+    }
+
+    #[test]
+    fn test_header_names() {
+        let raw = "HTTP/1.1 200 OK\r\nx-request-id: 42\r\nContent-Type: text/plain\r\ncache-control: no-store\r\n\r\n";
+        let response = Response::from_raw_response(raw.to_string()).unwrap();
+        assert_eq!(response.header_names(), vec!["Cache-Control", "Content-Type", "X-Request-Id"]);
+        assert!(response.get_header(&response.header_names()[0]).is_some());
+    }
+
+    #[test]
+    fn test_headers_sorted() {
+        let raw = "HTTP/1.1 200 OK\r\nx-request-id: 42\r\nContent-Type: text/plain\r\nDATE: Sun, 06 Nov 1994 08:49:37 GMT\r\ncache-control: no-store\r\n\r\n";
+        let expected = vec![
+            ("Cache-Control".to_string(), "no-store".to_string()),
+            ("Content-Type".to_string(), "text/plain".to_string()),
+            ("Date".to_string(), "Sun, 06 Nov 1994 08:49:37 GMT".to_string()),
+            ("X-Request-Id".to_string(), "42".to_string()),
+        ];
+
+        // 每次解析都会得到新的 HashMap（随机哈希种子），排序结果应保持一致
+        for _ in 0..5 {
+            let response = Response::from_raw_response(raw.to_string()).unwrap();
+            assert_eq!(response.headers_sorted(), expected);
+        }
+    }
+
+    #[test]
+    fn test_is_keep_alive() {
+        let response = Response::from_raw_response("HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n".to_string()).unwrap();
+        assert!(response.is_keep_alive());
+
+        let response =
+            Response::from_raw_response("HTTP/1.1 200 OK\r\nConnection: Close\r\n\r\n".to_string()).unwrap();
+        assert!(!response.is_keep_alive());
+
+        let response =
+            Response::from_raw_response("HTTP/1.0 200 OK\r\nConnection: Keep-Alive\r\n\r\n".to_string()).unwrap();
+        assert!(response.is_keep_alive());
+
+        let response = Response::from_raw_response("HTTP/1.0 200 OK\r\n\r\n".to_string()).unwrap();
+        assert!(!response.is_keep_alive());
+    }
+
+    #[test]
+    fn test_date_headers() {
+        let raw = "HTTP/1.1 200 OK\r\n\
+            Date: Sun, 06 Nov 1994 08:49:37 GMT\r\n\
+            Last-Modified: Sunday, 06-Nov-94 08:49:37 GMT\r\n\
+            Expires: Sun Nov  6 08:49:37 1994\r\n\r\n"
+            .to_string();
+        let response = Response::from_raw_response(raw).unwrap();
+        let expected = SystemTime::UNIX_EPOCH + Duration::from_secs(784111777);
+        assert_eq!(response.date(), Some(expected));
+        assert_eq!(response.last_modified(), Some(expected));
+        assert_eq!(response.expires(), Some(expected));
+
+        let raw = "HTTP/1.1 200 OK\r\nExpires: 0\r\n\r\n".to_string();
+        let response = Response::from_raw_response(raw).unwrap();
+        assert_eq!(response.expires(), None);
+        assert_eq!(response.last_modified(), None);
+    }
+
+    #[test]
+    fn test_reject_bare_lf_in_header() {
+        let raw = "HTTP/1.1 200 OK\r\nX-Injected: a\nSet-Cookie: evil=1\r\n\r\nbody".to_string();
+        assert!(Response::from_raw_response(raw).is_err());
+
+        let raw = "HTTP/1.1 200 OK\nContent-Type: text/plain\r\n\r\nbody".to_string();
+        assert!(Response::from_raw_response(raw).is_err());
+    }
+
+    #[test]
+    fn test_reject_nul_and_folding_in_header() {
+        let raw = "HTTP/1.1 200 OK\r\nX-Test: a\0b\r\n\r\nbody".to_string();
+        assert!(Response::from_raw_response(raw).is_err());
+
+        let raw = "HTTP/1.1 200 OK\r\nX-Test: a\r\n folded\r\n\r\nbody".to_string();
+        assert!(Response::from_raw_response(raw).is_err());
+    }
+
+    #[test]
+    fn test_partial_chunked_response() {
+        let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n6\r\nHello \r\n6\r\nWo".to_vec();
+        assert!(Response::from_raw_bytes(raw.clone()).is_err());
+
+        let response = Response::from_partial_bytes(raw).unwrap();
+        assert!(!response.is_complete());
+        assert_eq!(response.body, b"Hello Wo");
+    }
+
+    #[test]
+    fn test_text_utf8_transcodes_declared_charset() {
+        let response = |content_type: &str, body: &[u8]| {
+            let mut raw = format!("HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\n\r\n", content_type, body.len())
+                .into_bytes();
+            raw.extend_from_slice(body);
+            Response::from_raw_bytes(raw).unwrap()
+        };
+
+        let latin1 = response("text/html; charset=\"ISO-8859-1\"", b"caf\xe9 \x80");
+        assert_eq!(latin1.charset().as_deref(), Some("iso-8859-1"));
+        assert_eq!(latin1.text_utf8(), "café €");
+
+        // 未声明编码时先看 BOM，再检查是否为有效 UTF-8，否则按 windows-1252 解码
+        assert_eq!(response("text/plain", b"\xff\xfeh\x00i\x00").text_utf8(), "hi");
+        assert_eq!(response("text/plain", "中文".as_bytes()).text_utf8(), "中文");
+        assert_eq!(response("text/plain", b"na\xefve").text_utf8(), "naïve");
+        assert_eq!(response("text/plain; charset=utf-16be", b"\x00h\x00i\xd8").text_utf8(), "hi\u{FFFD}");
+
+        let shift_jis = response("text/html; charset=Shift_JIS", b"\x93\xfa\x96\x7b\x8c\xea\x82\xa0ok");
+        assert_eq!(shift_jis.text_utf8(), "日本語あok");
+
+        // 无法识别的编码按 UTF-8 有损解码，结果仍是有效的 UTF-8
+        assert_eq!(response("text/plain; charset=x-unknown", b"\xffok").text_utf8(), "\u{FFFD}ok");
+    }
+
+    #[tokio::test]
+    async fn test_json_utf16le_charset() {
+        #[derive(serde::Deserialize, Debug, PartialEq)]
+        struct Greeting {
+            text: String,
+        }
+
+        let body: Vec<u8> = "{\"text\":\"你好\"}".encode_utf16().flat_map(u16::to_le_bytes).collect();
+        let mut raw = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json; charset=utf-16le\r\nContent-Length: {}\r\n\r\n",
+            body.len()
+        )
+        .into_bytes();
+        raw.extend_from_slice(&body);
+
+        let greeting: Greeting = Response::from_raw_bytes(raw.clone()).unwrap().json().await.unwrap();
+        assert_eq!(greeting, Greeting { text: "你好".to_string() });
+        let value = Response::from_raw_bytes(raw).unwrap().json_value().unwrap();
+        assert_eq!(value["text"], "你好");
+
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 17\r\n\r\n\xef\xbb\xbf{\"text\":\"hi\"}".to_vec();
+        let greeting: Greeting = Response::from_raw_bytes(raw).unwrap().json().await.unwrap();
+        assert_eq!(greeting.text, "hi");
+
+        let raw = b"HTTP/1.1 200 OK\r\n\r\n{\"text\":".to_vec();
+        let error = Response::from_raw_bytes(raw).unwrap().json::<Greeting>().await.unwrap_err();
+        assert!(matches!(error, Error::Serialization(_)), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_text_strips_bom() {
+        let mut raw = b"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\r\n".to_vec();
+        raw.extend_from_slice(&[0xEF, 0xBB, 0xBF]);
+        raw.extend_from_slice(b"{\"a\":1}");
+        let response = Response::from_raw_bytes(raw).unwrap();
+        assert_eq!(response.text().await.unwrap(), "{\"a\":1}");
+
+        let mut raw = b"HTTP/1.1 200 OK\r\n\r\n".to_vec();
+        raw.extend_from_slice(&[0xFF, 0xFE, b'h', 0, b'i', 0]);
+        let response = Response::from_raw_bytes(raw).unwrap();
+        assert_eq!(response.text().await.unwrap(), "hi");
+
+        let mut raw = b"HTTP/1.1 200 OK\r\n\r\n".to_vec();
+        raw.extend_from_slice(&[0xFE, 0xFF, 0, b'h', 0, b'i']);
+        let response = Response::from_raw_bytes(raw).unwrap();
+        assert_eq!(response.text().await.unwrap(), "hi");
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=shift_jis\r\n\r\n\x93\xfa\x96\x7b".to_vec();
+        assert_eq!(Response::from_raw_bytes(raw).unwrap().text().await.unwrap(), "日本");
+
+        let raw = b"HTTP/1.1 200 OK\r\n\r\nna\xefve".to_vec();
+        assert!(Response::from_raw_bytes(raw).unwrap().text().await.is_err());
+    }
+
     #[test]
     fn test_binary_response_body() {
         // 模拟二进制数据（包含非UTF-8字节）
@@ -318,6 +1543,298 @@ mod tests {
         assert!(response.is_success());
     }
 
+    #[tokio::test]
+    async fn test_read_response_content_length() {
+        let mut reader: &[u8] = b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nHelloNEXT";
+        let raw = read_response(&mut reader, &Method::GET).await.unwrap();
+        assert_eq!(raw, b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nHello");
+    }
+
+    #[tokio::test]
+    async fn test_content_length_zero_ignores_following_bytes() {
+        let data = b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\nHTTP/1.1 200 OK\r\nContent-Length: 4\r\n\r\nnext";
+        let mut reader: &[u8] = data;
+        let raw = read_response(&mut reader, &Method::GET).await.unwrap();
+        assert_eq!(raw, b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n");
+
+        let response = Response::from_raw_bytes(data.to_vec()).unwrap();
+        assert!(response.body.is_empty());
+
+        let response = Response::from_raw_bytes(b"HTTP/1.1 200 OK\r\nContent-Length: 3\r\n\r\nabcdef".to_vec()).unwrap();
+        assert_eq!(response.body, b"abc");
+    }
+
+    #[tokio::test]
+    async fn test_read_response_chunked() {
+        let data = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nHello\r\n0\r\nX-Check: 1\r\n\r\n";
+        let mut reader: &[u8] = data;
+        let raw = read_response(&mut reader, &Method::GET).await.unwrap();
+        assert_eq!(raw, data);
+
+        let response = Response::from_raw_bytes(raw).unwrap();
+        assert_eq!(response.body, b"Hello");
+        assert_eq!(response.trailers().get("x-check").unwrap(), "1");
+    }
+
+    #[test]
+    fn test_announced_trailers() {
+        let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\nTrailer: Grpc-Status, grpc-message\r\n\r\n2\r\nok\r\n0\r\ngrpc-status: 0\r\n\r\n".to_vec();
+        let response = Response::from_raw_bytes(raw).unwrap();
+        assert_eq!(response.announced_trailers(), vec!["grpc-status", "grpc-message"]);
+        assert_eq!(response.trailers().get("grpc-status").unwrap(), "0");
+        assert_eq!(response.missing_trailers(), vec!["grpc-message"]);
+    }
+
+    #[tokio::test]
+    async fn test_read_response_until_close() {
+        let data = b"HTTP/1.0 200 OK\r\nContent-Type: text/plain\r\n\r\nall the rest";
+        let mut reader: &[u8] = data;
+        let raw = read_response(&mut reader, &Method::GET).await.unwrap();
+        assert_eq!(raw, data);
+    }
+
+    #[tokio::test]
+    async fn test_read_response_head_and_no_content() {
+        let mut reader: &[u8] = b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\n\r\n";
+        let raw = read_response(&mut reader, &Method::HEAD).await.unwrap();
+        assert!(Response::from_raw_bytes(raw).unwrap().body.is_empty());
+
+        let mut reader: &[u8] = b"HTTP/1.1 204 No Content\r\n\r\nHTTP/1.1 200 OK\r\n";
+        let raw = read_response(&mut reader, &Method::GET).await.unwrap();
+        assert_eq!(raw, b"HTTP/1.1 204 No Content\r\n\r\n");
+    }
+
+    #[tokio::test]
+    async fn test_chunked_wins_over_content_length() {
+        let data = b"HTTP/1.1 200 OK\r\nContent-Length: 3\r\nTransfer-Encoding: chunked\r\n\r\n6\r\nHello \r\n6\r\nWorld!\r\n0\r\n\r\n";
+        let mut reader: &[u8] = data;
+        let raw = read_response(&mut reader, &Method::GET).await.unwrap();
+        assert_eq!(raw, data);
+
+        let response = Response::from_raw_bytes(raw).unwrap();
+        assert_eq!(response.body, b"Hello World!");
+        assert_eq!(response.content_length(), None);
+    }
+
+    #[test]
+    fn test_json_value() {
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\r\n{\"a\":1}".to_vec();
+        let value = Response::from_raw_bytes(raw).unwrap().json_value().unwrap();
+        assert_eq!(value["a"], 1);
+
+        let raw = b"HTTP/1.1 200 OK\r\n\r\n{\"a\":\n  1,]".to_vec();
+        let error = Response::from_raw_bytes(raw).unwrap().json_value().unwrap_err();
+        assert!(matches!(error, Error::Serialization(ref message) if message.contains("byte offset 10")), "{}", error);
+    }
+
+    #[test]
+    fn test_display_is_language_neutral() {
+        let raw = b"HTTP/1.1 404 Not Found\r\nContent-Type: text/plain\r\nContent-Length: 4\r\n\r\ngone".to_vec();
+        let response = Response::from_raw_bytes(raw).unwrap();
+
+        assert_eq!(
+            response.to_string(),
+            "HTTP/1.1 404 Not Found\ncontent-type: text/plain\ncontent-length: 4\nbody-length: 4"
+        );
+        assert!(response.summary().contains("客户端错误"));
+    }
+
+    #[test]
+    fn test_to_raw_bytes_roundtrip_binary_body() {
+        let body: Vec<u8> = (0..=255u8).rev().collect();
+        let mut raw = b"HTTP/1.1 200 OK\r\nContent-Type: application/octet-stream\r\nX-Name: caf\xe9\r\n".to_vec();
+        raw.extend_from_slice(format!("Content-Length: {}\r\n\r\n", body.len()).as_bytes());
+        raw.extend_from_slice(&body);
+
+        let response = Response::from_raw_bytes(raw).unwrap();
+        let recorded = response.to_raw_bytes();
+        let replayed = Response::from_raw_bytes(recorded).unwrap();
+        assert_eq!(replayed.body, body);
+        assert_eq!(replayed.status_code, 200);
+        assert_eq!(replayed.headers, response.headers);
+        assert_eq!(replayed.raw_header_values.get("x-name").unwrap(), &vec![b'c', b'a', b'f', 0xe9]);
+    }
+
+    #[test]
+    fn test_to_raw_bytes_keeps_png_body() {
+        let mut png = b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR".to_vec();
+        png.extend_from_slice(&[0x00, 0xff, 0xfe, 0x80, 0x0d, 0x0a]);
+        let mut raw = format!("HTTP/1.1 200 OK\r\nContent-Type: image/png\r\nContent-Length: {}\r\n\r\n", png.len()).into_bytes();
+        raw.extend_from_slice(&png);
+
+        let response = Response::from_raw_bytes(raw).unwrap();
+        let recorded = response.to_raw_bytes();
+        assert!(recorded.ends_with(&png));
+        assert_eq!(Response::from_raw_bytes(recorded).unwrap().body, png);
+
+        // 二进制响应体无法无损表示为字符串，UTF-8 响应体则逐字节一致
+        assert!(response.to_raw_string().contains('\u{FFFD}'));
+        let text = Response::from_raw_bytes(b"HTTP/1.1 200 OK\r\nContent-Length: 4\r\n\r\ntext".to_vec()).unwrap();
+        assert_eq!(text.to_raw_string(), "HTTP/1.1 200 OK\r\nContent-Length: 4\r\n\r\ntext");
+    }
+
+    #[test]
+    fn test_to_raw_string_roundtrip_chunked() {
+        let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n6\r\nHello \r\n6\r\nWorld!\r\n0\r\n\r\n";
+        let response = Response::from_raw_bytes(raw.to_vec()).unwrap();
+
+        let text = response.to_raw_string();
+        assert_eq!(text, "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\nc\r\nHello World!\r\n0\r\n\r\n");
+        let replayed = Response::from_raw_bytes(text.into_bytes()).unwrap();
+        assert_eq!(replayed.body, b"Hello World!");
+        assert_eq!(replayed.headers, response.headers);
+    }
+
+    #[test]
+    fn test_to_raw_bytes_roundtrip_chunked_gzip() {
+        let compressed = crate::decompression::compress(b"\x00\x01compressed\xff", Compression::Gzip).unwrap();
+        let mut raw = format!(
+            "HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\nTransfer-Encoding: chunked\r\n\r\n{:x}\r\n",
+            compressed.len()
+        )
+        .into_bytes();
+        raw.extend_from_slice(&compressed);
+        raw.extend_from_slice(b"\r\n0\r\nX-Checksum: abc\r\n\r\n");
+
+        let response = Response::from_raw_bytes(raw).unwrap();
+        let replayed = Response::from_raw_bytes(response.to_raw_bytes()).unwrap();
+        assert_eq!(replayed.body, compressed);
+        assert_eq!(replayed.headers, response.headers);
+        assert_eq!(replayed.trailers.get("x-checksum").map(String::as_str), Some("abc"));
+
+        // 解压后的响应不再声明 Content-Encoding，同样可以往返
+        let mut decoded = replayed;
+        decoded.decompress_body(Decoders::default()).unwrap();
+        let replayed = Response::from_raw_bytes(decoded.to_raw_bytes()).unwrap();
+        assert_eq!(replayed.body, b"\x00\x01compressed\xff");
+        assert_eq!(replayed.get_header("content-encoding"), None);
+    }
+
+    #[test]
+    fn test_from_raw_bytes_leaves_gzip_compressed() {
+        let compressed = crate::decompression::compress(b"parsed, not decoded", Compression::Gzip).unwrap();
+        let mut raw = format!("HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\nContent-Length: {}\r\n\r\n", compressed.len()).into_bytes();
+        raw.extend_from_slice(&compressed);
+
+        let mut response = Response::from_raw_bytes(raw).unwrap();
+        assert_eq!(response.body, compressed);
+        assert_eq!(response.get_header("content-encoding").map(String::as_str), Some("gzip"));
+
+        response.decompress_body(Decoders { gzip: false, ..Decoders::default() }).unwrap();
+        assert_eq!(response.body, compressed);
+
+        response.decompress_body(Decoders::default()).unwrap();
+        assert_eq!(response.body, b"parsed, not decoded");
+        assert_eq!(response.get_header("content-encoding"), None);
+        assert_eq!(response.content_length(), None);
+    }
+
+    #[test]
+    fn test_into_parts_roundtrip() {
+        let mut raw = b"HTTP/1.1 404 Not Found\r\nContent-Type: text/plain\r\nX-Name: caf\xe9\r\nContent-Length: 4\r\n\r\n".to_vec();
+        raw.extend_from_slice(b"gone");
+        let response = Response::from_raw_bytes(raw).unwrap();
+
+        let (status, version, status_message, headers, body) = response.clone().into_parts();
+        assert_eq!(status.code, 404);
+        assert_eq!(version, "HTTP/1.1");
+        assert_eq!(status_message, "Not Found");
+        assert_eq!(headers.get("content-type").unwrap(), "text/plain");
+        assert_eq!(body, b"gone");
+
+        let rebuilt = Response::from_parts(status, version, status_message, headers, body);
+        assert_eq!(rebuilt.version, response.version);
+        assert_eq!(rebuilt.status_code, response.status_code);
+        assert_eq!(rebuilt.status_message, response.status_message);
+        assert_eq!(rebuilt.headers, response.headers);
+        assert_eq!(rebuilt.raw_header_values, response.raw_header_values);
+        assert_eq!(rebuilt.body, response.body);
+    }
+
+    #[test]
+    fn test_from_parts_keeps_reason_phrase() {
+        let response = Response::from_raw_bytes(b"HTTP/1.1 200 Everything Fine\r\nContent-Length: 0\r\n\r\n".to_vec()).unwrap();
+        let (status, version, status_message, headers, body) = response.into_parts();
+        let rebuilt = Response::from_parts(status, version, status_message, headers, body);
+        assert_eq!(rebuilt.status_message, "Everything Fine");
+
+        let rebuilt = Response::from_parts(StatusCode { code: 404 }, "HTTP/1.1".to_string(), String::new(), HeaderMap::new(), Vec::new());
+        assert_eq!(rebuilt.status_message, "");
+    }
+
+    #[test]
+    fn test_empty_gzip_body_not_decompressed() {
+        // HEAD 响应：Content-Encoding 描述的是 GET 响应体，实际响应体为空
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\nContent-Length: 1234\r\n\r\n".to_vec();
+        let response = Response::from_raw_bytes(raw).unwrap();
+        assert!(response.body.is_empty());
+
+        let raw = b"HTTP/1.1 304 Not Modified\r\nContent-Encoding: gzip\r\nTransfer-Encoding: chunked\r\n\r\n".to_vec();
+        let response = Response::from_raw_bytes(raw).unwrap();
+        assert_eq!(response.status_code, 304);
+        assert!(response.body.is_empty());
+    }
+
+    #[test]
+    fn test_decompress_sniffed_gzip_without_content_encoding() {
+        let compressed = crate::decompression::compress(b"mislabeled body", Compression::Gzip).unwrap();
+        let mut raw = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n", compressed.len()).into_bytes();
+        raw.extend_from_slice(&compressed);
+
+        let mut response = Response::from_raw_bytes(raw).unwrap();
+        assert_eq!(response.body, compressed);
+        assert!(response.decompress_sniffed());
+        assert_eq!(response.body, b"mislabeled body");
+        assert_eq!(response.content_length(), None);
+
+        // 非压缩数据保持不变
+        let mut response = Response::from_raw_bytes(b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nplain".to_vec()).unwrap();
+        assert!(!response.decompress_sniffed());
+        assert_eq!(response.body, b"plain");
+        assert_eq!(response.content_length(), Some(5));
+
+        // 看起来像 gzip 但无法解压时保留原始数据
+        let mut response = Response::from_raw_bytes(b"HTTP/1.1 200 OK\r\nContent-Length: 4\r\n\r\n\x1f\x8bxx".to_vec()).unwrap();
+        assert!(!response.decompress_sniffed());
+        assert_eq!(response.body, b"\x1f\x8bxx");
+    }
+
+    #[test]
+    fn test_duplicate_content_length() {
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\nContent-Length: 3\r\n\r\nhello".to_vec();
+        let error = Response::from_raw_bytes(raw).unwrap_err();
+        assert!(matches!(error, Error::HttpParse(ref message) if message == "conflicting Content-Length headers"));
+
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\ncontent-length: 5\r\n\r\nhello".to_vec();
+        let response = Response::from_raw_bytes(raw).unwrap();
+        assert_eq!(response.body, b"hello");
+        assert_eq!(response.get_header("content-length").unwrap(), "5");
+    }
+
+    #[test]
+    fn test_too_many_response_headers() {
+        let mut raw = b"HTTP/1.1 200 OK\r\n".to_vec();
+        for i in 0..=DEFAULT_MAX_HEADER_COUNT {
+            raw.extend_from_slice(format!("X-H{}: v\r\n", i).as_bytes());
+        }
+        raw.extend_from_slice(b"Content-Length: 0\r\n\r\n");
+
+        let error = Response::from_raw_bytes(raw.clone()).unwrap_err();
+        assert!(matches!(error, Error::HttpParse(ref message) if message == "too many response headers"));
+
+        let response = Response::from_raw_bytes_with_max_headers(raw.clone(), DEFAULT_MAX_HEADER_COUNT + 2).unwrap();
+        assert_eq!(response.headers.len(), DEFAULT_MAX_HEADER_COUNT + 2);
+        assert!(Response::from_raw_bytes_with_max_headers(raw, DEFAULT_MAX_HEADER_COUNT + 1).is_err());
+    }
+
+    #[test]
+    fn test_compress_encoding_errors() {
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Encoding: compress\r\nContent-Length: 4\r\n\r\n\x1f\x9d\x90\x41".to_vec();
+        let mut response = Response::from_raw_bytes(raw).unwrap();
+        let error = response.decompress_body(Decoders::default()).unwrap_err();
+        assert!(matches!(error, Error::Decompression(ref message) if message == "unsupported content-encoding: compress"));
+    }
+
     #[test]
     fn test_chunked_gzip_response() {
         use flate2::write::GzEncoder;
@@ -343,12 +1860,15 @@ mod tests {
         let mut raw_bytes = raw.into_bytes();
         raw_bytes.extend(&chunked_data);
 
-        let response = Response::from_raw_bytes(raw_bytes).unwrap();
+        let mut response = Response::from_raw_bytes(raw_bytes).unwrap();
 
         assert_eq!(response.version, "HTTP/1.1");
         assert_eq!(response.status_code, 200);
         assert_eq!(response.get_header("transfer-encoding").unwrap(), "chunked");
         assert_eq!(response.get_header("content-encoding").unwrap(), "gzip");
+        assert_eq!(response.body, compressed_data);
+
+        response.decompress_body(Decoders::default()).unwrap();
         assert_eq!(response.body, original_data);
         assert!(response.is_success());
     }
@@ -378,12 +1898,15 @@ mod tests {
         let mut raw_bytes = raw.into_bytes();
         raw_bytes.extend(&chunked_data);
 
-        let response = Response::from_raw_bytes(raw_bytes).unwrap();
+        let mut response = Response::from_raw_bytes(raw_bytes).unwrap();
 
         assert_eq!(response.version, "HTTP/1.1");
         assert_eq!(response.status_code, 200);
         assert_eq!(response.get_header("transfer-encoding").unwrap(), "chunked");
         assert_eq!(response.get_header("content-encoding").unwrap(), "deflate");
+        assert_eq!(response.body, compressed_data);
+
+        response.decompress_body(Decoders::default()).unwrap();
         assert_eq!(response.body, original_data);
         assert!(response.is_success());
     }
@@ -421,5 +1944,7 @@ mod tests {
         assert_eq!(response.status_code, 200);
         assert_eq!(String::from_utf8(response.body.clone()).unwrap(), "Hello World!");
         assert!(response.is_success());
+        assert_eq!(response.trailers().get("x-trailer").unwrap(), "test");
+        assert!(response.get_header("x-trailer").is_none());
     }
 }
@@ -0,0 +1,85 @@
+//! 请求各阶段耗时
+//!
+//! 连接在建立和读取响应时记录各阶段完成的时刻，客户端在请求结束后
+//! 换算为相对请求开始的耗时（与 curl 的 `time_connect`、`time_starttransfer` 等含义相同）
+
+use std::time::{Duration, Instant};
+
+/// 连接记录的各阶段完成时刻
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConnectionTimings {
+    /// 域名解析完成的时刻（经过代理或使用自定义连接器时为 None）
+    pub dns_resolved: Option<Instant>,
+    /// TCP 连接（经过代理时为隧道）建立完成的时刻
+    pub connected: Option<Instant>,
+    /// TLS 握手完成的时刻，明文连接为 None
+    pub tls_handshaken: Option<Instant>,
+    /// 最近一次读取响应时收到第一个字节的时刻
+    pub first_byte: Option<Instant>,
+}
+
+impl ConnectionTimings {
+    /// 连接刚刚建立（如经过代理的隧道），没有单独的域名解析阶段
+    pub fn connected_now() -> Self {
+        Self {
+            connected: Some(Instant::now()),
+            ..Self::default()
+        }
+    }
+}
+
+/// 单个请求各阶段相对请求开始的耗时
+///
+/// 各阶段的耗时是累计的，满足 `dns <= connect <= tls_handshake <= ttfb <= total`。
+/// 复用已有连接（重定向或预连接）时连接阶段不会发生，对应字段为 None
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timings {
+    /// 域名解析完成
+    pub dns: Option<Duration>,
+    /// TCP 连接建立完成
+    pub connect: Option<Duration>,
+    /// TLS 握手完成
+    pub tls_handshake: Option<Duration>,
+    /// 收到响应的第一个字节
+    pub ttfb: Option<Duration>,
+    /// 收到完整响应
+    pub total: Duration,
+}
+
+impl Timings {
+    /// 根据请求开始时刻和连接记录的时刻计算耗时，早于请求开始的时刻（如预连接）被忽略
+    pub fn from_connection(start: Instant, connection: ConnectionTimings, end: Instant) -> Self {
+        let since_start = |instant: Option<Instant>| instant.and_then(|instant| instant.checked_duration_since(start));
+        Self {
+            dns: since_start(connection.dns_resolved),
+            connect: since_start(connection.connected),
+            tls_handshake: since_start(connection.tls_handshaken),
+            ttfb: since_start(connection.first_byte),
+            total: end.saturating_duration_since(start),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timings_relative_to_start() {
+        let before = Instant::now();
+        let start = before + Duration::from_millis(5);
+        let connection = ConnectionTimings {
+            dns_resolved: Some(before),
+            connected: Some(start + Duration::from_millis(10)),
+            tls_handshaken: None,
+            first_byte: Some(start + Duration::from_millis(30)),
+        };
+
+        let timings = Timings::from_connection(start, connection, start + Duration::from_millis(40));
+        assert_eq!(timings.dns, None);
+        assert_eq!(timings.connect, Some(Duration::from_millis(10)));
+        assert_eq!(timings.tls_handshake, None);
+        assert_eq!(timings.ttfb, Some(Duration::from_millis(30)));
+        assert_eq!(timings.total, Duration::from_millis(40));
+    }
+}
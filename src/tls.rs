@@ -33,10 +33,8 @@ fn init_crypto_provider_internal() -> Result<()> {
     // 首先尝试安装 aws-lc-rs provider（推荐）
     let provider = rustls::crypto::aws_lc_rs::default_provider();
     match rustls::crypto::CryptoProvider::install_default(provider) {
-        Ok(()) => return Ok(()),
-        Err(e) => {
-            return Err(Error::other(format!("Failed to install aws-lc-rs crypto provider: {:?}", e)));
-        }
+        Ok(()) => Ok(()),
+        Err(e) => Err(Error::other(format!("Failed to install aws-lc-rs crypto provider: {:?}", e))),
     }
 }
 
@@ -3,6 +3,8 @@
 //! 提供各种辅助函数和工具
 
 use crate::error::{Error, Result};
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use url::Url;
 
 #[derive(Debug, PartialEq)]
@@ -14,6 +16,22 @@ pub struct ParsedUrl {
     pub is_https: bool,
 }
 
+impl ParsedUrl {
+    /// 获取协议的默认端口
+    pub fn default_port(&self) -> u16 {
+        if self.is_https { 443 } else { 80 }
+    }
+
+    /// 生成 Host 请求头的值，端口不是协议默认端口时附带端口
+    pub fn host_header(&self) -> String {
+        if self.port == self.default_port() {
+            self.hostname.clone()
+        } else {
+            format!("{}:{}", self.hostname, self.port)
+        }
+    }
+}
+
 /// 解析URL为主机和端口
 pub fn parse_host_port(url: &str) -> Result<ParsedUrl> {
     let parsed_url = url
@@ -42,3 +60,269 @@ pub fn parse_host_port(url: &str) -> Result<ParsedUrl> {
         is_https,
     })
 }
+
+/// 将相对路径或 URL 按 RFC 3986 相对于 `base` 解析为绝对 URL
+///
+/// 与浏览器行为一致：`base` 的最后一段路径不以 `/` 结尾时会被替换，
+/// 例如 `https://api.example.com/v1` 与 `users` 解析为 `https://api.example.com/users`
+pub fn resolve_url(base: &Url, reference: &str) -> Result<Url> {
+    base.join(reference)
+        .map_err(|e| Error::url_parse(format!("Invalid URL reference {}: {}", reference, e)))
+}
+
+/// 将参数编码为以 `?` 开头的查询字符串，键和值都进行百分号编码，参数为空时返回空字符串
+///
+/// HashMap 的遍历顺序不确定，因此参数顺序也不确定
+pub fn build_query_string(params: &HashMap<String, String>) -> String {
+    if params.is_empty() {
+        return String::new();
+    }
+
+    let pairs: Vec<String> = params
+        .iter()
+        .map(|(key, value)| format!("{}={}", urlencoding::encode(key), urlencoding::encode(value)))
+        .collect();
+    format!("?{}", pairs.join("&"))
+}
+
+/// 标准 Base64 编码（带填充）
+pub fn base64_encode(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b0 = chunk[0] as u32;
+        let b1 = chunk.get(1).copied().unwrap_or(0) as u32;
+        let b2 = chunk.get(2).copied().unwrap_or(0) as u32;
+        let triple = (b0 << 16) | (b1 << 8) | b2;
+
+        encoded.push(ALPHABET[(triple >> 18) as usize & 0x3F] as char);
+        encoded.push(ALPHABET[(triple >> 12) as usize & 0x3F] as char);
+        encoded.push(if chunk.len() > 1 { ALPHABET[(triple >> 6) as usize & 0x3F] as char } else { '=' });
+        encoded.push(if chunk.len() > 2 { ALPHABET[triple as usize & 0x3F] as char } else { '=' });
+    }
+    encoded
+}
+
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// 解析 HTTP 日期
+///
+/// 支持 IMF-fixdate（`Sun, 06 Nov 1994 08:49:37 GMT`）、
+/// RFC 850（`Sunday, 06-Nov-94 08:49:37 GMT`）和 asctime（`Sun Nov  6 08:49:37 1994`）三种格式
+pub fn parse_http_date(value: &str) -> Option<SystemTime> {
+    let value = value.trim();
+    let (day, month, year, time) = if let Some((_, rest)) = value.split_once(", ") {
+        if rest.contains('-') {
+            // RFC 850: 06-Nov-94 08:49:37 GMT
+            let mut parts = rest.split_whitespace();
+            let date = parts.next()?;
+            let time = parts.next()?;
+            if parts.next()? != "GMT" {
+                return None;
+            }
+            let mut date_parts = date.split('-');
+            let day: u32 = date_parts.next()?.parse().ok()?;
+            let month = date_parts.next()?;
+            let year: i64 = date_parts.next()?.parse().ok()?;
+            // 两位年份：按 RFC 7231 规则解释，50 及以上视为 19xx
+            let year = if year < 50 { 2000 + year } else if year < 100 { 1900 + year } else { year };
+            (day, month, year, time)
+        } else {
+            // IMF-fixdate: 06 Nov 1994 08:49:37 GMT
+            let parts: Vec<&str> = rest.split_whitespace().collect();
+            if parts.len() != 5 || parts[4] != "GMT" {
+                return None;
+            }
+            (parts[0].parse().ok()?, parts[1], parts[2].parse().ok()?, parts[3])
+        }
+    } else {
+        // asctime: Sun Nov  6 08:49:37 1994
+        let parts: Vec<&str> = value.split_whitespace().collect();
+        if parts.len() != 5 {
+            return None;
+        }
+        (parts[2].parse().ok()?, parts[1], parts[4].parse().ok()?, parts[3])
+    };
+
+    let month = MONTHS.iter().position(|m| *m == month)? as u32 + 1;
+    if !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let mut time_parts = time.split(':');
+    let hour: u64 = time_parts.next()?.parse().ok()?;
+    let minute: u64 = time_parts.next()?.parse().ok()?;
+    let second: u64 = time_parts.next()?.parse().ok()?;
+    if time_parts.next().is_some() || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+
+    let days = days_from_civil(year, month, day);
+    if days < 0 {
+        return None;
+    }
+    let seconds = days as u64 * 86400 + hour * 3600 + minute * 60 + second;
+    Some(UNIX_EPOCH + Duration::from_secs(seconds))
+}
+
+/// 将时间格式化为 IMF-fixdate 格式的 HTTP 日期
+pub fn format_http_date(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let days = (seconds / 86400) as i64;
+    let (year, month, day) = civil_from_days(days);
+    let seconds_of_day = seconds % 86400;
+
+    // 1970-01-01 是星期四
+    let weekday = WEEKDAYS[((days + 3) % 7) as usize];
+
+    format!(
+        "{}, {:02} {} {} {:02}:{:02}:{:02} GMT",
+        weekday,
+        day,
+        MONTHS[month as usize - 1],
+        year,
+        seconds_of_day / 3600,
+        seconds_of_day % 3600 / 60,
+        seconds_of_day % 60
+    )
+}
+
+/// 文件扩展名与 MIME 类型的对应表
+const MIME_TYPES: [(&str, &str); 22] = [
+    ("json", "application/json"),
+    ("xml", "application/xml"),
+    ("pdf", "application/pdf"),
+    ("zip", "application/zip"),
+    ("gz", "application/gzip"),
+    ("wasm", "application/wasm"),
+    ("js", "text/javascript"),
+    ("txt", "text/plain"),
+    ("html", "text/html"),
+    ("htm", "text/html"),
+    ("css", "text/css"),
+    ("csv", "text/csv"),
+    ("md", "text/markdown"),
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+    ("svg", "image/svg+xml"),
+    ("mp3", "audio/mpeg"),
+    ("mp4", "video/mp4"),
+    ("webm", "video/webm"),
+];
+
+/// 根据文件扩展名（不区分大小写）推断 MIME 类型，无法识别时返回 `application/octet-stream`
+pub fn guess_mime_type(path: &std::path::Path) -> &'static str {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .and_then(|extension| {
+            MIME_TYPES
+                .iter()
+                .find(|(known, _)| known.eq_ignore_ascii_case(extension))
+                .map(|(_, mime)| *mime)
+        })
+        .unwrap_or("application/octet-stream")
+}
+
+/// 计算公历日期距 1970-01-01 的天数
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = month as i64;
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// 根据距 1970-01-01 的天数计算公历日期
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_query_string() {
+        assert_eq!(build_query_string(&HashMap::new()), "");
+
+        let params = HashMap::from([("q".to_string(), "rust http".to_string())]);
+        assert_eq!(build_query_string(&params), "?q=rust%20http");
+    }
+
+    #[test]
+    fn test_resolve_url() {
+        let base = Url::parse("https://api.example.com/v1/").unwrap();
+        assert_eq!(resolve_url(&base, "users/1").unwrap().as_str(), "https://api.example.com/v1/users/1");
+        assert_eq!(resolve_url(&base, "/health").unwrap().as_str(), "https://api.example.com/health");
+        assert_eq!(resolve_url(&base, "?page=2").unwrap().as_str(), "https://api.example.com/v1/?page=2");
+        assert_eq!(resolve_url(&base, "http://other.example/x").unwrap().as_str(), "http://other.example/x");
+
+        let base = Url::parse("https://api.example.com/v1").unwrap();
+        assert_eq!(resolve_url(&base, "users").unwrap().as_str(), "https://api.example.com/users");
+    }
+
+    #[test]
+    fn test_guess_mime_type() {
+        use std::path::Path;
+
+        assert_eq!(guess_mime_type(Path::new("data.json")), "application/json");
+        assert_eq!(guess_mime_type(Path::new("/tmp/Photo.PNG")), "image/png");
+        assert_eq!(guess_mime_type(Path::new("archive.unknown")), "application/octet-stream");
+        assert_eq!(guess_mime_type(Path::new("Makefile")), "application/octet-stream");
+        assert_eq!(guess_mime_type(Path::new(".json")), "application/octet-stream");
+    }
+
+    #[test]
+    fn test_parse_http_date_formats() {
+        let expected = UNIX_EPOCH + Duration::from_secs(784111777);
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"), Some(expected));
+        assert_eq!(parse_http_date("Sunday, 06-Nov-94 08:49:37 GMT"), Some(expected));
+        assert_eq!(parse_http_date("Sun Nov  6 08:49:37 1994"), Some(expected));
+        assert_eq!(parse_http_date("not a date"), None);
+    }
+
+    #[test]
+    fn test_host_header() {
+        assert_eq!(parse_host_port("http://x.com:8443/").unwrap().host_header(), "x.com:8443");
+        assert_eq!(parse_host_port("https://x.com:80/").unwrap().host_header(), "x.com:80");
+        assert_eq!(parse_host_port("http://x.com/").unwrap().host_header(), "x.com");
+        assert_eq!(parse_host_port("https://x.com:443/").unwrap().host_header(), "x.com");
+    }
+
+    #[test]
+    fn test_base64_encode() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+        assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn test_format_http_date() {
+        let time = UNIX_EPOCH + Duration::from_secs(784111777);
+        assert_eq!(format_http_date(time), "Sun, 06 Nov 1994 08:49:37 GMT");
+        assert_eq!(parse_http_date(&format_http_date(time)), Some(time));
+    }
+}
@@ -0,0 +1,117 @@
+//! WebSocket 升级支持
+//!
+//! 负责发送 HTTP/1.1 Upgrade 握手并校验服务器响应，
+//! 握手完成后将底层连接交给 WebSocket 库处理帧协议
+
+use crate::connection::BoxedStream;
+use crate::error::{Error, Result};
+use crate::response::Response;
+use crate::utils::base64_encode;
+use ring::digest::{digest, SHA1_FOR_LEGACY_USE_ONLY};
+use ring::rand::{SecureRandom, SystemRandom};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// RFC 6455 中用于计算 Sec-WebSocket-Accept 的固定 GUID
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// 握手响应头的最大长度
+const MAX_HANDSHAKE_RESPONSE_SIZE: usize = 16 * 1024;
+
+/// 完成升级的 WebSocket 连接
+pub struct WebSocketUpgrade {
+    /// 服务器返回的 101 Switching Protocols 响应
+    pub response: Response,
+    /// 已升级的底层连接（TCP 或 TLS），由 WebSocket 库驱动帧收发
+    pub stream: BoxedStream,
+}
+
+/// 生成随机的 Sec-WebSocket-Key
+pub fn generate_key() -> Result<String> {
+    let mut nonce = [0u8; 16];
+    SystemRandom::new()
+        .fill(&mut nonce)
+        .map_err(|_| Error::other("Failed to generate WebSocket key"))?;
+    Ok(base64_encode(&nonce))
+}
+
+/// 根据 Sec-WebSocket-Key 计算期望的 Sec-WebSocket-Accept
+pub fn accept_key(key: &str) -> String {
+    let hash = digest(&SHA1_FOR_LEGACY_USE_ONLY, format!("{}{}", key, WEBSOCKET_GUID).as_bytes());
+    base64_encode(hash.as_ref())
+}
+
+/// 在已建立的连接上发送握手请求并校验响应
+///
+/// 逐字节读取响应头，确保握手响应之后的 WebSocket 帧数据仍留在连接中
+pub(crate) async fn handshake(stream: &mut BoxedStream, request: &str, key: &str) -> Result<Response> {
+    stream.write_all(request.as_bytes()).await
+        .map_err(|e| Error::other(format!("Failed to write WebSocket handshake: {}", e)))?;
+    stream.flush().await
+        .map_err(|e| Error::other(format!("Failed to flush WebSocket handshake: {}", e)))?;
+
+    let mut raw_response = Vec::new();
+    let mut byte = [0u8; 1];
+    while !raw_response.ends_with(b"\r\n\r\n") {
+        let n = stream.read(&mut byte).await
+            .map_err(|e| Error::other(format!("Failed to read WebSocket handshake: {}", e)))?;
+        if n == 0 {
+            return Err(Error::response("Connection closed during WebSocket handshake"));
+        }
+        raw_response.push(byte[0]);
+
+        if raw_response.len() > MAX_HANDSHAKE_RESPONSE_SIZE {
+            return Err(Error::response("WebSocket handshake response too large"));
+        }
+    }
+
+    let response = Response::from_raw_bytes(raw_response)?;
+    validate_handshake_response(&response, key)?;
+    Ok(response)
+}
+
+/// 校验服务器的握手响应
+fn validate_handshake_response(response: &Response, key: &str) -> Result<()> {
+    if response.status_code != 101 {
+        return Err(Error::http_status(
+            response.status_code,
+            format!("WebSocket upgrade rejected: {}", response.status_message),
+        ));
+    }
+
+    let upgrade = response.get_header("upgrade").map(|v| v.to_lowercase());
+    if upgrade.as_deref() != Some("websocket") {
+        return Err(Error::response("Missing or invalid Upgrade header in WebSocket handshake"));
+    }
+
+    let connection_upgrade = response
+        .get_header("connection")
+        .is_some_and(|v| v.split(',').any(|token| token.trim().eq_ignore_ascii_case("upgrade")));
+    if !connection_upgrade {
+        return Err(Error::response("Missing or invalid Connection header in WebSocket handshake"));
+    }
+
+    let expected = accept_key(key);
+    if response.get_header("sec-websocket-accept").map(|v| v.trim()) != Some(expected.as_str()) {
+        return Err(Error::response("Invalid Sec-WebSocket-Accept in WebSocket handshake"));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accept_key() {
+        // RFC 6455 第 1.3 节中的示例
+        assert_eq!(accept_key("dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    }
+
+    #[test]
+    fn test_generate_key() {
+        let key = generate_key().unwrap();
+        assert_eq!(key.len(), 24);
+        assert_ne!(key, generate_key().unwrap());
+    }
+}
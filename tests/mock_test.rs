@@ -0,0 +1,44 @@
+use rr::request::Method;
+use rr::{HttpClient, MockResponse};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct User {
+    id: u32,
+    name: String,
+}
+
+// 使用模拟传输层，无需访问网络
+#[tokio::test]
+async fn test_mock_json_response() {
+    let client = HttpClient::builder()
+        .with_mock(vec![
+            MockResponse::new(Method::GET, "https://api.example.com/users/1", 200)
+                .header("Content-Type", "application/json")
+                .body(r#"{"id":1,"name":"alice"}"#),
+        ])
+        .build()
+        .expect("构建客户端失败");
+
+    let response = client
+        .get("https://api.example.com/users/1")
+        .send()
+        .await
+        .expect("请求失败");
+
+    assert!(response.is_success());
+    assert_eq!(response.content_type().unwrap(), "application/json");
+
+    let user: User = response.json().await.expect("JSON解析失败");
+    assert_eq!(user, User { id: 1, name: "alice".to_string() });
+}
+
+#[tokio::test]
+async fn test_mock_unmatched_request() {
+    let client = HttpClient::builder()
+        .with_mock(vec![MockResponse::new(Method::GET, "https://api.example.com/", 200)])
+        .build()
+        .expect("构建客户端失败");
+
+    assert!(client.post("https://api.example.com/").send().await.is_err());
+}